@@ -1 +1,126 @@
 /* DEFINE ALL OF YOUR CRON ROUTINES HERE */
+use chrono::Utc;
+use mongodb::bson::doc;
+use futures::StreamExt;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::Handler::Post::Publish::publish_post;
+use crate::Handler::Post::Delete::{purge_post, RESTORE_WINDOW_DAYS};
+use crate::Handler::Feed::ForYou::CANDIDATE_WINDOW_DAYS;
+use crate::model::Post::{FeedSeen, PostCore, PostPublishState, PostStat};
+use crate::utils::{comment_rank, view_counter::ViewCounter};
+
+/* Writes the buffered post views to `post_stat` */
+pub async fn flush_post_views(view_counter: &ViewCounter) {
+    let pending = view_counter.take_pending();
+    if pending.is_empty() {
+        return;
+    }
+
+    let db = MongoDB.connect();
+    let collection = db.collection::<PostStat>("post_stat");
+    let now = Utc::now().timestamp_millis();
+
+    let mut failed = std::collections::HashMap::new();
+    for (post_id, count) in pending {
+        let result = collection.update_one(
+            doc!{"uuid": &post_id},
+            doc!{
+                "$inc": {"view_count": count},
+                "$set": {"modified_at": now}
+            },
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            failed.insert(post_id, count);
+        }
+    }
+
+    if !failed.is_empty() {
+        view_counter.restore_pending(failed);
+    }
+}
+
+/* Publishes the scheduled posts whose time has come */
+pub async fn publish_scheduled_posts() {
+    let db = MongoDB.connect();
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find(doc!{
+        "publish_state": PostPublishState::Scheduled.to_string(),
+        "publish_at": {"$lte": Utc::now().timestamp_millis()},
+        "deleted_at": null
+    })
+    .sort(doc!{"publish_at": 1}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return;
+    }
+
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return;
+        }
+
+        // Failures stay scheduled and are retried on the next run
+        let post_core = result.unwrap();
+        if let Err(_) = publish_post(&db, &post_core).await {
+            log::error!("Failed to publish scheduled post {}", post_core.uuid);
+        }
+    }
+}
+
+/* Purges the deleted posts whose restore window has passed */
+pub async fn purge_deleted_posts() {
+    let window = chrono::Duration::days(RESTORE_WINDOW_DAYS).num_milliseconds();
+
+    let db = MongoDB.connect();
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find(doc!{
+        "deleted_at": {"$lte": Utc::now().timestamp_millis() - window}
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return;
+    }
+
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return;
+        }
+
+        // Failures stay deleted and are retried on the next run
+        let post_core = result.unwrap();
+        if let Err(_) = purge_post(&db, &post_core).await {
+            log::error!("Failed to purge deleted post {}", post_core.uuid);
+        }
+    }
+}
+
+/* Seen marks older than the For You window can't hide a candidate anymore */
+pub async fn prune_feed_seen() {
+    let window = chrono::Duration::days(CANDIDATE_WINDOW_DAYS).num_milliseconds();
+
+    let collection = MongoDB.connect().collection::<FeedSeen>("feed_seen");
+    let result = collection.delete_many(doc!{
+        "seen_at": {"$lt": Utc::now().timestamp_millis() - window}
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+    }
+}
+
+/* Ranks the comments written before the `top` order, once at startup */
+pub async fn rank_unranked_comments() {
+    match comment_rank::backfill(&MongoDB.connect()).await {
+        Ok(0) => {},
+        Ok(ranked) => log::info!("Ranked {} comments", ranked),
+        Err(error) => log::error!("{:?}", error),
+    }
+}
\ No newline at end of file
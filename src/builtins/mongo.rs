@@ -1,7 +1,10 @@
 use std::env;
 use std::time::Duration;
-use mongodb::{ Client, ClientSession, Database, error };
-use mongodb::options::{ ClientOptions, ServerAddress };
+use mongodb::{ Client, ClientSession, Database, IndexModel, error };
+use mongodb::bson::{ doc, Document };
+use mongodb::options::{ ClientOptions, IndexOptions, ServerAddress };
+use mongodb::event::{ EventHandler, command::CommandEvent };
+use crate::utils::query_counter;
 
 pub struct MongoDB;
 impl MongoDB {
@@ -25,6 +28,11 @@ impl MongoDB {
     .max_pool_size(Some(256))
     .default_database(Some(app_name.into()))
     .app_name(Some(app_name.into()))
+    .command_event_handler(EventHandler::callback(|event: CommandEvent| {
+      if let CommandEvent::Started(_) = event {
+        query_counter::record();
+      }
+    }))
     .build();
 
     Ok(Client::with_options(options)?)
@@ -51,3 +59,96 @@ impl MongoDB {
     client.database(db_name)
   }
 }
+
+/*
+  Indexes the handlers rely on for uniqueness and lookups.
+  Creating an existing index is a no-op, so this runs on every start.
+*/
+pub async fn create_indexes() -> Result<(), error::Error> {
+  let db = MongoDB.connect();
+
+  let unique = || IndexOptions::builder().unique(true).build();
+
+  // A post is marked seen once per user, reads are limited by `seen_at`
+  db.collection::<Document>("feed_seen").create_indexes(vec![
+    IndexModel::builder()
+    .keys(doc!{"user_id": 1, "post_id": 1})
+    .options(unique())
+    .build(),
+    IndexModel::builder()
+    .keys(doc!{"user_id": 1, "seen_at": 1})
+    .build(),
+  ]).await?;
+
+  // One like per user and post
+  db.collection::<Document>("post_like").create_index(
+    IndexModel::builder()
+    .keys(doc!{"post_id": 1, "liked_by": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  // A post is pinned once, pins of an owner are counted under their lock
+  db.collection::<Document>("post_pin").create_index(
+    IndexModel::builder()
+    .keys(doc!{"owner": 1, "post_id": 1})
+    .options(unique())
+    .build()
+  ).await?;
+  db.collection::<Document>("post_pin_lock").create_index(
+    IndexModel::builder()
+    .keys(doc!{"owner": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  // A user votes for an option once
+  db.collection::<Document>("poll_vote").create_index(
+    IndexModel::builder()
+    .keys(doc!{"poll_id": 1, "user_id": 1, "option": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  // One like per user and profile
+  db.collection::<Document>("account_like").create_index(
+    IndexModel::builder()
+    .keys(doc!{"user_id": 1, "liked_by": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  // One like per user and comment
+  db.collection::<Document>("comment_like").create_index(
+    IndexModel::builder()
+    .keys(doc!{"comment_id": 1, "liked_by": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  // One like per user and reply
+  db.collection::<Document>("reply_like").create_index(
+    IndexModel::builder()
+    .keys(doc!{"reply_id": 1, "liked_by": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  // One bookmark per user and post
+  db.collection::<Document>("post_bookmark").create_index(
+    IndexModel::builder()
+    .keys(doc!{"post_id": 1, "bookmarked_by": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  // One follow per user and tag
+  db.collection::<Document>("tag_follow").create_index(
+    IndexModel::builder()
+    .keys(doc!{"tag": 1, "followed_by": 1})
+    .options(unique())
+    .build()
+  ).await?;
+
+  Ok(())
+}
\ No newline at end of file
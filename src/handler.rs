@@ -35,4 +35,19 @@ pub mod profile;
 pub use profile as Profile;
 
 pub mod video;
-pub use video as Video;
\ No newline at end of file
+pub use video as Video;
+
+pub mod verification;
+pub use verification as Verification;
+
+pub mod feed;
+pub use feed as Feed;
+
+pub mod bookmark;
+pub use bookmark as Bookmark;
+
+pub mod poll;
+pub use poll as Poll;
+
+pub mod tag;
+pub use tag as Tag;
\ No newline at end of file
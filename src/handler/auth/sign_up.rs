@@ -190,6 +190,25 @@ pub async fn task(form_data: web::Json<ReqBody>) -> Result<HttpResponse, Error>
         return Ok(Response::internal_server_error(&error.to_string()));
     }
 
+    //Create account_privacy_setting
+    let collection = db.collection::
+    <Account::AccountPrivacySettings>("account_privacy_setting");
+
+    let mut account_privacy_setting = Account::AccountPrivacySettings::default_for(
+        &user_id
+    );
+    account_privacy_setting.modified_at = now;
+
+    let result = collection.insert_one(
+        account_privacy_setting,
+    ).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
     //creating validation request
     let mut rng = rand::rng();
     let validation_code: u32 = rng.random_range(100000..999999);
@@ -0,0 +1,23 @@
+pub mod add;
+pub use add as Add;
+
+pub mod remove;
+pub use remove as Remove;
+
+pub mod list;
+pub use list as List;
+
+pub mod collection_list;
+pub use collection_list as CollectionList;
+
+pub mod collection_create;
+pub use collection_create as CollectionCreate;
+
+pub mod collection_rename;
+pub use collection_rename as CollectionRename;
+
+pub mod collection_reorder;
+pub use collection_reorder as CollectionReorder;
+
+pub mod collection_delete;
+pub use collection_delete as CollectionDelete;
\ No newline at end of file
@@ -0,0 +1,100 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::mongo;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::{BookmarkCollection, PostBookmark};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    collection_id: Option<String>,
+}
+
+/*
+    Saves the post `post_id`, optionally into one of the user's collections.
+    Bookmarking an already saved post only moves it to `collection_id`.
+*/
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    match viewer.can_view_post_id(&db, &post_id).await {
+        Ok(true) => (),
+        Ok(false) => return Ok(Response::not_found("post not found")),
+        Err(error) => return Ok(error),
+    }
+
+    if let Some(collection_id) = &req_body.collection_id {
+        let collection = db.collection::
+        <BookmarkCollection>("bookmark_collection");
+        let result = collection.count_documents(doc!{
+            "uuid": collection_id,
+            "owner": &user_id
+        }).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        if result.unwrap() == 0 {
+            return Ok(Response::not_found("collection not found"));
+        }
+    }
+
+    let collection = db.collection::<PostBookmark>("post_bookmark");
+    let filter = doc!{"post_id": &post_id, "bookmarked_by": &user_id};
+    let update = doc!{
+        "$set": {"collection_id": &req_body.collection_id},
+        "$setOnInsert": {
+            "post_id": &post_id,
+            "bookmarked_by": &user_id,
+            "bookmarked_at": Utc::now().timestamp_millis()
+        }
+    };
+
+    let result = match collection.update_one(filter.clone(), update.clone())
+    .upsert(true).await {
+        // A concurrent bookmark of the same post inserted it first
+        Err(error) if mongo::is_duplicate_key(&error) => {
+            collection.update_one(filter, update).await
+        },
+        result => result,
+    };
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "post_id": &post_id,
+            "bookmarked": true,
+            "collection_id": &req_body.collection_id
+        }))
+    )
+}
@@ -0,0 +1,97 @@
+use uuid::Uuid;
+use chrono::Utc;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::BookmarkCollection;
+
+pub const MAX_NAME_LENGTH: usize = 50;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    name: String,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let name = req_body.name.trim().to_string();
+    if let Err(error) = check_name(&name) {
+        return Ok(Response::bad_request(&error));
+    }
+
+    let db = MongoDB.connect();
+    let collection = db.collection::
+    <BookmarkCollection>("bookmark_collection");
+
+    let result = collection.count_documents(doc!{
+        "owner": &user_id,
+        "name": &name
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap() > 0 {
+        return Ok(Response::conflict("A collection with this name already exists"));
+    }
+
+    // New collections go to the end
+    let result = collection.count_documents(doc!{"owner": &user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let bookmark_collection = BookmarkCollection {
+        uuid: Uuid::new_v4().to_string(),
+        owner: user_id.clone(),
+        name,
+        position: result.unwrap() as i64,
+        created_at: now,
+        modified_at: now,
+    };
+
+    let result = collection.insert_one(&bookmark_collection).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(bookmark_collection)
+    )
+}
+
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Collection name is required".to_string());
+    }
+
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "Collection name can't be longer than {} characters",
+            MAX_NAME_LENGTH
+        ));
+    }
+
+    Ok(())
+}
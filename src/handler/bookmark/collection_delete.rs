@@ -0,0 +1,84 @@
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::{BookmarkCollection, PostBookmark};
+
+/*
+    Deletes one of the user's collections.
+    Posts saved in it stay saved, just outside of any collection.
+*/
+pub async fn task(
+    req: HttpRequest,
+    collection_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let collection_id = collection_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::
+    <BookmarkCollection>("bookmark_collection");
+    let result = collection.find_one_and_delete(
+        doc!{"uuid": &collection_id, "owner": &user_id}
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("collection not found"));
+    }
+
+    let deleted = option.unwrap();
+
+    // Keep the remaining positions contiguous
+    let result = collection.update_many(
+        doc!{"owner": &user_id, "position": {"$gt": deleted.position}},
+        doc!{"$inc": {"position": -1}},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostBookmark>("post_bookmark");
+    let result = collection.update_many(
+        doc!{"bookmarked_by": &user_id, "collection_id": &collection_id},
+        doc!{"$set": {"collection_id": null}},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(HttpResponse::Ok().content_type("application/json").json(
+        Response { message: "Successfully Deleted".to_string() }
+    ))
+}
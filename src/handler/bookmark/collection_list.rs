@@ -0,0 +1,44 @@
+use futures::StreamExt;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::BookmarkCollection;
+
+pub async fn task(req: HttpRequest) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let db = MongoDB.connect();
+    let collection = db.collection::
+    <BookmarkCollection>("bookmark_collection");
+    let result = collection.find(doc!{"owner": &user_id})
+    .sort(doc!{"position": 1}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut collections = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        collections.push(result.unwrap());
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(collections)
+    )
+}
@@ -0,0 +1,77 @@
+use chrono::Utc;
+use mongodb::bson::doc;
+use mongodb::options::ReturnDocument;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::BookmarkCollection;
+use super::collection_create::check_name;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    name: String,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    collection_id: web::Path<String>,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let collection_id = collection_id.into_inner();
+
+    let name = req_body.name.trim().to_string();
+    if let Err(error) = check_name(&name) {
+        return Ok(Response::bad_request(&error));
+    }
+
+    let db = MongoDB.connect();
+    let collection = db.collection::
+    <BookmarkCollection>("bookmark_collection");
+
+    let result = collection.count_documents(doc!{
+        "owner": &user_id,
+        "name": &name,
+        "uuid": {"$ne": &collection_id}
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap() > 0 {
+        return Ok(Response::conflict("A collection with this name already exists"));
+    }
+
+    let result = collection.find_one_and_update(
+        doc!{"uuid": &collection_id, "owner": &user_id},
+        doc!{"$set": {
+            "name": &name,
+            "modified_at": Utc::now().timestamp_millis()
+        }},
+    ).return_document(ReturnDocument::After).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Ok(Response::not_found("collection not found"));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(option.unwrap())
+    )
+}
@@ -0,0 +1,92 @@
+use chrono::Utc;
+use futures::StreamExt;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::BookmarkCollection;
+
+/*
+    Sets the order of the user's collections.
+    The body must list every collection of the user exactly once.
+*/
+pub async fn task(
+    req: HttpRequest,
+    collection_ids: web::Json<Vec<String>>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::
+    <BookmarkCollection>("bookmark_collection");
+    let result = collection.find(doc!{"owner": &user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut owned = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        owned.push(result.unwrap().uuid);
+    }
+
+    let mut requested = collection_ids.clone();
+    requested.sort();
+    requested.dedup();
+    owned.sort();
+
+    if requested.len() != collection_ids.len() || requested != owned {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::bad_request(
+            "Every collection must be listed exactly once"
+        ));
+    }
+
+    let now = Utc::now().timestamp_millis();
+    for (position, collection_id) in collection_ids.iter().enumerate() {
+        let result = collection.update_one(
+            doc!{"uuid": collection_id, "owner": &user_id},
+            doc!{"$set": {
+                "position": position as i64,
+                "modified_at": now
+            }},
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(HttpResponse::Ok().content_type("application/json").json(
+        Response { message: "Successfully Reordered".to_string() }
+    ))
+}
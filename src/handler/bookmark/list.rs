@@ -0,0 +1,140 @@
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::bson::doc;
+use std::collections::HashMap;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::Handler::Post::Get::build_posts;
+use crate::model::Post::{PostBookmark, PostCore};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    collection_id: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+/*
+    The user's saved posts, most recently saved first.
+    Posts the user can no longer see are left out.
+*/
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let pagination = match Pagination::new(
+        Keyset::desc("bookmarked_at", "post_id"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20),
+        Legacy::Page(req_query.page),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+
+    let mut filter = doc!{"bookmarked_by": &user_id, "deleted_at": null};
+    if let Some(collection_id) = &req_query.collection_id {
+        filter.insert("collection_id", collection_id);
+    }
+
+    let collection = db.collection::<PostBookmark>("post_bookmark");
+    let result = pagination.find(&collection, filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut bookmarks = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        bookmarks.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut bookmarks,
+        |bookmark| (bookmark.bookmarked_at, bookmark.post_id.clone())
+    );
+
+    let post_ids: Vec<String> = bookmarks.iter()
+    .map(|bookmark| bookmark.post_id.clone())
+    .collect();
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find(doc!{"uuid": {"$in": &post_ids}}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut post_cores = HashMap::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let post_core = result.unwrap();
+        post_cores.insert(post_core.uuid.clone(), post_core);
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    let mut visible = Vec::new();
+    let mut bookmarked = Vec::new();
+    for bookmark in bookmarks.iter() {
+        let post_core = match post_cores.remove(&bookmark.post_id) {
+            Some(post_core) => post_core,
+            None => continue,
+        };
+
+        match viewer.can_view_post(&db, &post_core).await {
+            Ok(true) => (),
+            Ok(false) => continue,
+            Err(error) => return Ok(error),
+        }
+
+        visible.push(post_core);
+        bookmarked.push(bookmark);
+    }
+
+    let mut posts = match build_posts(&db, &mut viewer, &visible).await {
+        Ok(posts) => posts,
+        Err(error) => return Ok(error),
+    };
+
+    for (post, bookmark) in posts.iter_mut().zip(bookmarked) {
+        post.insert("bookmark".to_string(), json!({
+            "collection_id": &bookmark.collection_id,
+            "bookmarked_at": bookmark.bookmarked_at,
+        }));
+    }
+
+    Ok(pagination.response("posts", posts, page_info))
+}
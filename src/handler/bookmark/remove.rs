@@ -0,0 +1,42 @@
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostBookmark;
+
+/* Removes the post `post_id` from the user's saved posts */
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+    let collection = db.collection::<PostBookmark>("post_bookmark");
+    let result = collection.delete_one(doc!{
+        "post_id": &post_id,
+        "bookmarked_by": &user_id
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "post_id": &post_id,
+            "bookmarked": false
+        }))
+    )
+}
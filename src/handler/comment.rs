@@ -5,4 +5,19 @@ pub mod delete;
 pub use delete as Delete;
 
 pub mod get;
-pub use get as Get;
\ No newline at end of file
+pub use get as Get;
+
+pub mod like;
+pub use like as Like;
+
+pub mod unlike;
+pub use unlike as Unlike;
+
+pub mod edit;
+pub use edit as Edit;
+
+pub mod pin;
+pub use pin as Pin;
+
+pub mod unpin;
+pub use unpin as Unpin;
\ No newline at end of file
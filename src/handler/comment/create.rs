@@ -3,13 +3,23 @@ use chrono::Utc;
 use serde_json::json;
 use mongodb::Database;
 use mongodb::bson::doc;
+use crate::utils::{comment_rank, mention, notification};
+use crate::utils::mention::MentionedIn;
+use crate::utils::visibility::Viewer;
 use crate::BuiltIns::mongo::MongoDB;
 // use crate::Integrations::Firebase;
 use serde::{ Serialize, Deserialize };
 use crate::utils::response::Response;
 use actix_web::{web, Error, HttpResponse, HttpRequest};
 use crate::middleware::auth::{require_access, AccessRequirement};
-use crate::model::{Comment, Post, Mention, AudioStruct, Account::AccountRole};
+use crate::model::{
+    Comment,
+    Post,
+    Mention,
+    AudioStruct,
+    Notification::NotificationType,
+    Post::{PostOwnerType, PostPublishState},
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ReqBody {
@@ -26,7 +36,7 @@ pub async fn task(
 ) -> Result<HttpResponse, Error> {
     let user = require_access(
         &req,
-        AccessRequirement::Role(AccountRole::Administrator)
+        AccessRequirement::AnyToken
     )?;
 
     let user_id = user.user_id;
@@ -43,6 +53,42 @@ pub async fn task(
         return Ok(Response::internal_server_error(&error.to_string()));
     }
 
+    // Anyone who can read the post can comment on it
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let post_core = match get_visible_post(
+        &db,
+        &mut viewer,
+        &form_data.post_id
+    ).await {
+        Ok(post_core) => post_core,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    //Validate the mentions and resolve the written @usernames
+    let mentions = match mention::resolve(
+        &db,
+        &user_id,
+        &form_data.text,
+        &form_data.mentions,
+        &Vec::new()
+    ).await {
+        Ok(mentions) => mentions,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
     let comment_id = Uuid::new_v4().to_string();
     let now = Utc::now().timestamp_millis();
 
@@ -55,17 +101,19 @@ pub async fn task(
         text: form_data.text.clone(),
         images: form_data.images.clone(),
         audio: form_data.audio.clone(),
-        mentions: form_data.mentions.clone(),
+        mentions: mentions.clone(),
         status: Comment::CommentStatus::Active,
         created_at: now,
         deleted_at: None,
         suspended_at: None,
         suspended_by: None,
         is_edited: false,
+        top_rank: comment_rank::top_rank(now, 0, 0),
         modified_at: now,
     };
 
-    let result = collection.insert_one(comment_core).await;
+    let result = collection.insert_one(comment_core)
+    .session(&mut session).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -82,7 +130,8 @@ pub async fn task(
         reply_count: 0,
     };
 
-    let result = collection.insert_one(comment_stat).await;
+    let result = collection.insert_one(comment_stat)
+    .session(&mut session).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -98,7 +147,7 @@ pub async fn task(
             "$inc":{ "comment_count": 1 },
             "$set":{ "modified_at": now }
         },
-    ).await;
+    ).session(&mut session).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -112,25 +161,40 @@ pub async fn task(
         return Ok(Response::not_found("post not found"));
     }
 
-    //Finding the post owner id
-    let post_owner = match get_post_owner(&db, &form_data.post_id).await {
-        Ok(post_owner) => post_owner,
-        Err(error) => {
-            log::error!("{:?}", error);
-            session.abort_transaction().await.ok().unwrap();
-            return Ok(error);
-        },
-    };
-
-    if post_owner != user_id {
-        
-    }
-
     /* DATABASE ACID COMMIT */
     if let Err(error) = session.commit_transaction().await {
         log::error!("{:?}", error);
         return Ok(Response::internal_server_error(&error.to_string()));
     }
+
+    // Page posts have no single owner to notify
+    if let PostOwnerType::User = post_core.owner_type {
+        if let Err(error) = notification::send(
+            &db,
+            &post_core.owner,
+            &user_id,
+            NotificationType::Comment,
+            Some("commented on your post".to_string()),
+            Some(json!({
+                "post_id": &form_data.post_id,
+                "comment_id": &comment_id
+            }))
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    if let Err(error) = mention::notify(
+        &db,
+        &user_id,
+        &mentions,
+        MentionedIn::Comment {
+            post_id: &form_data.post_id,
+            comment_id: &comment_id
+        }
+    ).await {
+        return Ok(error);
+    }
   
     Ok(
         HttpResponse::Ok()
@@ -141,35 +205,37 @@ pub async fn task(
     )
 }
 
-async fn get_post_owner(
+/*
+    Published post `post_id`, if the viewer can read it.
+    Posts they can't read are reported as missing.
+*/
+pub async fn get_visible_post(
     db: &Database,
+    viewer: &mut Viewer,
     post_id: &str
-) -> Result<String, HttpResponse> {
-    #[derive(Debug, Deserialize, Serialize)]
-    struct PostCoreRead {
-        uuid: String,
-        owner: String,
-    }
-
-    let collection = db.collection::<PostCoreRead>("post_core");
-    let result = collection.find_one(
-        doc!{ "uuid": post_id },
-    ).await;
+) -> Result<Post::PostCore, HttpResponse> {
+    let collection = db.collection::<Post::PostCore>("post_core");
+    let result = collection.find_one(doc!{"uuid": post_id}).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
         return Err(Response::internal_server_error(&error.to_string()));
     }
 
-    let option = result.unwrap();
-    if let None = option {
+    let post_core = match result.unwrap() {
+        Some(post_core) => post_core,
+        None => return Err(Response::not_found("post not found")),
+    };
+
+    if post_core.deleted_at.is_some() ||
+    post_core.publish_state != PostPublishState::Published {
         return Err(Response::not_found("post not found"));
     }
 
-    let post = option.unwrap();
-    let post_owner = post.owner;
-
-    Ok(post_owner)
+    match viewer.can_view_post(db, &post_core).await? {
+        true => Ok(post_core),
+        false => Err(Response::not_found("post not found")),
+    }
 }
 
 fn check_empty_fields(data: &ReqBody) -> Result<(), String> {
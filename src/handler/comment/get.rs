@@ -1,13 +1,40 @@
-use crate::utils::string;
+use futures::StreamExt;
+use crate::utils::fields::{Fields, Section};
+use crate::utils::visibility::Viewer;
 use serde_json::{ Map, Value};
-use mongodb::{bson::doc, Database};
+use mongodb::{bson::{doc, Document}, Database};
 use crate::builtins::mongo::MongoDB;
 use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
 use serde::{ Serialize, Deserialize };
 use crate::model::{Comment, Account::AccountRole};
 use actix_web::{ web, Error, HttpResponse, HttpRequest};
 use crate::middleware::auth::{require_access, AccessRequirement};
 
+/* Sections of a comment, for `fields=(core(text,...),stat)` */
+pub const FIELDS: &[Section] = &[
+    Section {
+        name: "core",
+        fields: &[
+            "uuid", "owner", "post_id", "text", "images", "audio", "status",
+            "is_edited", "mentions", "top_rank", "created_at", "modified_at",
+            "deleted_at", "suspended_at", "suspended_by",
+        ],
+    },
+    Section {
+        name: "stat",
+        fields: &["uuid", "like_count", "reply_count", "modified_at"],
+    },
+    Section {
+        name: "meta",
+        fields: &["liked"],
+    },
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentSort { Newest, Oldest, Top }
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Query {
     uuid: Option<String>,
@@ -16,8 +43,11 @@ pub struct Query {
     fields: Option<String>,
     status: Option<Comment::CommentStatus>,
     is_edited: Option<bool>,
-    limit: i64,
-    offset: i64,
+    sort: Option<CommentSort>,
+    cursor: Option<String>,
+    paginated: Option<bool>,
+    limit: Option<u32>,
+    offset: Option<u32>,
 }
 
 pub async fn task(
@@ -26,240 +56,261 @@ pub async fn task(
 ) -> Result<HttpResponse, Error> {
     let user = require_access(
         &req,
-        AccessRequirement::Role(AccountRole::Administrator)
+        AccessRequirement::AnyToken
     )?;
 
     let user_id = user.user_id;
+    let is_admin = user.role == AccountRole::Administrator;
 
-    let mut response = Map::new();
+    let sort = query.sort.clone().unwrap_or(CommentSort::Newest);
+    let keyset = match sort {
+        CommentSort::Newest => Keyset::desc("created_at", "uuid"),
+        CommentSort::Oldest => Keyset::asc("created_at", "uuid"),
+        CommentSort::Top => Keyset::desc("top_rank", "uuid"),
+    };
+
+    let pagination = match Pagination::new(
+        keyset,
+        &query.cursor,
+        query.limit.unwrap_or(20),
+        Legacy::Offset(query.offset),
+        Shape::Legacy(query.paginated)
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
 
     let db = MongoDB.connect();
-    if let Some(fields) = query.fields.clone() {
-        let fields = match string::parse_comma_separated(&fields) {
-            Ok(fields) => fields,
-            Err(error) => return Ok(Response::bad_request(&error)),
-        };
 
-        for field in fields {
-            if let Ok(value) = string::strip_prefix("core", field) {
-                let sub_fields = match value.len() {
-                    0 => vec![],
-                    _ => match string::parse_comma_separated(&value) {
-                        Ok(sub_fields) => sub_fields,
-                        Err(error) => {
-                            return Ok(Response::bad_request(&error));
-                        },
-                    },
-                };
-                
-                let comment_core = match get_comment_core(
-                    &user_id,
-                    &db,
-                    sub_fields
-                ).await {
-                    Ok(comment_core) => comment_core,
-                    Err(error) => return Ok(error),
-                };
-
-                response.insert("core".to_string(), comment_core);
-            }
-            else if let Ok(value) = string::strip_prefix("stat", field) {
-                let sub_fields = match value.len() {
-                    0 => vec![],
-                    _ => match string::parse_comma_separated(&value) {
-                        Ok(sub_fields) => sub_fields,
-                        Err(error) => {
-                            return Ok(Response::bad_request(&error));
-                        },
-                    },
-                };
-                
-                let comment_stat = match get_comment_stat(
-                    &user_id,
-                    &db,
-                    sub_fields
-                ).await {
-                    Ok(comment_stat) => comment_stat,
-                    Err(error) => return Ok(error),
-                };
-
-                response.insert("stat".to_string(), comment_stat);
-            }
-            else {
-                return Ok(Response::bad_request(
-                    &format!("Invalid field: {}", field)
-                ));
-            }
-        }
+    let fields = match Fields::parse(&query.fields, FIELDS) {
+        Ok(fields) => fields,
+        Err(error) => return Ok(error),
+    };
+
+    let mut filter = doc!{"deleted_at": null};
+
+    if let Some(uuid) = query.uuid.clone() {
+        filter.insert("uuid", uuid);
+    }
+    if let Some(owner) = query.owner.clone() {
+        filter.insert("owner", owner);
     }
-    else {
-        let comment_core = match get_comment_core(
-            &user_id,
+    if let Some(post_id) = query.post_id.clone() {
+        filter.insert("post_id", post_id);
+    }
+    if let Some(status) = query.status.clone() {
+        filter.insert("status", status.to_string());
+    }
+    if let Some(is_edited) = query.is_edited.clone() {
+        filter.insert("is_edited", is_edited);
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    // Comments are only readable by those who can read the post, posts are
+    // narrowed down before paginating so pages stay full
+    if !is_admin {
+        let post_ids = match visible_post_ids(
             &db,
-            vec![]
+            &mut viewer,
+            &query.post_id,
+            &filter
         ).await {
-            Ok(comment_core) => comment_core,
+            Ok(post_ids) => post_ids,
             Err(error) => return Ok(error),
         };
 
-        response.insert("core".to_string(), comment_core);
+        filter.insert("post_id", doc!{"$in": post_ids});
+    }
 
-        let comment_stat = match get_comment_stat(
-            &user_id,
-            &db,
-            vec![]
-        ).await {
-            Ok(comment_stat) => comment_stat,
+    // The pinned comment leads the first page and is left out of the rest
+    let mut pinned = None;
+    if let Some(post_id) = &query.post_id {
+        pinned = match get_pinned(&db, post_id, &filter).await {
+            Ok(pinned) => pinned,
             Err(error) => return Ok(error),
         };
-
-        response.insert("stat".to_string(), comment_stat);
     }
 
-    Ok(
-        HttpResponse::Ok()
-        .content_type("application/json")
-        .json(Value::Object(response))
-    )
-}
+    if let Some(pinned) = &pinned {
+        filter = doc!{"$and": [filter, {"uuid": {"$ne": &pinned.uuid}}]};
+    }
 
-async fn get_comment_core(
-    user_id: &str,
-    db: &Database,
-    sub_fields: Vec<&str>
-) -> Result<serde_json::Value, HttpResponse> {
     let collection = db.collection::<Comment::CommentCore>("comment_core");
-    let result = collection.find_one(doc!{"uuid": user_id}).await;
+    let result = pagination.find(&collection, filter).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
-        return Err(Response::internal_server_error(&error.to_string()));
+        return Ok(Response::internal_server_error(&error.to_string()));
     }
 
-    let option = result.unwrap();
-    if let None = option {
-        return Err(Response::not_found("account not found"));
+    let mut comment_cores = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        comment_cores.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut comment_cores,
+        |comment_core| match sort {
+            CommentSort::Top => (comment_core.top_rank, comment_core.uuid.clone()),
+            _ => (comment_core.created_at, comment_core.uuid.clone()),
+        }
+    );
+
+    let first_page = query.cursor.is_none() && query.offset.unwrap_or(0) == 0;
+    let pinned_id = pinned.as_ref().map(|pinned| pinned.uuid.clone());
+    if let Some(pinned) = pinned.filter(|_| first_page) {
+        comment_cores.insert(0, pinned);
     }
 
-    if sub_fields.len() > 0 {
-        let comment_core = option.unwrap();
+    let mut liked = Vec::new();
+    if fields.wants("meta", "liked") {
+        let comment_ids: Vec<String> = comment_cores.iter()
+        .map(|comment_core| comment_core.uuid.clone())
+        .collect();
+
+        liked = match get_liked(&db, &user_id, &comment_ids).await {
+            Ok(liked) => liked,
+            Err(error) => return Ok(error),
+        };
+    }
+
+    let mut comments = Vec::new();
+    for comment_core in comment_cores {
         let mut response = Map::new();
-        for field in sub_fields {
-            match field {
-                "owner" => response.insert(
-                    "owner".to_string(),
-                    Value::String(comment_core.owner.clone())
-                ),
-                "post_id" => response.insert(
-                    "post_id".to_string(),
-                    Value::String(comment_core.post_id.clone())
-                ),
-                "text" => response.insert(
-                    "text".to_string(),
-                    if comment_core.text.is_none() {
-                        Value::Null
-                    }
-                    else {
-                        Value::String(comment_core.text.clone().unwrap())
-                    }
-                ),
-                "images" => response.insert(
-                    "images".to_string(),
-                    serde_json::to_value(
-                        comment_core.images.clone()
-                    ).unwrap()
-                ),
-                "audio" => response.insert(
-                    "audio".to_string(),
-                    if comment_core.audio.is_none() {
-                        Value::Null
-                    }
-                    else {
-                        serde_json::to_value(
-                            comment_core.audio.clone().unwrap()
-                        ).unwrap()
-                    }
-                ),
-                "status" => response.insert(
-                    "status".to_string(),
-                    Value::String(
-                        comment_core.status.clone().to_string()
-                    )
-                ),
-                "is_edited" => response.insert(
-                    "is_edited".to_string(),
-                    Value::Bool(
-                        comment_core.is_edited.clone()
-                    )
-                ),
-                "mentions" => response.insert(
-                    "mentions".to_string(),
-                    serde_json::to_value(
-                        comment_core.mentions.clone()
-                    ).unwrap()
-                ),
-                "created_at" => response.insert(
-                    "created_at".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        comment_core.created_at.clone()
-                    ))
-                ),
-                "modified_at" => response.insert(
-                    "modified_at".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        comment_core.modified_at.clone()
-                    ))
-                ),
-                "suspended_at" => {
-                    if comment_core.suspended_at.is_none() {
-                        response.insert(
-                            "suspended_at".to_string(),
-                            Value::Null
-                        )
-                    }
-                    else {
-                        response.insert(
-                            "suspended_at".to_string(),
-                            Value::Number(serde_json::Number::from(
-                                comment_core.suspended_at.clone().unwrap()
-                            ))
-                        )
-                    }
-                },
-                "suspended_by" => {
-                    if comment_core.suspended_by.is_none() {
-                        response.insert(
-                            "suspended_by".to_string(),
-                            Value::Null
-                        )
-                    }
-                    else {
-                        response.insert(
-                            "suspended_by".to_string(),
-                            Value::String(comment_core.suspended_by.clone().unwrap())
-                        )
-                    }
-                },
-                others => return Err(Response::bad_request(
-                    &format!("Invalid field: {others}"))
-                ),
+        response.insert(
+            "pinned".to_string(),
+            Value::Bool(pinned_id.as_ref() == Some(&comment_core.uuid))
+        );
+
+        if fields.includes("core") {
+            response.insert(
+                "core".to_string(),
+                serde_json::to_value(&comment_core).unwrap()
+            );
+        }
+
+        if fields.includes("stat") {
+            let value = match get_comment_stat(
+                &db,
+                &comment_core.uuid,
+                &fields
+            ).await {
+                Ok(value) => value,
+                Err(error) => return Ok(error),
             };
+
+            response.insert("stat".to_string(), value);
         }
 
-        return Ok(serde_json::to_value(response).unwrap());
+        if fields.includes("meta") {
+            response.insert(
+                "meta".to_string(),
+                serde_json::json!({
+                    "liked": liked.contains(&comment_core.uuid),
+                })
+            );
+        }
+
+        comments.push(Value::Object(fields.apply(response)));
     }
 
-    Ok(serde_json::to_value(option.unwrap()).unwrap())
+    Ok(pagination.response("comments", comments, page_info))
+}
+
+/*
+    Posts of the comments matching `filter` the viewer can read, only
+    `post_id` when the comments of one post are listed.
+*/
+async fn visible_post_ids(
+    db: &Database,
+    viewer: &mut Viewer,
+    post_id: &Option<String>,
+    filter: &Document
+) -> Result<Vec<String>, HttpResponse> {
+    let post_ids = match post_id {
+        Some(post_id) => vec![post_id.clone()],
+        None => {
+            let collection = db.collection::<Comment::CommentCore>("comment_core");
+            let result = collection.distinct("post_id", filter.clone()).await;
+
+            if let Err(error) = result {
+                log::error!("{:?}", error);
+                return Err(Response::internal_server_error(&error.to_string()));
+            }
+
+            result.unwrap().iter()
+            .filter_map(|post_id| post_id.as_str().map(|post_id| post_id.to_string()))
+            .collect()
+        }
+    };
+
+    let mut visible = Vec::new();
+    for post_id in post_ids {
+        if viewer.can_view_post_id(db, &post_id).await? {
+            visible.push(post_id);
+        }
+    }
+
+    Ok(visible)
+}
+
+/*
+    The comment pinned on `post_id`, if it is active and matches `filter`.
+    Pins of removed comments are kept but nothing is shown for them.
+*/
+async fn get_pinned(
+    db: &Database,
+    post_id: &str,
+    filter: &Document
+) -> Result<Option<Comment::CommentCore>, HttpResponse> {
+    let collection = db.collection::<Comment::CommentPin>("comment_pin");
+    let result = collection.find_one(doc!{"post_id": post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let pin = match result.unwrap() {
+        Some(pin) => pin,
+        None => return Ok(None),
+    };
+
+    let collection = db.collection::<Comment::CommentCore>("comment_core");
+    let result = collection.find_one(doc!{
+        "$and": [filter.clone(), {
+            "uuid": &pin.comment_id,
+            "status": Comment::CommentStatus::Active.to_string(),
+            "deleted_at": null
+        }]
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(result.unwrap())
 }
 
 async fn get_comment_stat(
-    user_id: &str,
     db: &Database,
-    sub_fields: Vec<&str>
-) -> Result<serde_json::Value, HttpResponse> {
-    let collection = db.collection::
-    <Comment::CommentStat>("comment_stat");
-    let result = collection.find_one(doc!{"uuid": user_id}).await;
+    comment_id: &str,
+    fields: &Fields
+) -> Result<Value, HttpResponse> {
+    let collection = db.collection::<Document>("comment_stat");
+    let result = collection.find_one(doc!{"uuid": comment_id})
+    .projection(fields.projection("stat")).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -268,40 +319,41 @@ async fn get_comment_stat(
 
     let option = result.unwrap();
     if let None = option {
-        return Err(Response::not_found("account not found"));
+        return Err(Response::not_found("Comment stat not found"));
     }
 
-    if sub_fields.len() > 0 {
-        let comment_stat = option.unwrap();
-        let mut response = Map::new();
-        for field in sub_fields {
-            match field {
-                "like_count" => response.insert(
-                    "like_count".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        comment_stat.like_count.clone()
-                    ))
-                ),
-                "reply_count" => response.insert(
-                    "reply_count".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        comment_stat.reply_count.clone()
-                    ))
-                ),
-                "modified_at" => response.insert(
-                    "modified_at".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        comment_stat.modified_at.clone()
-                    ))
-                ),
-                others => return Err(Response::bad_request(
-                    &format!("Invalid field: {others}"))
-                ),
-            };
-        }
+    Ok(serde_json::to_value(option.unwrap()).unwrap())
+}
 
-        return Ok(serde_json::to_value(response).unwrap());
+/* Which of `comment_ids` the viewer liked */
+async fn get_liked(
+    db: &Database,
+    user_id: &str,
+    comment_ids: &Vec<String>
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<Comment::CommentLike>("comment_like");
+    let result = collection.find(doc!{
+        "comment_id": {"$in": comment_ids},
+        "liked_by": user_id,
+        "deleted_at": null,
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
     }
 
-    Ok(serde_json::to_value(option.unwrap()).unwrap())
+    let mut liked = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        match result {
+            Ok(like) => liked.push(like.comment_id),
+            Err(error) => {
+                log::error!("{:?}", error);
+                return Err(Response::internal_server_error(&error.to_string()));
+            }
+        }
+    }
+
+    Ok(liked)
 }
\ No newline at end of file
@@ -0,0 +1,146 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::{comment_rank, mongo, notification};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Handler::Reply::Create::get_visible_comment;
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Comment::{CommentLike, CommentStat},
+    Notification::NotificationType,
+};
+
+/*
+    Likes the comment `comment_id`.
+    Liking an already liked comment is a no-op.
+*/
+pub async fn task(
+    req: HttpRequest,
+    comment_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let comment_id = comment_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let comment_core = match get_visible_comment(
+        &db,
+        &mut viewer,
+        &comment_id
+    ).await {
+        Ok(comment_core) => comment_core,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let now = Utc::now().timestamp_millis();
+
+    // Upsert keeps repeated likes from creating duplicates
+    let collection = db.collection::<CommentLike>("comment_like");
+    let result = collection.update_one(
+        doc!{"comment_id": &comment_id, "liked_by": &user_id},
+        doc!{
+            "$setOnInsert": {
+                "comment_id": &comment_id,
+                "liked_by": &user_id,
+                "liked_at": now
+            }
+        },
+    ).upsert(true).session(&mut session).await;
+
+    let newly_liked = match result {
+        Ok(result) => result.upserted_id.is_some(),
+        // A concurrent like of the same comment won the unique index
+        Err(error) if mongo::is_duplicate_key(&error) => {
+            session.abort_transaction().await.ok();
+            return Ok(liked_response(&comment_id));
+        },
+        Err(error) => {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    };
+
+    if newly_liked {
+        let collection = db.collection::<CommentStat>("comment_stat");
+        let result = collection.update_one(
+            doc!{"uuid": &comment_id},
+            doc!{
+                "$inc": {"like_count": 1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        if let Err(error) = comment_rank::refresh(
+            &db,
+            &mut session,
+            &comment_id
+        ).await {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if newly_liked {
+        if let Err(error) = notification::send(
+            &db,
+            &comment_core.owner,
+            &user_id,
+            NotificationType::Like,
+            Some("liked your comment".to_string()),
+            Some(json!({
+                "post_id": &comment_core.post_id,
+                "comment_id": &comment_id
+            }))
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    Ok(liked_response(&comment_id))
+}
+
+fn liked_response(comment_id: &str) -> HttpResponse {
+    HttpResponse::Ok()
+    .content_type("application/json")
+    .json(json!({
+        "comment_id": comment_id,
+        "liked": true
+    }))
+}
@@ -0,0 +1,98 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Handler::Post::Edit::can_edit;
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Comment::{CommentCore, CommentPin, CommentStatus};
+use super::Create::get_visible_post;
+
+/*
+    Pins a comment to the top of its post's comments.
+    A post has one pin, pinning another comment replaces it.
+*/
+pub async fn task(
+    req: HttpRequest,
+    comment_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let comment_id = comment_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::<CommentCore>("comment_core");
+    let result = collection.find_one(doc!{
+        "uuid": &comment_id,
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Ok(Response::not_found("comment not found"));
+    }
+
+    let comment_core = option.unwrap();
+
+    if !matches!(comment_core.status, CommentStatus::Active) {
+        return Ok(Response::conflict("Only active comments can be pinned"));
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    let post_core = match get_visible_post(
+        &db,
+        &mut viewer,
+        &comment_core.post_id
+    ).await {
+        Ok(post_core) => post_core,
+        Err(error) => return Ok(error),
+    };
+
+    match can_edit(&db, &post_core, &user_id).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(Response::forbidden(
+            "You are not authorized to pin comments on this post"
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    let pin = CommentPin {
+        post_id: post_core.uuid.clone(),
+        comment_id: comment_id.clone(),
+        pinned_by: user_id.clone(),
+        pinned_at: Utc::now().timestamp_millis(),
+    };
+
+    let collection = db.collection::<CommentPin>("comment_pin");
+    let result = collection.replace_one(
+        doc!{"post_id": &pin.post_id},
+        &pin
+    ).upsert(true).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!(pin))
+    )
+}
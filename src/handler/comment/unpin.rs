@@ -0,0 +1,78 @@
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Handler::Post::Edit::can_edit;
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{Comment::CommentPin, Post::PostCore};
+
+pub async fn task(
+    req: HttpRequest,
+    comment_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let comment_id = comment_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::<CommentPin>("comment_pin");
+    let result = collection.find_one(doc!{"comment_id": &comment_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Ok(Response::not_found("Comment isn't pinned"));
+    }
+
+    let pin = option.unwrap();
+
+    let post_collection = db.collection::<PostCore>("post_core");
+    let result = post_collection.find_one(doc!{"uuid": &pin.post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Ok(Response::not_found("post not found"));
+    }
+
+    match can_edit(&db, &option.unwrap(), &user_id).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(Response::forbidden(
+            "You are not authorized to unpin this comment"
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    let result = collection.delete_one(doc!{
+        "post_id": &pin.post_id,
+        "comment_id": &comment_id
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "comment_id": &comment_id,
+            "pinned": false
+        }))
+    )
+}
@@ -2,6 +2,7 @@ use chrono::Utc;
 use uuid::Uuid;
 use serde_json::json;
 use mongodb::bson::doc;
+use crate::utils::privacy;
 use crate::BuiltIns::mongo::MongoDB;
 use crate::utils::response::Response;
 use serde::{ Serialize, Deserialize };
@@ -39,6 +40,23 @@ pub async fn task(
         return Ok(Response::internal_server_error(&error.to_string()));
     }
 
+    //Check if every member can be added by this user
+    for member in &req_body.members {
+        match privacy::can_add_to_group(&db, member, &user_id).await {
+            Ok(true) => (),
+            Ok(false) => {
+                session.abort_transaction().await.ok().unwrap();
+                return Ok(Response::forbidden(&format!(
+                    "User {} can't be added to groups by you", member
+                )));
+            },
+            Err(error) => {
+                session.abort_transaction().await.ok().unwrap();
+                return Ok(error);
+            }
+        }
+    }
+
     //Creating Conversation
     let conversation_id = Uuid::new_v4().to_string();
     let now = Utc::now().timestamp_millis();
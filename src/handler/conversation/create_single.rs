@@ -3,6 +3,7 @@ use futures::StreamExt;
 use uuid::Uuid;
 use serde_json::json;
 use mongodb::bson::doc;
+use crate::utils::privacy;
 use crate::BuiltIns::mongo::MongoDB;
 use crate::handler::web_socket::message::AddToRoom;
 use crate::utils::response::Response;
@@ -36,6 +37,21 @@ pub async fn task(
         return Ok(Response::internal_server_error(&error.to_string()));
     }
 
+    //Check if the other user accepts messages from this user
+    match privacy::can_message(&db, &req_body.other_user, &user_id).await {
+        Ok(true) => (),
+        Ok(false) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::forbidden(
+                "This user doesn't accept messages from you"
+            ));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    }
+
     //Check if conversation between users exist
     if let Some(_) = single_conversation_exists(&db, &user_id, &req_body.other_user).await {
         session.abort_transaction().await.ok().unwrap();
@@ -153,7 +169,9 @@ pub async fn task(
 
     // Getting account status
     let collection = db.collection::<Account::AccountStatus>("account_status");
-    let result = collection.find_one(doc!{"uuid": &user_id}).await;
+    let result = collection.find_one(
+        doc!{"uuid": &req_body.other_user}
+    ).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -167,6 +185,17 @@ pub async fn task(
 
     let account_status = option.unwrap();
 
+    let (online, last_seen) = match privacy::visible_presence(
+        &db,
+        &req_body.other_user,
+        &user_id,
+        account_status.online,
+        account_status.last_seen
+    ).await {
+        Ok(presence) => presence,
+        Err(error) => return Ok(error),
+    };
+
     /* DATABASE ACID COMMIT */
     if let Err(error) = session.commit_transaction().await {
         log::error!("{:?}", error);
@@ -195,8 +224,8 @@ pub async fn task(
                 "first_name": account_profile.first_name,
                 "last_name": account_profile.last_name,
                 "image": image,
-                "online": account_status.online,
-                "last_seen": account_status.last_seen,
+                "online": online,
+                "last_seen": last_seen,
                 "is_blocked": false,
                 "am_blocked": false
             }),
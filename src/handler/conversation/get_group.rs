@@ -1,8 +1,9 @@
 use futures::StreamExt;
 use mongodb::Database;
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 use mongodb::bson::doc;
 use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::fields::{Fields, Section};
 use crate::utils::response::Response;
 use serde::{ Serialize, Deserialize };
 use actix_web::{web, Error, HttpResponse, HttpRequest};
@@ -13,6 +14,38 @@ use crate::model::{
     ImageStruct,
 };
 
+/* Sections of a group conversation, for `fields=(core,last_text(text,...),...)` */
+pub const FIELDS: &[Section] = &[
+    Section {
+        name: "core",
+        fields: &["uuid", "type", "last_message_at", "last_message_id", "created_at"],
+    },
+    Section {
+        name: "common_metadata",
+        fields: &["is_favorite", "is_muted"],
+    },
+    Section {
+        name: "last_text",
+        fields: &[
+            "uuid", "owner", "conversation_id", "text", "type", "images",
+            "audio", "video", "attachment", "seen_by", "created_at",
+        ],
+    },
+    Section {
+        name: "unread_count",
+        fields: &[],
+    },
+    Section {
+        name: "group_metadata",
+        fields: &["name", "image"],
+    },
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    fields: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct MessageUuidQuery {
     uuid: String,
@@ -23,7 +56,11 @@ pub struct MessageReadQuery {
     message_id: String,
 }
 
-pub async fn task(req: HttpRequest,  conversation_id: web::Path<String>) -> Result<HttpResponse, Error> {
+pub async fn task(
+    req: HttpRequest,
+    conversation_id: web::Path<String>,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
     let _user = require_access(
         &req,
         AccessRequirement::AnyToken
@@ -31,6 +68,11 @@ pub async fn task(req: HttpRequest,  conversation_id: web::Path<String>) -> Resu
 
     let user_id = _user.user_id;
 
+    let fields = match Fields::parse(&req_query.fields, FIELDS) {
+        Ok(fields) => fields,
+        Err(error) => return Ok(error),
+    };
+
     let db = MongoDB.connect();
 
     let collection = db.collection::<Conversation::ConversationCore>("conversation_core");
@@ -51,154 +93,175 @@ pub async fn task(req: HttpRequest,  conversation_id: web::Path<String>) -> Resu
 
     let conversation_core = option.unwrap();
 
-    // Check if this conversation is favorited by the current user
-    let fav_collection = db.collection::<Conversation::ConversationFavorite>("conversation_favorite");
-    let fav_result = fav_collection.find_one(doc!{
-        "conversation_id": &conversation_core.uuid,
-        "user_id": &user_id
-    }).await;
+    let mut response = Map::new();
+    response.insert(
+        "core".to_string(),
+        serde_json::to_value(&conversation_core).unwrap()
+    );
+
+    if fields.includes("common_metadata") {
+        // Check if this conversation is favorited by the current user
+        let fav_collection = db.collection::<Conversation::ConversationFavorite>("conversation_favorite");
+        let fav_result = fav_collection.find_one(doc!{
+            "conversation_id": &conversation_core.uuid,
+            "user_id": &user_id
+        }).await;
 
-    let is_favorite = match fav_result {
-        Ok(option) => option.is_some(),
-        Err(error) => {
-            log::error!("{:?}", error);
-            false
-        }
-    };
+        let is_favorite = match fav_result {
+            Ok(option) => option.is_some(),
+            Err(error) => {
+                log::error!("{:?}", error);
+                false
+            }
+        };
 
-    // Check if this conversation is muted by the current user
-    let mute_collection = db.collection::<Conversation::ConversationMuted>("conversation_muted");
+        // Check if this conversation is muted by the current user
+        let mute_collection = db.collection::<Conversation::ConversationMuted>("conversation_muted");
 
-    let mute_result = mute_collection.find_one(doc!{
-        "conversation_id": &conversation_core.uuid,
-        "user_id": &user_id
-    }).await;
+        let mute_result = mute_collection.find_one(doc!{
+            "conversation_id": &conversation_core.uuid,
+            "user_id": &user_id
+        }).await;
+
+        let is_muted = match mute_result {
+            Ok(option) => option.is_some(),
+            Err(error) => {
+                log::error!("{:?}", error);
+                false
+            }
+        };
+
+        let common_metadata = json!({
+            "is_favorite": is_favorite,
+            "is_muted": is_muted
+        });
 
-    let is_muted = match mute_result {
-        Ok(option) => option.is_some(),
-        Err(error) => {
+        response.insert("common_metadata".to_string(), common_metadata);
+    }
+
+    if fields.includes("group_metadata") {
+        let collection = db.collection::<Conversation::GroupConversationMetadata>("conversation_group_metadata");
+
+        let result = collection.find_one(doc!{
+            "conversation_id": &conversation_core.uuid
+        }).await;
+
+        if let Err(error) = result {
             log::error!("{:?}", error);
-            false
+            return Ok(Response::internal_server_error(
+                &error.to_string()
+            ));
         }
-    };
 
-    let common_metadata = json!({
-        "is_favorite": is_favorite,
-        "is_muted": is_muted
-    });
+        let option = result.unwrap();
+        if let None = option {
+            return Ok(Response::not_found(
+                "Group conversation metadata not found"
+            ));
+        }
 
-    let collection = db.collection::<Conversation::GroupConversationMetadata>("conversation_group_metadata");
+        let group_conversation_metadata = option.unwrap();
+
+        let image = match group_conversation_metadata.image
+        .filter(|_| fields.wants("group_metadata", "image")) {
+            Some(image) => {
+                let collection = db.collection::<ImageStruct>("image");
+                let result = collection.find_one(doc!{
+                    "uuid": image
+                }).await;
+
+                if let Err(error) = result {
+                    log::error!("{:?}", error);
+                    return Ok(Response::internal_server_error(
+                        &error.to_string()
+                    ));
+                }
 
-    let result = collection.find_one(doc!{
-        "conversation_id": &conversation_core.uuid
-    }).await;
+                let option = result.unwrap();
+                if let None = option {
+                    return Ok(Response::not_found("Image not found"));
+                }
 
-    if let Err(error) = result {
-        log::error!("{:?}", error);
-        return Ok(Response::internal_server_error(
-            &error.to_string()
-        ));
-    }
+                let image = option.unwrap();
+                Some(image)
+            },
+            None => None
+        };
 
-    let option = result.unwrap();
-    if let None = option {
-        return Ok(Response::not_found(
-            "Group conversation metadata not found"
-        ));
+        response.insert("group_metadata".to_string(), json!({
+            "name": group_conversation_metadata.name,
+            "image": image,
+        }));
     }
 
-    let group_conversation_metadata = option.unwrap();
-
-    let image = match group_conversation_metadata.image {
-        Some(image) => {
-            let collection = db.collection::<ImageStruct>("image");
-            let result = collection.find_one(doc!{
-                "uuid": image
-            }).await;
-
-            if let Err(error) = result {
-                log::error!("{:?}", error);
-                return Ok(Response::internal_server_error(
-                    &error.to_string()
-                ));
-            }
+    if fields.includes("last_text") {
+        // Fetch last message content
+        let last_text = match conversation_core.last_message_id.clone() {
+            Some(last_msg_id) => {
+                let result = get_last_text(&db, &last_msg_id, &fields).await;
+                match result {
+                    Ok(text) => Some(text),
+                    Err(error) => {
+                        return Ok(error);
+                    }
+                }
+            },
+            None => None
+        };
 
-            let option = result.unwrap();
-            if let None = option {
-                return Ok(Response::not_found("Image not found"));
-            }
+        response.insert("last_text".to_string(), json!(last_text));
+    }
 
-            let image = option.unwrap();
-            Some(image)
-        },
-        None => None
-    };
+    if fields.includes("unread_count") {
+        // Count unread messages (messages from others that user hasn't read)
+        let msg_collection = db.collection::<MessageUuidQuery>("message_core");
+        let msg_cursor = msg_collection.find(doc!{
+            "conversation_id": &conversation_core.uuid,
+            "owner": { "$ne": &user_id }
+        }).await;
 
-    // Fetch last message content
-    let last_text = match conversation_core.last_message_id.clone() {
-        Some(last_msg_id) => {
-            let result = get_last_text(&db, &last_msg_id).await;
-            match result {
-                Ok(text) => Some(text),
-                Err(error) => {
-                    return Ok(error);
+        let unread_count = match msg_cursor {
+            Ok(mut cursor) => {
+                let mut other_msg_ids: Vec<String> = Vec::new();
+                while let Some(msg) = cursor.next().await {
+                    if let Ok(msg) = msg {
+                        other_msg_ids.push(msg.uuid);
+                    }
                 }
-            }
-        },
-        None => None
-    };
+                if other_msg_ids.is_empty() {
+                    0
+                } else {
+                    let read_collection = db.collection::<MessageReadQuery>("message_read");
 
-    // Count unread messages (messages from others that user hasn't read)
-    let msg_collection = db.collection::<MessageUuidQuery>("message_core");
-    let msg_cursor = msg_collection.find(doc!{
-        "conversation_id": &conversation_core.uuid,
-        "owner": { "$ne": &user_id }
-    }).await;
+                    let read_count = read_collection.count_documents(doc!{
+                        "message_id": { "$in": &other_msg_ids },
+                        "user_id": &user_id
+                    }).await.unwrap_or(0);
 
-    let unread_count = match msg_cursor {
-        Ok(mut cursor) => {
-            let mut other_msg_ids: Vec<String> = Vec::new();
-            while let Some(msg) = cursor.next().await {
-                if let Ok(msg) = msg {
-                    other_msg_ids.push(msg.uuid);
+                    (other_msg_ids.len() as u64) - read_count
                 }
+            },
+            Err(error) => {
+                log::error!("{:?}", error);
+                return Ok(Response::internal_server_error(&error.to_string()));
             }
-            if other_msg_ids.is_empty() {
-                0
-            } else {
-                let read_collection = db.collection::<MessageReadQuery>("message_read");
+        };
 
-                let read_count = read_collection.count_documents(doc!{
-                    "message_id": { "$in": &other_msg_ids },
-                    "user_id": &user_id
-                }).await.unwrap_or(0);
+        response.insert("unread_count".to_string(), json!(unread_count));
+    }
 
-                (other_msg_ids.len() as u64) - read_count
-            }
-        },
-        Err(error) => {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
-        }
-    };
-    
     Ok(
         HttpResponse::Ok()
         .content_type("application/json")
-        .json(json!({
-            "core": conversation_core,
-            "common_metadata": common_metadata,
-            "last_text": last_text,
-            "unread_count": unread_count,
-            "group_metadata": json!({
-                "name": group_conversation_metadata.name,
-                "image": image,
-            })
-        }))
+        .json(fields.apply(response))
     )
 }
 
-async fn get_last_text(db: &Database, last_msg_id: &str) -> Result<Value, HttpResponse> {
+async fn get_last_text(
+    db: &Database,
+    last_msg_id: &str,
+    fields: &Fields
+) -> Result<Value, HttpResponse> {
     let collection = db.collection::<Conversation::MessageContent>("message_content");
 
     let result = collection.find_one(doc!{
@@ -270,7 +333,7 @@ async fn get_last_text(db: &Database, last_msg_id: &str) -> Result<Value, HttpRe
 
     let mut images: Option<Vec<ImageStruct>> = None;
 
-    if !text_content.images.is_none() {
+    if !text_content.images.is_none() && fields.wants("last_text", "images") {
         let mut images_some = Vec::new();
 
         for image in text_content.images.unwrap().iter() {
@@ -302,7 +365,7 @@ async fn get_last_text(db: &Database, last_msg_id: &str) -> Result<Value, HttpRe
     }
 
     let mut video = None;
-    if !text_content.video.is_none() {
+    if !text_content.video.is_none() && fields.wants("last_text", "video") {
         let collection = db.collection::<ImageStruct>("image");
 
         let result = collection.find_one(doc!{
@@ -1,8 +1,10 @@
 use futures::StreamExt;
 use mongodb::Database;
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 use mongodb::bson::doc;
+use crate::utils::privacy;
 use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::fields::{Fields, Section};
 use serde::{ Serialize, Deserialize };
 use crate::utils::response::Response;
 use actix_web::{web, Error, HttpResponse, HttpRequest};
@@ -14,6 +16,41 @@ use crate::model::{
     ImageStruct,
 };
 
+/* Sections of a single conversation, for `fields=(core,last_text(text,...),...)` */
+pub const FIELDS: &[Section] = &[
+    Section {
+        name: "core",
+        fields: &["uuid", "type", "last_message_at", "last_message_id", "created_at"],
+    },
+    Section {
+        name: "common_metadata",
+        fields: &["is_favorite", "is_muted"],
+    },
+    Section {
+        name: "last_text",
+        fields: &[
+            "uuid", "owner", "conversation_id", "text", "type", "images",
+            "audio", "video", "attachment", "seen_by", "created_at",
+        ],
+    },
+    Section {
+        name: "unread_count",
+        fields: &[],
+    },
+    Section {
+        name: "single_metadata",
+        fields: &[
+            "user_id", "first_name", "last_name", "image", "online",
+            "last_seen", "is_blocked", "am_blocked",
+        ],
+    },
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    fields: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct MessageUuidQuery {
     uuid: String,
@@ -24,7 +61,11 @@ pub struct MessageReadQuery {
     message_id: String,
 }
 
-pub async fn task(req: HttpRequest, conversation_id: web::Path<String>) -> Result<HttpResponse, Error> {
+pub async fn task(
+    req: HttpRequest,
+    conversation_id: web::Path<String>,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
     let user = require_access(
         &req,
         AccessRequirement::AnyToken
@@ -32,6 +73,11 @@ pub async fn task(req: HttpRequest, conversation_id: web::Path<String>) -> Resul
 
     let user_id = user.user_id;
 
+    let fields = match Fields::parse(&req_query.fields, FIELDS) {
+        Ok(fields) => fields,
+        Err(error) => return Ok(error),
+    };
+
     let db = MongoDB.connect();
 
     let collection = db.collection::<Conversation::ConversationCore>("conversation_core");
@@ -52,144 +98,156 @@ pub async fn task(req: HttpRequest, conversation_id: web::Path<String>) -> Resul
 
     let conversation_core = option.unwrap();
 
-    let single_metadata = match conversation_core.r#type {
-        Conversation::ConversationType::Group => {
-            return Ok(Response::bad_request("Not a single conversation"));
-        },
-        Conversation::ConversationType::Single => {
-            let collection = db.collection::<Conversation::ConversationParticipant>("conversation_participant");
+    let mut response = Map::new();
+    response.insert(
+        "core".to_string(),
+        serde_json::to_value(&conversation_core).unwrap()
+    );
 
-            let result = collection.find_one(doc!{
-                "conversation_id": &conversation_core.uuid,
-                "user_id": {
-                    "$ne": &user_id
-                }
-            }).await;
+    if let Conversation::ConversationType::Group = conversation_core.r#type {
+        return Ok(Response::bad_request("Not a single conversation"));
+    }
 
-            if let Err(error) = result {
-                log::error!("{:?}", error);
-                return Ok(Response::internal_server_error(
-                    &error.to_string()
-                ));
-            }
+    if fields.includes("single_metadata") {
+        let collection = db.collection::<Conversation::ConversationParticipant>("conversation_participant");
 
-            let option = result.unwrap();
-            if let None = option {
-                return Ok(Response::not_found(
-                    "Conversation participant not found"
-                ));
+        let result = collection.find_one(doc!{
+            "conversation_id": &conversation_core.uuid,
+            "user_id": {
+                "$ne": &user_id
             }
+        }).await;
 
-            let conversation_participant = option.unwrap();
-            
-            match get_single_metadata(
-                &db,
-                &user_id,
-                &conversation_participant.user_id
-            ).await {
-                Ok(metadata) => metadata,
-                Err(error) => {
-                    return Ok(error);
-                }
-            }
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(
+                &error.to_string()
+            ));
         }
-    };
 
-    // Check if this conversation is favorited by the current user
-    let fav_collection = db.collection::<Conversation::ConversationFavorite>("conversation_favorite");
-    let fav_result = fav_collection.find_one(doc!{
-        "conversation_id": &conversation_core.uuid,
-        "user_id": &user_id
-    }).await;
-
-    let is_favorite = match fav_result {
-        Ok(option) => option.is_some(),
-        Err(error) => {
-            log::error!("{:?}", error);
-            false
+        let option = result.unwrap();
+        if let None = option {
+            return Ok(Response::not_found(
+                "Conversation participant not found"
+            ));
         }
-    };
 
-    // Check if this conversation is muted by the current user
-    let mute_collection = db.collection::<Conversation::ConversationMuted>("conversation_muted");
+        let conversation_participant = option.unwrap();
 
-    let mute_result = mute_collection.find_one(doc!{
-        "conversation_id": &conversation_core.uuid,
-        "user_id": &user_id
-    }).await;
+        let single_metadata = match get_single_metadata(
+            &db,
+            &user_id,
+            &conversation_participant.user_id
+        ).await {
+            Ok(metadata) => metadata,
+            Err(error) => {
+                return Ok(error);
+            }
+        };
 
-    let is_muted = match mute_result {
-        Ok(option) => option.is_some(),
-        Err(error) => {
-            log::error!("{:?}", error);
-            false
-        }
-    };
+        response.insert("single_metadata".to_string(), single_metadata);
+    }
 
-    let common_metadata = json!({
-        "is_favorite": is_favorite,
-        "is_muted": is_muted
-    });
+    if fields.includes("common_metadata") {
+        // Check if this conversation is favorited by the current user
+        let fav_collection = db.collection::<Conversation::ConversationFavorite>("conversation_favorite");
+        let fav_result = fav_collection.find_one(doc!{
+            "conversation_id": &conversation_core.uuid,
+            "user_id": &user_id
+        }).await;
 
-    
-    // Fetch last message content
-    let last_text = match conversation_core.last_message_id.clone() {
-        Some(last_msg_id) => {
-            let result = get_last_text(&db, &last_msg_id).await;
-            match result {
-                Ok(text) => Some(text),
-                Err(error) => {
-                    return Ok(error);
-                }
+        let is_favorite = match fav_result {
+            Ok(option) => option.is_some(),
+            Err(error) => {
+                log::error!("{:?}", error);
+                false
             }
-        },
-        None => None
-    };
+        };
 
-    // Count unread messages (messages from others that user hasn't read)
-    let msg_collection = db.collection::<MessageUuidQuery>("message_core");
-    let msg_cursor = msg_collection.find(doc!{
-        "conversation_id": &conversation_core.uuid,
-        "owner": { "$ne": &user_id }
-    }).await;
+        // Check if this conversation is muted by the current user
+        let mute_collection = db.collection::<Conversation::ConversationMuted>("conversation_muted");
 
-    let unread_count = match msg_cursor {
-        Ok(mut cursor) => {
-            let mut other_msg_ids: Vec<String> = Vec::new();
-            while let Some(msg) = cursor.next().await {
-                if let Ok(msg) = msg {
-                    other_msg_ids.push(msg.uuid);
-                }
+        let mute_result = mute_collection.find_one(doc!{
+            "conversation_id": &conversation_core.uuid,
+            "user_id": &user_id
+        }).await;
+
+        let is_muted = match mute_result {
+            Ok(option) => option.is_some(),
+            Err(error) => {
+                log::error!("{:?}", error);
+                false
             }
+        };
+
+        let common_metadata = json!({
+            "is_favorite": is_favorite,
+            "is_muted": is_muted
+        });
 
-            if other_msg_ids.is_empty() {
-                0
-            } else {
-                let read_collection = db.collection::<MessageReadQuery>("message_read");
-                let read_count = read_collection.count_documents(doc!{
-                    "message_id": { "$in": &other_msg_ids },
-                    "user_id": &user_id
-                }).await.unwrap_or(0);
+        response.insert("common_metadata".to_string(), common_metadata);
+    }
 
-                (other_msg_ids.len() as u64) - read_count
+    if fields.includes("last_text") {
+        // Fetch last message content
+        let last_text = match conversation_core.last_message_id.clone() {
+            Some(last_msg_id) => {
+                let result = get_last_text(&db, &last_msg_id, &fields).await;
+                match result {
+                    Ok(text) => Some(text),
+                    Err(error) => {
+                        return Ok(error);
+                    }
+                }
+            },
+            None => None
+        };
+
+        response.insert("last_text".to_string(), json!(last_text));
+    }
+
+    if fields.includes("unread_count") {
+        // Count unread messages (messages from others that user hasn't read)
+        let msg_collection = db.collection::<MessageUuidQuery>("message_core");
+        let msg_cursor = msg_collection.find(doc!{
+            "conversation_id": &conversation_core.uuid,
+            "owner": { "$ne": &user_id }
+        }).await;
+
+        let unread_count = match msg_cursor {
+            Ok(mut cursor) => {
+                let mut other_msg_ids: Vec<String> = Vec::new();
+                while let Some(msg) = cursor.next().await {
+                    if let Ok(msg) = msg {
+                        other_msg_ids.push(msg.uuid);
+                    }
+                }
+
+                if other_msg_ids.is_empty() {
+                    0
+                } else {
+                    let read_collection = db.collection::<MessageReadQuery>("message_read");
+                    let read_count = read_collection.count_documents(doc!{
+                        "message_id": { "$in": &other_msg_ids },
+                        "user_id": &user_id
+                    }).await.unwrap_or(0);
+
+                    (other_msg_ids.len() as u64) - read_count
+                }
+            },
+            Err(error) => {
+                log::error!("{:?}", error);
+                return Ok(Response::internal_server_error(&error.to_string()));
             }
-        },
-        Err(error) => {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
-        }
-    };
+        };
+
+        response.insert("unread_count".to_string(), json!(unread_count));
+    }
 
     Ok(
         HttpResponse::Ok()
         .content_type("application/json")
-        .json(json!({
-            "core": conversation_core,
-            "common_metadata": common_metadata,
-            "last_text": last_text,
-            "unread_count": unread_count,
-            "single_metadata": single_metadata,
-        }))
+        .json(fields.apply(response))
     )
 }
 
@@ -260,6 +318,17 @@ async fn get_single_metadata(db: &Database, my_id: &str, user_id: &str) -> Resul
 
     let account_status = option.unwrap();
 
+    let (online, last_seen) = match privacy::visible_presence(
+        db,
+        user_id,
+        my_id,
+        account_status.online,
+        account_status.last_seen
+    ).await {
+        Ok(presence) => presence,
+        Err(error) => return Err(error),
+    };
+
     // Check if user is blocked by me
     let collection = db.collection::<Conversation::ConversationBlock>("conversation_block");
     let result = collection.count_documents(doc!{
@@ -297,15 +366,19 @@ async fn get_single_metadata(db: &Database, my_id: &str, user_id: &str) -> Resul
             "first_name": account_profile.first_name,
             "last_name": account_profile.last_name,
             "image": image,
-            "online": account_status.online,
-            "last_seen": account_status.last_seen,
+            "online": online,
+            "last_seen": last_seen,
             "is_blocked": is_blocked,
             "am_blocked": am_blocked
         })
     )
 }
 
-async fn get_last_text(db: &Database, last_msg_id: &str) -> Result<Value, HttpResponse> {
+async fn get_last_text(
+    db: &Database,
+    last_msg_id: &str,
+    fields: &Fields
+) -> Result<Value, HttpResponse> {
     let collection = db.collection::<Conversation::MessageContent>("message_content");
 
     let result = collection.find_one(doc!{
@@ -377,7 +450,7 @@ async fn get_last_text(db: &Database, last_msg_id: &str) -> Result<Value, HttpRe
 
     let mut images: Option<Vec<ImageStruct>> = None;
 
-    if !text_content.images.is_none() {
+    if !text_content.images.is_none() && fields.wants("last_text", "images") {
         let mut images_some = Vec::new();
 
         for image in text_content.images.unwrap().iter() {
@@ -409,7 +482,7 @@ async fn get_last_text(db: &Database, last_msg_id: &str) -> Result<Value, HttpRe
     }
 
     let mut video = None;
-    if !text_content.video.is_none() {
+    if !text_content.video.is_none() && fields.wants("last_text", "video") {
         let collection = db.collection::<ImageStruct>("image");
 
         let result = collection.find_one(doc!{
@@ -2,8 +2,10 @@ use futures::StreamExt;
 use mongodb::Database;
 use serde_json::{json, Value};
 use mongodb::bson::doc;
+use crate::utils::privacy;
 use crate::BuiltIns::mongo::MongoDB;
 use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
 use serde::{ Serialize, Deserialize };
 use actix_web::{web, Error, HttpResponse, HttpRequest};
 use crate::Middleware::Auth::{require_access, AccessRequirement};
@@ -16,6 +18,8 @@ use crate::model::{
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ReqQuery {
+    cursor: Option<String>,
+    paginated: Option<bool>,
     limit: Option<u32>,
     offset: Option<u32>,
     ascending: Option<bool>
@@ -45,6 +49,17 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
 
     let user_id = user.user_id;
 
+    let pagination = match Pagination::new(
+        Keyset::desc("last_message_at", "uuid"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(10),
+        Legacy::Offset(req_query.offset),
+        Shape::Legacy(req_query.paginated)
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
     let db = MongoDB.connect();
     let collection = db.collection::<ParticipantQuery>("conversation_participant");
 
@@ -68,20 +83,12 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
     }
 
     let collection = db.collection::<Conversation::ConversationCore>("conversation_core");
-    
-    let limit = req_query.limit.unwrap_or(10) as i64;
-    let offset = req_query.offset.unwrap_or(0) as i64;
 
-    let result = collection.find(doc!{
+    let result = pagination.find(&collection, doc!{
         "uuid": {
             "$in": conversation_ids
         }
-    }).sort(doc! {
-        "last_message_at": -1,
-        "created_at": -1
-    })
-    .limit(limit)
-    .skip(offset as u64).await;
+    }).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -90,7 +97,7 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
 
     let mut cursor = result.unwrap();
 
-    let mut response = Vec::new();
+    let mut conversation_cores = Vec::new();
 
     while let Some(conversation_core) = cursor.next().await {
         if let Err(error) = conversation_core {
@@ -98,7 +105,20 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
             return Ok(Response::internal_server_error(&error.to_string()));
         }
 
-        let conversation_core = conversation_core.unwrap();
+        conversation_cores.push(conversation_core.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut conversation_cores,
+        |conversation_core| (
+            conversation_core.last_message_at,
+            conversation_core.uuid.clone()
+        )
+    );
+
+    let mut response = Vec::new();
+
+    for conversation_core in conversation_cores {
 
         // Check if this conversation is favorited by the current user
         let fav_collection = db.collection::<Conversation::ConversationFavorite>("conversation_favorite");
@@ -293,11 +313,7 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
         }
     }
 
-    Ok(
-        HttpResponse::Ok()
-        .content_type("application/json")
-        .json(response)
-    )
+    Ok(pagination.response("conversations", response, page_info))
 }
 
 async fn get_single_metadata(db: &Database, my_id: &str, user_id: &str) -> Result<Value, HttpResponse> {
@@ -367,6 +383,17 @@ async fn get_single_metadata(db: &Database, my_id: &str, user_id: &str) -> Resul
 
     let account_status = option.unwrap();
 
+    let (online, last_seen) = match privacy::visible_presence(
+        db,
+        user_id,
+        my_id,
+        account_status.online,
+        account_status.last_seen
+    ).await {
+        Ok(presence) => presence,
+        Err(error) => return Err(error),
+    };
+
     // Check if user is blocked by me
     let collection = db.collection::<Conversation::ConversationBlock>("conversation_block");
     let result = collection.count_documents(doc!{
@@ -404,8 +431,8 @@ async fn get_single_metadata(db: &Database, my_id: &str, user_id: &str) -> Resul
             "first_name": account_profile.first_name,
             "last_name": account_profile.last_name,
             "image": image,
-            "online": account_status.online,
-            "last_seen": account_status.last_seen,
+            "online": online,
+            "last_seen": last_seen,
             "is_blocked": is_blocked,
             "am_blocked": am_blocked
         })
@@ -2,7 +2,9 @@ use futures::StreamExt;
 use serde_json::{json, Value};
 use mongodb::bson::doc;
 use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::link_preview;
 use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
 use serde::{ Serialize, Deserialize };
 use actix_web::{web, Error, HttpResponse, HttpRequest};
 use crate::Middleware::Auth::{require_access, AccessRequirement};
@@ -12,6 +14,8 @@ use crate::model::{Conversation, ImageStruct};
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ReqQuery {
     conversation_id: String,
+    cursor: Option<String>,
+    paginated: Option<bool>,
     limit: Option<u32>,
     offset: Option<u32>,
 }
@@ -25,6 +29,17 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
     let user_id = user.user_id;
     let conversation_id = &req_query.conversation_id;
 
+    let pagination = match Pagination::new(
+        Keyset::desc("created_at", "uuid"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20),
+        Legacy::Offset(req_query.offset),
+        Shape::Legacy(req_query.paginated)
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
     let db = MongoDB.connect();
 
     // Verify user is a participant of this conversation
@@ -48,16 +63,9 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
     // Fetch message cores for the conversation
     let collection = db.collection::<Conversation::MessageCore>("message_core");
 
-    let limit = req_query.limit.unwrap_or(20) as i64;
-    let offset = req_query.offset.unwrap_or(0) as i64;
-
-    let result = collection.find(doc!{
+    let result = pagination.find(&collection, doc!{
         "conversation_id": conversation_id
-    }).sort(doc! {
-        "created_at": -1
-    })
-    .limit(limit)
-    .skip(offset as u64).await;
+    }).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -66,7 +74,7 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
 
     let mut cursor = result.unwrap();
 
-    let mut response = Vec::new();
+    let mut message_cores = Vec::new();
 
     while let Some(message_core) = cursor.next().await {
         if let Err(error) = message_core {
@@ -74,8 +82,17 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
             return Ok(Response::internal_server_error(&error.to_string()));
         }
 
-        let message_core = message_core.unwrap();
+        message_cores.push(message_core.unwrap());
+    }
 
+    let page_info = pagination.finish(
+        &mut message_cores,
+        |message_core| (message_core.created_at, message_core.uuid.clone())
+    );
+
+    let mut response = Vec::new();
+
+    for message_core in message_cores {
         let text = get_text(&message_core).await;
         match text {
             Ok(text) => response.push(text),
@@ -83,11 +100,7 @@ pub async fn task(req: HttpRequest, req_query: web::Query<ReqQuery>) -> Result<H
         }
     }
 
-    Ok(
-        HttpResponse::Ok()
-        .content_type("application/json")
-        .json(response)
-    )
+    Ok(pagination.response("messages", response, page_info))
 }
 
 async fn get_text(text_core: &Conversation::MessageCore) -> Result<Value, HttpResponse> {
@@ -197,6 +210,14 @@ async fn get_text(text_core: &Conversation::MessageCore) -> Result<Value, HttpRe
         video = Some(option.unwrap());
     }
 
+    let urls = match &text_content.text {
+        Some(text) => link_preview::extract_urls(text),
+        None => Vec::new(),
+    };
+
+    let cache = link_preview::get_cached(&db, &urls).await?;
+    let link_previews = link_preview::previews_of(&text_content.text, &cache);
+
     Ok(json!({
         "uuid": text_core.uuid,
         "owner": text_core.owner,
@@ -207,6 +228,7 @@ async fn get_text(text_core: &Conversation::MessageCore) -> Result<Value, HttpRe
         "audio": text_content.audio,
         "video": video,
         "attachment": text_content.attachment,
+        "link_previews": link_previews,
         "seen_by": seen_by,
         "created_at": text_core.created_at,
     }))
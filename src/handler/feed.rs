@@ -0,0 +1,5 @@
+pub mod home;
+pub use home as Home;
+
+pub mod for_you;
+pub use for_you as ForYou;
\ No newline at end of file
@@ -0,0 +1,503 @@
+use std::env;
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use std::collections::HashMap;
+use crate::utils::visibility::{unpublished_states, Viewer};
+use mongodb::{Database, bson::doc};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::mongo;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{ web, Error, HttpResponse, HttpRequest };
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::Handler::Post::Get::build_posts;
+use crate::model::{
+    Account::AccountRole,
+    Post::{
+        FeedSeen,
+        PostCore,
+        PostLike,
+        PostStat,
+        PostTag,
+        PostVisibility,
+    },
+};
+
+/* How far back candidates are looked up and how many are scored */
+pub const CANDIDATE_WINDOW_DAYS: i64 = 7;
+const CANDIDATE_LIMIT: i64 = 500;
+
+/* How many of the viewer's recent likes describe their interests */
+const INTEREST_SAMPLE: i64 = 200;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    limit: Option<u32>,
+    debug: Option<bool>,
+}
+
+/*
+    Ranking weights, read from the environment so they can be tuned
+    without a release. Missing or invalid values fall back to defaults.
+*/
+#[derive(Debug, Serialize, Clone)]
+pub struct RankingWeights {
+    pub like: f64,
+    pub comment: f64,
+    pub share: f64,
+    pub view: f64,
+    pub affinity: f64,
+    pub tag: f64,
+    pub recency_half_life_hours: f64,
+}
+
+impl RankingWeights {
+    pub fn from_env() -> Self {
+        fn read(key: &str, default: f64) -> f64 {
+            env::var(key).ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(default)
+        }
+
+        RankingWeights {
+            like: read("FEED_WEIGHT_LIKE", 1.0),
+            comment: read("FEED_WEIGHT_COMMENT", 2.0),
+            share: read("FEED_WEIGHT_SHARE", 3.0),
+            view: read("FEED_WEIGHT_VIEW", 0.05),
+            affinity: read("FEED_WEIGHT_AFFINITY", 1.5),
+            tag: read("FEED_WEIGHT_TAG", 2.0),
+            recency_half_life_hours: read("FEED_RECENCY_HALF_LIFE_HOURS", 24.0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScoreBreakdown {
+    pub engagement: f64,
+    pub recency: f64,
+    pub affinity: f64,
+    pub tag_overlap: f64,
+    pub score: f64,
+}
+
+/* What the viewer engages with, derived from their recent likes */
+struct ViewerSignals {
+    author_likes: HashMap<String, i64>,
+    tag_interest: HashMap<String, f64>,
+}
+
+/*
+    Ranked discovery feed.
+    Posts already served to the viewer are never returned again,
+    `debug` (administrators only) adds the score breakdown of every post.
+*/
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let limit = req_query.limit.unwrap_or(20).clamp(1, 50) as usize;
+    let debug = req_query.debug.unwrap_or(false);
+
+    if debug && user.role != AccountRole::Administrator {
+        return Ok(Response::forbidden("Debug mode is for administrators only"));
+    }
+
+    let db = MongoDB.connect();
+    let weights = RankingWeights::from_env();
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    let signals = match get_viewer_signals(&db, &user_id).await {
+        Ok(signals) => signals,
+        Err(error) => return Ok(error),
+    };
+
+    let candidates = match get_candidates(&db, &mut viewer).await {
+        Ok(candidates) => candidates,
+        Err(error) => return Ok(error),
+    };
+
+    let candidate_ids: Vec<String> = candidates.iter()
+    .map(|post| post.uuid.clone())
+    .collect();
+
+    let stats = match get_stats(&db, &candidate_ids).await {
+        Ok(stats) => stats,
+        Err(error) => return Ok(error),
+    };
+
+    let tags = match get_tags(&db, &candidate_ids).await {
+        Ok(tags) => tags,
+        Err(error) => return Ok(error),
+    };
+
+    let now = Utc::now().timestamp_millis();
+
+    let mut ranked: Vec<(ScoreBreakdown, PostCore)> = candidates.into_iter()
+    .map(|post| {
+        let breakdown = score(
+            &weights,
+            &viewer,
+            &signals,
+            &post,
+            stats.get(&post.uuid),
+            tags.get(&post.uuid),
+            now
+        );
+
+        (breakdown, post)
+    })
+    .collect();
+
+    ranked.sort_by(|a, b| b.0.score.total_cmp(&a.0.score));
+    ranked.truncate(limit);
+
+    let (breakdowns, post_cores): (Vec<ScoreBreakdown>, Vec<PostCore>) = ranked
+    .into_iter()
+    .unzip();
+
+    let mut posts = match build_posts(&db, &mut viewer, &post_cores).await {
+        Ok(posts) => posts,
+        Err(error) => return Ok(error),
+    };
+
+    if debug {
+        for (post, breakdown) in posts.iter_mut().zip(breakdowns.iter()) {
+            post.insert(
+                "ranking".to_string(),
+                serde_json::to_value(breakdown).unwrap()
+            );
+        }
+    }
+
+    let served: Vec<String> = post_cores.iter()
+    .map(|post| post.uuid.clone())
+    .collect();
+
+    if let Err(error) = mark_seen(&db, &user_id, &served, now).await {
+        return Ok(error);
+    }
+
+    let mut response = json!({"posts": posts});
+    if debug {
+        response["weights"] = serde_json::to_value(&weights).unwrap();
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(response)
+    )
+}
+
+fn score(
+    weights: &RankingWeights,
+    viewer: &Viewer,
+    signals: &ViewerSignals,
+    post: &PostCore,
+    stat: Option<&PostStat>,
+    tags: Option<&Vec<String>>,
+    now: i64
+) -> ScoreBreakdown {
+    let engagement = match stat {
+        Some(stat) => (
+            1.0 +
+            weights.like * stat.like_count as f64 +
+            weights.comment * stat.comment_count as f64 +
+            weights.share * stat.share_count as f64 +
+            weights.view * stat.view_count as f64
+        ).ln(),
+        None => 0.0,
+    };
+
+    let age_hours = (now - post.created_at).max(0) as f64 / 3_600_000.0;
+    let recency = 0.5_f64.powf(
+        age_hours / weights.recency_half_life_hours.max(1.0)
+    );
+
+    let mut affinity = 0.0;
+    if viewer.is_friend(&post.owner) {
+        affinity += 1.0;
+    }
+    if viewer.is_following(&post.owner) {
+        affinity += 0.5;
+    }
+    if let Some(likes) = signals.author_likes.get(&post.owner) {
+        affinity += (1.0 + *likes as f64).ln();
+    }
+
+    let tag_overlap: f64 = match tags {
+        Some(tags) => tags.iter()
+        .filter_map(|tag| signals.tag_interest.get(tag))
+        .sum(),
+        None => 0.0,
+    };
+
+    let score = engagement * recency +
+    weights.affinity * affinity * recency +
+    weights.tag * tag_overlap * recency;
+
+    ScoreBreakdown {
+        engagement,
+        recency,
+        affinity,
+        tag_overlap,
+        score,
+    }
+}
+
+async fn get_viewer_signals(
+    db: &Database,
+    user_id: &str
+) -> Result<ViewerSignals, HttpResponse> {
+    let collection = db.collection::<PostLike>("post_like");
+    let result = collection.find(doc!{"liked_by": user_id, "deleted_at": null})
+    .sort(doc!{"liked_at": -1})
+    .limit(INTEREST_SAMPLE).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut liked_ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        liked_ids.push(result.unwrap().post_id);
+    }
+
+    let mut author_likes = HashMap::new();
+    let mut tag_interest = HashMap::new();
+
+    if liked_ids.is_empty() {
+        return Ok(ViewerSignals {
+            author_likes,
+            tag_interest,
+        });
+    }
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find(doc!{"uuid": {"$in": &liked_ids}}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        *author_likes.entry(result.unwrap().owner).or_insert(0) += 1;
+    }
+
+    // Tag interest is the share of liked posts carrying the tag
+    let liked_tags = get_tags(db, &liked_ids).await?;
+    let total = liked_ids.len() as f64;
+    for tags in liked_tags.values() {
+        for tag in tags {
+            *tag_interest.entry(tag.clone()).or_insert(0.0) += 1.0 / total;
+        }
+    }
+
+    Ok(ViewerSignals {
+        author_likes,
+        tag_interest,
+    })
+}
+
+/*
+    Recent active posts the viewer may see and hasn't been served yet.
+    Public posts come from anyone, restricted ones only from the
+    friends and followed users allowed to see them.
+*/
+async fn get_candidates(
+    db: &Database,
+    viewer: &mut Viewer
+) -> Result<Vec<PostCore>, HttpResponse> {
+    let user_id = viewer.user_id.clone();
+
+    let since = Utc::now().timestamp_millis() -
+    CANDIDATE_WINDOW_DAYS * 24 * 3_600_000;
+
+    // Posts seen before the window are older than it and can't come back
+    let collection = db.collection::<FeedSeen>("feed_seen");
+    let result = collection.distinct("post_id", doc!{
+        "user_id": &user_id,
+        "seen_at": {"$gte": since}
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let seen = result.unwrap();
+
+    let mut excluded_owners = viewer.blocked().clone();
+    excluded_owners.push(user_id.clone());
+
+    let friends_and_following = [
+        viewer.friends().as_slice(),
+        viewer.following().as_slice()
+    ].concat();
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find(doc!{
+        "uuid": {"$nin": seen},
+        "owner": {"$nin": &excluded_owners},
+        "created_at": {"$gte": since},
+        "publish_state": {"$nin": unpublished_states()},
+        "deleted_at": null,
+        "suspended_at": null,
+        "$or": [
+            {"visibility": PostVisibility::Public.to_string()},
+            {
+                "visibility": PostVisibility::FriendAndFollowers.to_string(),
+                "owner": {"$in": &friends_and_following},
+            },
+            {
+                "visibility": PostVisibility::FieldsOnly.to_string(),
+                "owner": {"$in": viewer.friends()},
+            },
+        ]
+    })
+    .sort(doc!{"created_at": -1})
+    .limit(CANDIDATE_LIMIT).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut candidates = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let post_core = result.unwrap();
+
+        if !viewer.can_view_post(db, &post_core).await? {
+            continue;
+        }
+
+        candidates.push(post_core);
+    }
+
+    Ok(candidates)
+}
+
+async fn get_stats(
+    db: &Database,
+    post_ids: &Vec<String>
+) -> Result<HashMap<String, PostStat>, HttpResponse> {
+    let mut stats = HashMap::new();
+    if post_ids.is_empty() {
+        return Ok(stats);
+    }
+
+    let collection = db.collection::<PostStat>("post_stat");
+    let result = collection.find(doc!{"uuid": {"$in": post_ids}}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let stat = result.unwrap();
+        stats.insert(stat.uuid.clone(), stat);
+    }
+
+    Ok(stats)
+}
+
+async fn get_tags(
+    db: &Database,
+    post_ids: &Vec<String>
+) -> Result<HashMap<String, Vec<String>>, HttpResponse> {
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    if post_ids.is_empty() {
+        return Ok(tags);
+    }
+
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = collection.find(doc!{"post_id": {"$in": post_ids}}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let post_tag = result.unwrap();
+        tags.entry(post_tag.post_id).or_default().push(post_tag.tag);
+    }
+
+    Ok(tags)
+}
+
+async fn mark_seen(
+    db: &Database,
+    user_id: &str,
+    post_ids: &Vec<String>,
+    now: i64
+) -> Result<(), HttpResponse> {
+    if post_ids.len() == 0 {
+        return Ok(());
+    }
+
+    let seen: Vec<FeedSeen> = post_ids.iter()
+    .map(|post_id| FeedSeen {
+        post_id: post_id.clone(),
+        user_id: user_id.to_string(),
+        seen_at: now,
+    })
+    .collect();
+
+    // Posts a concurrent request served already stay marked as they are
+    let collection = db.collection::<FeedSeen>("feed_seen");
+    let result = collection.insert_many(seen).ordered(false).await;
+
+    if let Err(error) = result {
+        if !mongo::is_duplicate_key(&error) {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use futures::StreamExt;
+use crate::utils::visibility::{unpublished_states, Viewer};
+use mongodb::{Database, bson::{Document, doc}};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{ web, Error, HttpResponse, HttpRequest };
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::Handler::Post::Get::build_posts;
+use crate::model::{
+    Page::PageFollow,
+    Post::{PostCore, PostOwnerType, PostTag, PostVisibility, TagFollow},
+};
+
+/* Most followed tag posts considered per page of the feed */
+const MAX_TAGGED_POSTS: i64 = 500;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+/*
+    Chronological feed of the viewer's own posts and the posts of their
+    friends, followed users, followed pages and followed tags.
+    Pages are walked with an opaque (created_at, uuid) cursor so posts
+    created while scrolling never shift the next page.
+*/
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let pagination = match Pagination::new(
+        Keyset::desc("created_at", "uuid"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20).min(50),
+        Legacy::Page(None),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    let before = pagination.cursor().map(|cursor| cursor.at);
+    let filter = match get_source_filter(&db, &viewer, before).await {
+        Ok(filter) => filter,
+        Err(error) => return Ok(error),
+    };
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = pagination.find(&collection, filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut post_cores = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        post_cores.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut post_cores,
+        |post_core| (post_core.created_at, post_core.uuid.clone())
+    );
+
+    let mut visible = Vec::new();
+    for post_core in post_cores {
+        match viewer.can_view_post(&db, &post_core).await {
+            Ok(true) => visible.push(post_core),
+            Ok(false) => continue,
+            Err(error) => return Ok(error),
+        }
+    }
+
+    let posts = match build_posts(&db, &mut viewer, &visible).await {
+        Ok(posts) => posts,
+        Err(error) => return Ok(error),
+    };
+
+    Ok(pagination.response("posts", posts, page_info))
+}
+
+/*
+    Narrows the home feed to the viewer's sources in the query itself,
+    the final say on every post stays with `Viewer::can_view_post`.
+*/
+async fn get_source_filter(
+    db: &Database,
+    viewer: &Viewer,
+    before: Option<i64>
+) -> Result<Document, HttpResponse> {
+    let user_id = viewer.user_id.as_str();
+
+    let friends: Vec<String> = viewer.friends().iter()
+    .filter(|id| !viewer.is_blocked(id))
+    .cloned()
+    .collect();
+
+    let following: Vec<String> = viewer.following().iter()
+    .filter(|id| !viewer.is_blocked(id) && !viewer.is_friend(id))
+    .cloned()
+    .collect();
+
+    let pages = get_followed_page_ids(db, user_id).await?;
+    let tagged = get_followed_tag_post_ids(db, user_id, before).await?;
+
+    Ok(doc!{
+        "publish_state": {"$nin": unpublished_states()},
+        "deleted_at": null,
+        "suspended_at": null,
+        "$or": [
+            {
+                "owner_type": PostOwnerType::User.to_string(),
+                "owner": user_id,
+            },
+            {
+                "owner_type": PostOwnerType::User.to_string(),
+                "owner": {"$in": &friends},
+            },
+            {
+                "owner_type": PostOwnerType::User.to_string(),
+                "owner": {"$in": &following},
+                "visibility": {"$in": [
+                    PostVisibility::Public.to_string(),
+                    PostVisibility::FriendAndFollowers.to_string(),
+                ]},
+            },
+            {
+                "owner_type": PostOwnerType::Page.to_string(),
+                "owner": {"$in": &pages},
+            },
+            {
+                "uuid": {"$in": &tagged},
+            },
+        ]
+    })
+}
+
+async fn get_followed_page_ids(
+    db: &Database,
+    user_id: &str
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<PageFollow>("page_follow");
+    let result = collection.find(doc!{"followed_by": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        ids.push(result.unwrap().page_id);
+    }
+
+    Ok(ids)
+}
+
+/*
+    Newest posts carrying a followed tag, older than the cursor.
+    Bounded so following a busy tag can't blow up the feed query.
+*/
+async fn get_followed_tag_post_ids(
+    db: &Database,
+    user_id: &str,
+    before: Option<i64>
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<TagFollow>("tag_follow");
+    let result = collection.find(doc!{"followed_by": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut tags = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        tags.push(result.unwrap().tag);
+    }
+
+    if tags.len() == 0 {
+        return Ok(Vec::new());
+    }
+
+    // `$lte` keeps posts sharing the cursor's timestamp
+    let mut filter = doc!{
+        "tag": {"$in": &tags},
+        "created_at": {"$ne": null}
+    };
+
+    if let Some(before) = before {
+        filter.insert("created_at", doc!{"$lte": before});
+    }
+
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = collection.find(filter)
+    .sort(doc!{"created_at": -1})
+    .limit(MAX_TAGGED_POSTS).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let post_id = result.unwrap().post_id;
+        if !ids.contains(&post_id) {
+            ids.push(post_id);
+        }
+    }
+
+    Ok(ids)
+}
@@ -5,13 +5,23 @@ use webp::Encoder;
 use std::collections::HashMap;
 use actix_multipart::Multipart;
 use crate::utils::response::Response;
-use actix_web::{Error, HttpResponse};
+use actix_web::{Error, HttpResponse, HttpRequest};
+use mongodb::Database;
 use image::io::Reader as ImageReader;
 use crate::builtins::{mongo::MongoDB, sqlite};
 use crate::model::{AllowedImageType, ImageStruct, AssetUsedAt};
+use crate::middleware::auth::{require_access, AccessRequirement};
 
 
-pub async fn task(mut payload: Multipart) -> Result<HttpResponse, Error> {
+pub async fn task(
+    req: HttpRequest,
+    mut payload: Multipart
+) -> Result<HttpResponse, Error> {
+    // Anonymous uploads stay possible, the uploader is recorded when known
+    let uploaded_by = require_access(&req, AccessRequirement::AnyToken)
+    .ok()
+    .map(|user| user.user_id);
+
     let mut images_data: Vec<(String, Vec<u8>)> = Vec::new();
     let mut text_fields: HashMap<String, String> = HashMap::new();
     let mut image_ids: Vec<String> = Vec::new();
@@ -61,9 +71,6 @@ pub async fn task(mut payload: Multipart) -> Result<HttpResponse, Error> {
     }
 
     let db = MongoDB.connect();
-    let collection = db.collection::<ImageStruct>("image");
-    let created_at = Utc::now().timestamp_millis();
-    let sqlite_conn = sqlite::connect(sqlite::DBF::IMG).unwrap();
 
     for (field_name, bytes) in images_data.iter() {
         let index: usize = field_name
@@ -98,75 +105,114 @@ pub async fn task(mut payload: Multipart) -> Result<HttpResponse, Error> {
             .get(&format!("temporary_{}", index))
             .unwrap();
 
-        // Converting to webp
-        let webp_bytes = match convert_to_webp(bytes.clone()) {
-            Ok(bytes) => bytes,
-            Err(error) => {
-                return Ok(Response::internal_server_error(&error.to_string()));
-            },
-        };
+        let used_at = AssetUsedAt::from_str(used_at.as_str());
 
-        let image_type = match imghdr::from_bytes(bytes) {
-            Some(image_type) => match image_type {
-                imghdr::Type::Gif => AllowedImageType::Gif,
-                imghdr::Type::Png => AllowedImageType::Png,
-                imghdr::Type::Jpeg => AllowedImageType::Jpeg,
-                imghdr::Type::Webp => AllowedImageType::Webp,
-                _ => {
-                    return Ok(Response::internal_server_error(
-                        "Unsupported image format!"
-                    ));
-                }
-            }, 
-            None => {
-                return Ok(Response::internal_server_error(
-                    "Invalid image format!"
+        // Verification documents are tied to the account submitting them
+        if let AssetUsedAt::VerificationDocument = used_at {
+            if uploaded_by.is_none() {
+                return Ok(Response::unauthorized(
+                    "Verification documents require signing in"
                 ));
-            },
-        };
-    
-        // Creating the metadata in mongo
-        let image_doc = ImageStruct {
-            uuid: uuid.clone(),
-            blur_hash: blur_hash.clone(),
-            width: width.parse().unwrap(),
-            height: height.parse().unwrap(),
-            created_at,
-            original_size: bytes.len(),
-            webp_size: webp_bytes.len(),
-            used_at: AssetUsedAt::from_str(used_at.as_str()),
-            temporary: temporary.parse().unwrap(),
-            deleted: false,
-            original_type: image_type.to_str().to_string(),
-        };
-
-        let result = collection.insert_one(image_doc.clone()).await;
-        if let Err(error) = result {
-            return Ok(Response::internal_server_error(&error.to_string()));
+            }
         }
 
-        // Uploading image to sqlite
-        let result = sqlite_conn.execute("
-            INSERT INTO image (uuid, original, webp)
-            VALUES (?1, ?2, ?3)",
-            (
-                &uuid,
-                &bytes,
-                &webp_bytes
-            )
-        );
-
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
-        } else {
-            image_ids.push(uuid.clone());
+        let result = save_image(
+            &db,
+            uuid,
+            bytes,
+            blur_hash,
+            width.parse().unwrap(),
+            height.parse().unwrap(),
+            used_at,
+            temporary.parse().unwrap(),
+            uploaded_by.clone(),
+        ).await;
+
+        match result {
+            Ok(image) => image_ids.push(image.uuid),
+            Err(error) => return Ok(Response::internal_server_error(&error)),
         }
     }
 
     Ok(HttpResponse::Ok().content_type("application/json").json(image_ids))
 }
 
+/*
+    Stores an image the way every upload does: the original and a webp
+    copy go to sqlite, the metadata to mongo.
+*/
+pub async fn save_image(
+    db: &Database,
+    uuid: &str,
+    bytes: &Vec<u8>,
+    blur_hash: &str,
+    width: usize,
+    height: usize,
+    used_at: AssetUsedAt,
+    temporary: bool,
+    uploaded_by: Option<String>,
+) -> Result<ImageStruct, String> {
+    // Converting to webp
+    let webp_bytes = convert_to_webp(bytes.clone())?;
+
+    let image_type = match imghdr::from_bytes(bytes) {
+        Some(image_type) => match image_type {
+            imghdr::Type::Gif => AllowedImageType::Gif,
+            imghdr::Type::Png => AllowedImageType::Png,
+            imghdr::Type::Jpeg => AllowedImageType::Jpeg,
+            imghdr::Type::Webp => AllowedImageType::Webp,
+            _ => return Err("Unsupported image format!".to_string()),
+        },
+        None => return Err("Invalid image format!".to_string()),
+    };
+
+    // Creating the metadata in mongo
+    let image_doc = ImageStruct {
+        uuid: uuid.to_string(),
+        blur_hash: blur_hash.to_string(),
+        width,
+        height,
+        created_at: Utc::now().timestamp_millis(),
+        original_size: bytes.len(),
+        webp_size: webp_bytes.len(),
+        used_at,
+        temporary,
+        deleted: false,
+        uploaded_by,
+        original_type: image_type.to_str().to_string(),
+    };
+
+    let collection = db.collection::<ImageStruct>("image");
+    let result = collection.insert_one(image_doc.clone()).await;
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(error.to_string());
+    }
+
+    // Uploading image to sqlite
+    let sqlite_conn = match sqlite::connect(sqlite::DBF::IMG) {
+        Ok(conn) => conn,
+        Err(error) => return Err(error.to_string()),
+    };
+
+    let result = sqlite_conn.execute("
+        INSERT INTO image (uuid, original, webp)
+        VALUES (?1, ?2, ?3)",
+        (
+            uuid,
+            bytes,
+            &webp_bytes
+        )
+    );
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(error.to_string());
+    }
+
+    Ok(image_doc)
+}
+
 fn convert_to_webp(image_bytes: Vec<u8>) -> Result<Vec<u8>, String> {
     // Decode the image (autodetects format)
     let reader = match ImageReader::new(std::io::Cursor::new(image_bytes))
@@ -1,10 +1,14 @@
 use mongodb::bson::doc;
-use crate::model::ImageStruct;
 use crate::utils::response::Response;
 use crate::builtins::{mongo::MongoDB, sqlite};
-use actix_web::{web, Error, HttpResponse};
-
-pub async fn task(image_id: web::Path<String>) -> Result<HttpResponse, Error> {
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::model::{AssetUsedAt, ImageStruct, Account::AccountRole};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+
+pub async fn task(
+    req: HttpRequest,
+    image_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
     let image_id = image_id.trim().to_string();
     let sqlite_conn = sqlite::connect(sqlite::DBF::IMG).unwrap();
 
@@ -41,6 +45,14 @@ pub async fn task(image_id: web::Path<String>) -> Result<HttpResponse, Error> {
         return Ok(Response::not_found("Image not found!"));
     }
 
+    // Verification documents are private to the review team
+    if let AssetUsedAt::VerificationDocument = image_meta.used_at {
+        let _ = require_access(
+            &req,
+            AccessRequirement::Role(AccountRole::Administrator)
+        )?;
+    }
+
     Ok(
         HttpResponse::Ok()
         .content_type("image/webp")
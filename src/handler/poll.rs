@@ -0,0 +1,8 @@
+pub mod vote;
+pub use vote as Vote;
+
+pub mod close;
+pub use close as Close;
+
+pub mod voters;
+pub use voters as Voters;
\ No newline at end of file
@@ -0,0 +1,70 @@
+use chrono::Utc;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::Handler::Post::{Edit::can_edit, Get::get_poll};
+use crate::model::Poll::Poll;
+use super::Vote::get_poll_post;
+
+/* Closes the poll early, only whoever can edit the post may do so */
+pub async fn task(
+    req: HttpRequest,
+    poll_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let poll_id = poll_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let (_, post_core) = match get_poll_post(&db, &poll_id, &user_id).await {
+        Ok(value) => value,
+        Err(error) => return Ok(error),
+    };
+
+    match can_edit(&db, &post_core, &user_id).await {
+        Ok(true) => (),
+        Ok(false) => return Ok(Response::forbidden(
+            "You are not authorized to close this poll"
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    let now = Utc::now().timestamp_millis();
+
+    let collection = db.collection::<Poll>("poll");
+    let result = collection.update_one(
+        doc!{
+            "uuid": &poll_id,
+            "$or": [
+                {"closes_at": null},
+                {"closes_at": {"$gt": now}}
+            ]
+        },
+        doc!{"$set": {"closes_at": now}},
+    ).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().matched_count == 0 {
+        return Ok(Response::bad_request("Poll is already closed"));
+    }
+
+    match get_poll(&db, &Some(poll_id), &user_id).await {
+        Ok(poll) => Ok(
+            HttpResponse::Ok()
+            .content_type("application/json")
+            .json(poll)
+        ),
+        Err(error) => Ok(error),
+    }
+}
@@ -0,0 +1,276 @@
+use chrono::Utc;
+use mongodb::error::TRANSIENT_TRANSACTION_ERROR;
+use mongodb::{Database, bson::{doc, Document}};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::mongo;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::Handler::Post::Get::get_poll;
+use crate::model::{
+    Poll::{Poll, PollStat, PollType, PollVote},
+    Post::PostCore,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    options: Vec<String>,
+}
+
+/*
+    Sets the user's vote on a poll to `options`.
+    Votes can be changed until the poll closes, an empty list retracts
+    the vote. Tallies in `poll_stat` move by the difference only.
+*/
+pub async fn task(
+    req: HttpRequest,
+    poll_id: web::Path<String>,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let poll_id = poll_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let (poll, _) = match get_poll_post(&db, &poll_id, &user_id).await {
+        Ok(value) => value,
+        Err(error) => return Ok(error),
+    };
+
+    let now = Utc::now().timestamp_millis();
+    if let Some(closes_at) = poll.closes_at {
+        if closes_at <= now {
+            return Ok(Response::bad_request("Poll is closed"));
+        }
+    }
+
+    let mut options: Vec<String> = Vec::new();
+    for option in &req_body.options {
+        if !poll.options.contains(option) {
+            return Ok(Response::bad_request(&format!(
+                "Option '{}' doesn't exist", option
+            )));
+        }
+
+        if options.contains(option) {
+            return Ok(Response::bad_request(&format!(
+                "Option '{}' is selected more than once", option
+            )));
+        }
+
+        options.push(option.clone());
+    }
+
+    if let PollType::Single = poll.r#type {
+        if options.len() > 1 {
+            return Ok(Response::bad_request(
+                "Only one option can be selected"
+            ));
+        }
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    // Every vote on the poll writes its stat first, so concurrent votes
+    // conflict instead of both reading the same previous votes.
+    // Polls created before tallies existed have no stat yet
+    let stat_collection = db.collection::<PollStat>("poll_stat");
+    let result = stat_collection.update_one(
+        doc!{"uuid": &poll_id},
+        doc!{
+            "$setOnInsert": {
+                "option_votes": vec![0_i64; poll.options.len()],
+                "voter_count": 0_i64,
+            },
+            "$set": {"modified_at": now}
+        },
+    ).upsert(true).session(&mut session).await;
+
+    if let Err(error) = result {
+        session.abort_transaction().await.ok();
+        return Ok(vote_write_error(error));
+    }
+
+    let collection = db.collection::<PollVote>("poll_vote");
+    let result = collection.find(doc!{
+        "poll_id": &poll_id,
+        "user_id": &user_id,
+    }).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut previous: Vec<String> = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next(&mut session).await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        previous.push(result.unwrap().option);
+    }
+
+    let removed: Vec<String> = previous.iter()
+    .filter(|option| !options.contains(option))
+    .cloned()
+    .collect();
+
+    let added: Vec<String> = options.iter()
+    .filter(|option| !previous.contains(option))
+    .cloned()
+    .collect();
+
+    if removed.len() > 0 || added.len() > 0 {
+        let mut increments = Document::new();
+        for (index, option) in poll.options.iter().enumerate() {
+            if removed.contains(option) {
+                increments.insert(format!("option_votes.{}", index), -1_i64);
+            }
+
+            if added.contains(option) {
+                increments.insert(format!("option_votes.{}", index), 1_i64);
+            }
+        }
+
+        if previous.len() == 0 && options.len() > 0 {
+            increments.insert("voter_count", 1_i64);
+        }
+
+        if previous.len() > 0 && options.len() == 0 {
+            increments.insert("voter_count", -1_i64);
+        }
+
+        if removed.len() > 0 {
+            let result = collection.delete_many(doc!{
+                "poll_id": &poll_id,
+                "user_id": &user_id,
+                "option": {"$in": &removed}
+            }).session(&mut session).await;
+
+            if let Err(error) = result {
+                log::error!("{:?}", error);
+                session.abort_transaction().await.ok().unwrap();
+                return Ok(Response::internal_server_error(&error.to_string()));
+            }
+        }
+
+        if added.len() > 0 {
+            let votes: Vec<PollVote> = added.iter()
+            .map(|option| PollVote {
+                poll_id: poll_id.clone(),
+                user_id: user_id.clone(),
+                option: option.clone(),
+                voted_at: Some(now),
+            })
+            .collect();
+
+            let result = collection.insert_many(&votes)
+            .session(&mut session).await;
+
+            if let Err(error) = result {
+                session.abort_transaction().await.ok();
+                return Ok(vote_write_error(error));
+            }
+        }
+
+        let result = stat_collection.update_one(
+            doc!{"uuid": &poll_id},
+            doc!{"$inc": increments},
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        return Ok(vote_write_error(error));
+    }
+
+    match get_poll(&db, &Some(poll_id), &user_id).await {
+        Ok(poll) => Ok(
+            HttpResponse::Ok()
+            .content_type("application/json")
+            .json(poll)
+        ),
+        Err(error) => Ok(error),
+    }
+}
+
+/* A concurrent vote of the same user is a conflict, not a failure */
+fn vote_write_error(error: mongodb::error::Error) -> HttpResponse {
+    if mongo::is_duplicate_key(&error) ||
+    error.contains_label(TRANSIENT_TRANSACTION_ERROR) {
+        return Response::conflict("Votes changed at the same time, try again");
+    }
+
+    log::error!("{:?}", error);
+    Response::internal_server_error(&error.to_string())
+}
+
+/* Poll and the post carrying it, if the post is visible to the user */
+pub async fn get_poll_post(
+    db: &Database,
+    poll_id: &str,
+    user_id: &str
+) -> Result<(Poll, PostCore), HttpResponse> {
+    let collection = db.collection::<Poll>("poll");
+    let result = collection.find_one(doc!{"uuid": poll_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Err(Response::not_found("poll not found"));
+    }
+
+    let poll = option.unwrap();
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find_one(doc!{
+        "poll": poll_id,
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Err(Response::not_found("poll not found"));
+    }
+
+    let post_core = option.unwrap();
+
+    let mut viewer = Viewer::load(db, user_id).await?;
+    if !viewer.can_view_post(db, &post_core).await? {
+        return Err(Response::not_found("poll not found"));
+    }
+
+    Ok((poll, post_core))
+}
@@ -0,0 +1,96 @@
+use futures::StreamExt;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::privacy;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Poll::PollVote;
+use super::Vote::get_poll_post;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    option: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+/* Lists the votes of a poll, newest first. Anonymous polls hide them */
+pub async fn task(
+    req: HttpRequest,
+    poll_id: web::Path<String>,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let poll_id = poll_id.into_inner();
+
+    let pagination = match Pagination::new(
+        Keyset::desc("voted_at", "user_id"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20),
+        Legacy::Page(req_query.page),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+
+    let (poll, _) = match get_poll_post(&db, &poll_id, &user_id).await {
+        Ok(value) => value,
+        Err(error) => return Ok(error),
+    };
+
+    if poll.anonymous {
+        return Ok(Response::forbidden("Voters of this poll are anonymous"));
+    }
+
+    let blocked = match privacy::blocked_ids(&db, &user_id).await {
+        Ok(blocked) => blocked,
+        Err(error) => return Ok(error),
+    };
+
+    let mut filter = doc!{
+        "poll_id": &poll_id,
+        "user_id": {"$nin": blocked}
+    };
+
+    if let Some(option) = req_query.option.clone() {
+        filter.insert("option", option);
+    }
+
+    let collection = db.collection::<PollVote>("poll_vote");
+    let result = pagination.find(&collection, filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut votes = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        votes.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut votes,
+        |vote| (vote.voted_at.unwrap_or(0), vote.user_id.clone())
+    );
+
+    Ok(pagination.response("votes", votes, page_info))
+}
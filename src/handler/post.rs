@@ -5,4 +5,49 @@ pub mod delete;
 pub use delete as Delete;
 
 pub mod get;
-pub use get as Get;
\ No newline at end of file
+pub use get as Get;
+
+pub mod like;
+pub use like as Like;
+
+pub mod unlike;
+pub use unlike as Unlike;
+
+pub mod like_list;
+pub use like_list as LikeList;
+
+pub mod edit;
+pub use edit as Edit;
+
+pub mod revision_list;
+pub use revision_list as RevisionList;
+
+pub mod share;
+pub use share as Share;
+
+pub mod impression;
+pub use impression as Impression;
+
+pub mod publish;
+pub use publish as Publish;
+
+pub mod schedule;
+pub use schedule as Schedule;
+
+pub mod unschedule;
+pub use unschedule as Unschedule;
+
+pub mod draft_list;
+pub use draft_list as DraftList;
+
+pub mod restore;
+pub use restore as Restore;
+
+pub mod pin;
+pub use pin as Pin;
+
+pub mod unpin;
+pub use unpin as Unpin;
+
+pub mod pin_order;
+pub use pin_order as PinOrder;
\ No newline at end of file
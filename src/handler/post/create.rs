@@ -2,7 +2,10 @@ use chrono::Utc;
 use futures::StreamExt;
 use serde_json::json;
 use uuid::Uuid;
+use crate::utils::{link_preview, mention, tag};
+use crate::utils::mention::MentionedIn;
 use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::page_access;
 use crate::utils::response::Response;
 use serde::{ Serialize, Deserialize };
 use actix_web::{web, Error, HttpResponse, HttpRequest};
@@ -29,6 +32,8 @@ pub struct ReqBody {
     tags: Vec<String>,
     visibility: Post::PostVisibility,
     poll: Option<PollBody>,
+    draft: Option<bool>,
+    publish_at: Option<i64>,
 }
 
 
@@ -37,6 +42,8 @@ pub struct PollBody {
     question: String,
     options: Vec<String>,
     r#type: Poll::PollType,
+    closes_at: Option<i64>,
+    anonymous: Option<bool>,
 }
 
 
@@ -55,12 +62,45 @@ pub async fn task(
         return Ok(Response::bad_request(&res));
     }
 
+    if let Some(poll) = &req_body.poll {
+        if let Err(res) = check_poll(poll) {
+            return Ok(Response::bad_request(&res));
+        }
+    }
+
+    let post_tags = match tag::collect(&req_body.tags, &req_body.caption) {
+        Ok(tags) => tags,
+        Err(res) => return Ok(Response::bad_request(&res)),
+    };
+
+    let (publish_state, publish_at) = match get_publish_state(&req_body) {
+        Ok(state) => state,
+        Err(res) => return Ok(Response::bad_request(&res)),
+    };
+
+    let published = publish_state == Post::PostPublishState::Published;
+
     /* DATABASE ACID SESSION INIT */
     let (db, mut session) = MongoDB.connect_acid().await;
     if let Err(error) = session.start_transaction().await {
         log::error!("{:?}", error);
         return Ok(Response::internal_server_error(&error.to_string()));
     }
+
+    //Validate the mentions and resolve the written @usernames
+    let post_mentions = match mention::resolve(
+        &db,
+        &user_id,
+        &req_body.caption,
+        &req_body.mentions,
+        &Vec::new()
+    ).await {
+        Ok(mentions) => mentions,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
   
     let owner_type;
     let owner;
@@ -95,6 +135,24 @@ pub async fn task(
                 question: poll.question.clone(),
                 options: poll.options.clone(),
                 r#type: poll.r#type.clone(),
+                closes_at: poll.closes_at,
+                anonymous: poll.anonymous.unwrap_or(false),
+            }
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let collection = db.collection::<Poll::PollStat>("poll_stat");
+        let result = collection.insert_one(
+            &Poll::PollStat {
+                uuid: uuid.clone(),
+                option_votes: vec![0; poll.options.len()],
+                voter_count: 0,
+                modified_at: now,
             }
         ).await;
 
@@ -116,11 +174,15 @@ pub async fn task(
         videos: req_body.videos.clone(),
         audio: req_body.audio.clone(),
         poll: poll_id,
+        shared_post: None,
         visibility: req_body.visibility.clone(),
         is_nsfw: req_body.is_nsfw.clone(),
         content_warning: req_body.content_warning.clone(),
+        publish_state: publish_state.clone(),
+        publish_at,
         modified_at: now,
         created_at: now,
+        edited_at: None,
         deleted_at: None,
         suspended_at: None,
         suspended_by: None,
@@ -158,9 +220,9 @@ pub async fn task(
     }
 
     let mut mentions = Vec::new();
-    if req_body.mentions.len() > 0 {
+    if post_mentions.len() > 0 {
         //Creating the mentions
-        for mention in req_body.mentions.clone() {
+        for mention in post_mentions.clone() {
             let mention_struct = Post::PostMention {
                 post_id: post_core.uuid.clone(),
                 user_id: mention.user_id,
@@ -182,12 +244,14 @@ pub async fn task(
 
     
     let mut tags = Vec::new();
-    if req_body.tags.len() > 0 {
+    if post_tags.len() > 0 {
         //Creating the tags
-        for tag in req_body.tags.clone() {
+        for tag in post_tags.clone() {
             let tag_struct = Post::PostTag {
                 post_id: post_core.uuid.clone(),
-                tag: tag
+                tag: tag,
+                // Tag pages and trending only see published posts
+                created_at: if published { Some(post_core.created_at) } else { None },
             };
     
             tags.push(tag_struct);
@@ -231,16 +295,19 @@ pub async fn task(
             }
             
             let image = result.unwrap();
-    
-            let result = collection.update_one(
-                doc!{"uuid": &image.uuid},
-                doc!{"$set":{"temporary": false}},
-            ).await;
-    
-            if let Err(error) = result {
-                log::error!("{:?}", error);
-                session.abort_transaction().await.ok().unwrap();
-                return Ok(Response::internal_server_error(&error.to_string()));
+
+            // Drafts keep their images temporary until they are published
+            if published {
+                let result = collection.update_one(
+                    doc!{"uuid": &image.uuid},
+                    doc!{"$set":{"temporary": false}},
+                ).await;
+
+                if let Err(error) = result {
+                    log::error!("{:?}", error);
+                    session.abort_transaction().await.ok().unwrap();
+                    return Ok(Response::internal_server_error(&error.to_string()));
+                }
             }
             
             images.push(image);
@@ -275,16 +342,19 @@ pub async fn task(
             }
             
             let image = result.unwrap();
-    
-            let result = collection.update_one(
-                doc!{"uuid": &image.uuid},
-                doc!{"$set":{"temporary": false}},
-            ).await;
-    
-            if let Err(error) = result {
-                log::error!("{:?}", error);
-                session.abort_transaction().await.ok().unwrap();
-                return Ok(Response::internal_server_error(&error.to_string()));
+
+            // Drafts keep their images temporary until they are published
+            if published {
+                let result = collection.update_one(
+                    doc!{"uuid": &image.uuid},
+                    doc!{"$set":{"temporary": false}},
+                ).await;
+
+                if let Err(error) = result {
+                    log::error!("{:?}", error);
+                    session.abort_transaction().await.ok().unwrap();
+                    return Ok(Response::internal_server_error(&error.to_string()));
+                }
             }
             
             video_images.push(image);
@@ -297,6 +367,26 @@ pub async fn task(
         return Ok(Response::internal_server_error(&error.to_string()));
     }
 
+    // Links are unfurled in the background, reads pick the previews up
+    if let Some(caption) = post_core.caption.clone() {
+        let db = db.clone();
+        actix::spawn(async move {
+            link_preview::unfurl_text(&db, &caption).await;
+        });
+    }
+
+    // Mentions of drafts notify once the post is published
+    if published {
+        if let Err(error) = mention::notify(
+            &db,
+            &user_id,
+            &post_mentions,
+            MentionedIn::Post { post_id: &post_core.uuid }
+        ).await {
+            return Ok(error);
+        }
+    }
+
     Ok(
         HttpResponse::Ok()
         .content_type("application/json")
@@ -309,8 +399,12 @@ pub async fn task(
                 "videos": &video_images,
                 "audio": &post_core.audio,
                 "mentions": &mentions,
-                "tags": req_body.tags.clone(),
+                "tags": &post_tags,
                 "created_at": &post_core.created_at,
+                "publish_state": &post_core.publish_state,
+                "publish_at": &post_core.publish_at,
+                "edited": false,
+                "edited_at": null,
             }),
             "stat": &post_stat,
             "meta": json!({
@@ -321,38 +415,25 @@ pub async fn task(
     )
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct PageStruct {
-  uuid: String,
-  owner: String,
-  admins: Vec<String>,
-}
-
 async fn check_page_authority(
     db: &Database,
     session: &mut ClientSession,
     page_id: &str,
     user_id: &str
 ) -> Result<(), HttpResponse> {
-    let collection = db.collection::<PageStruct>("page");
-    let result = collection.find_one(
-        doc!{"uuid": page_id},
-    ).await;
-
-    if let Err(error) = result {
-        log::error!("{:?}", error);
-        session.abort_transaction().await.ok().unwrap();
-        return Err(Response::internal_server_error(&error.to_string()));
-    }
-
-    let option = result.unwrap();
-    if let None = option {
-        session.abort_transaction().await.ok().unwrap();
-        return Err(Response::not_found("page not found"));
-    }
+    let page = match page_access::find(db, page_id).await {
+        Ok(Some(page)) => page,
+        Ok(None) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Err(Response::not_found("page not found"));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Err(error);
+        }
+    };
 
-    let page = option.unwrap();
-    if page.owner != user_id && !page.admins.contains(&user_id.to_string()) {
+    if !page.is_manager(user_id) {
         session.abort_transaction().await.ok().unwrap();
         return Err(Response::forbidden(
             "You don't have permission to post on this page"
@@ -369,4 +450,47 @@ fn check_empty_fields(data: &ReqBody) -> Result<(), String> {
     else {
         Ok(())
     }
+}
+
+/* Scheduling wins over a plain draft, `publish_at` must be in the future */
+fn get_publish_state(
+    data: &ReqBody
+) -> Result<(Post::PostPublishState, Option<i64>), String> {
+    if let Some(publish_at) = data.publish_at {
+        if publish_at <= Utc::now().timestamp_millis() {
+            return Err("Publishing time must be in the future".to_string());
+        }
+
+        return Ok((Post::PostPublishState::Scheduled, Some(publish_at)));
+    }
+
+    if data.draft.unwrap_or(false) {
+        return Ok((Post::PostPublishState::Draft, None));
+    }
+
+    Ok((Post::PostPublishState::Published, None))
+}
+
+fn check_poll(poll: &PollBody) -> Result<(), String> {
+    if poll.options.len() < 2 {
+        return Err("A poll needs at least two options".to_string());
+    }
+
+    for (i, option) in poll.options.iter().enumerate() {
+        if option.trim().is_empty() {
+            return Err("Poll options can't be empty".to_string());
+        }
+
+        if poll.options[..i].contains(option) {
+            return Err(format!("Duplicate poll option: {}", option));
+        }
+    }
+
+    if let Some(closes_at) = poll.closes_at {
+        if closes_at <= Utc::now().timestamp_millis() {
+            return Err("Poll closing time must be in the future".to_string());
+        }
+    }
+
+    Ok(())
 }
\ No newline at end of file
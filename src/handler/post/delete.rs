@@ -1,23 +1,50 @@
 use chrono::Utc;
-use mongodb::bson::doc;
-use crate::model::{Post, Account::AccountRole};
+use futures::StreamExt;
+use mongodb::{ClientSession, Collection, Database, bson::{doc, Bson, Document}};
+use crate::model::{
+    Account::AccountRole,
+    Comment::{CommentCore, CommentLike, CommentPin, CommentStat},
+    ImageStruct,
+    Poll::{Poll, PollStat, PollVote},
+    Post::{
+        FeedSeen,
+        PostBookmark,
+        PostCore,
+        PostLike,
+        PostMention,
+        PostOwnerType,
+        PostPin,
+        PostRevision,
+        PostStat,
+        PostTag,
+    },
+    Reply::{ReplyCore, ReplyLike, ReplyStat},
+};
 use crate::BuiltIns::mongo::MongoDB;
 use crate::utils::response::Response;
 use actix_web::{web, Error, HttpResponse, HttpRequest};
 use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::utils::page_access;
 
+/* Days a deleted post can be restored before it's purged */
+pub const RESTORE_WINDOW_DAYS: i64 = 30;
 
+/*
+    Soft deletes a post together with its comments, replies, likes and
+    bookmarks, and releases its images until it's restored or purged.
+    Everything is stamped with the same `deleted_at`, so a restore brings
+    back exactly what the deletion took and leaves what was deleted
+    on its own. The purge job removes it for good after the window.
+*/
 pub async fn task(
     req: HttpRequest,
     post_id: web::Path<String>
 ) -> Result<HttpResponse, Error> {
     let user = require_access(
         &req,
-        AccessRequirement::Role(AccountRole::Administrator)
+        AccessRequirement::AnyToken
     )?;
 
-    let user_id = user.user_id;
-
     let post_id = post_id.into_inner();
     if post_id.len() == 0 {
         return Ok(Response::bad_request("post id required"));
@@ -26,7 +53,7 @@ pub async fn task(
     let db = MongoDB.connect();
 
     //finding the post
-    let collection = db.collection::<Post::PostCore>("post_core");
+    let collection = db.collection::<PostCore>("post_core");
     let result = collection.find_one(
         doc!{ "uuid": &post_id},
     ).await;
@@ -42,33 +69,422 @@ pub async fn task(
     }
 
     let post = option.unwrap();
-    if post.owner != user_id {
-        return Ok(Response::forbidden(
+    if post.deleted_at.is_some() {
+        return Ok(Response::not_found("post not found"));
+    }
+
+    match can_delete(&db, &user.user_id, &user.role, &post).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(Response::forbidden(
             "You are not authorized to delete this post"
-        ));
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let now = Utc::now().timestamp_millis();
+    if let Err(error) = set_deleted(
+        &db,
+        &mut session,
+        &post,
+        None,
+        Some(now)
+    ).await {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(error);
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
     }
 
-    let collection = db.collection::<Post::PostCore>("post_core");
+    Ok(HttpResponse::Ok().content_type("application/json").json(
+        Response { message: "Successfully Deleted".to_string() }
+    ))
+}
+
+/*
+    Owners delete their own posts, admins of the owning page delete the
+    page's posts and site administrators delete any post.
+*/
+pub async fn can_delete(
+    db: &Database,
+    user_id: &str,
+    role: &AccountRole,
+    post: &PostCore
+) -> Result<bool, HttpResponse> {
+    if *role == AccountRole::Administrator {
+        return Ok(true);
+    }
+
+    match post.owner_type {
+        PostOwnerType::User => Ok(post.owner == user_id),
+        PostOwnerType::Page => page_access::can_manage(db, &post.owner, user_id).await,
+    }
+}
+
+/*
+    Moves a post and everything cascading from it from the `from`
+    deletion stamp to `to`: `None` to `Some` deletes, back restores.
+    Images, the comments' and replies' included, are released while
+    the post is deleted.
+*/
+pub async fn set_deleted(
+    db: &Database,
+    session: &mut ClientSession,
+    post: &PostCore,
+    from: Option<i64>,
+    to: Option<i64>
+) -> Result<(), HttpResponse> {
     let now = Utc::now().timestamp_millis();
+
+    let collection = db.collection::<PostCore>("post_core");
     let result = collection.update_one(
-        doc!{"uuid": &post_id},
-        doc!{"$set": {
-            "deleted_at": now,
-            "modified_at": now,
-        }},
+        doc!{"uuid": &post.uuid, "deleted_at": from},
+        doc!{"$set": {"deleted_at": to, "modified_at": now}},
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().matched_count == 0 {
+        return Err(Response::not_found("post not found"));
+    }
+
+    // Replies of every comment, including ones deleted on their own
+    let collection = db.collection::<CommentCore>("comment_core");
+    let comment_ids = comment_ids(&collection, &post.uuid).await?;
+
+    let mut images = cascaded_images(
+        &collection,
+        session,
+        doc!{"post_id": &post.uuid, "deleted_at": from}
+    ).await?;
+
+    let result = collection.update_many(
+        doc!{"post_id": &post.uuid, "deleted_at": from},
+        doc!{"$set": {"deleted_at": to, "modified_at": now}},
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<ReplyCore>("reply_core");
+    let filter = doc!{"comment_id": {"$in": &comment_ids}, "deleted_at": from};
+    images.extend(cascaded_images(&collection, session, filter.clone()).await?);
+
+    let result = collection.distinct("uuid", filter.clone())
+    .session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let reply_ids = result.unwrap();
+
+    let result = collection.update_many(
+        filter,
+        doc!{"$set": {"deleted_at": to, "modified_at": now}},
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostLike>("post_like");
+    let result = collection.update_many(
+        doc!{"post_id": &post.uuid, "deleted_at": from},
+        doc!{"$set": {"deleted_at": to}},
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostBookmark>("post_bookmark");
+    let result = collection.update_many(
+        doc!{"post_id": &post.uuid, "deleted_at": from},
+        doc!{"$set": {"deleted_at": to}},
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<CommentLike>("comment_like");
+    let result = collection.update_many(
+        doc!{"comment_id": {"$in": &comment_ids}, "deleted_at": from},
+        doc!{"$set": {"deleted_at": to}},
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<ReplyLike>("reply_like");
+    let result = collection.update_many(
+        doc!{"reply_id": {"$in": &reply_ids}, "deleted_at": from},
+        doc!{"$set": {"deleted_at": to}},
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    // Pins don't come back with a restore
+    if to.is_some() {
+        let collection = db.collection::<PostPin>("post_pin");
+        let result = collection.delete_many(doc!{"post_id": &post.uuid})
+        .session(&mut *session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    images.extend(
+        post.images.iter()
+        .chain(post.videos.iter())
+        .map(|uuid| Bson::String(uuid.clone()))
+    );
+
+    if images.len() > 0 {
+        let collection = db.collection::<ImageStruct>("image");
+        let result = collection.update_many(
+            doc!{"uuid": {"$in": images}},
+            doc!{"$set": {"temporary": to.is_some()}},
+        ).session(&mut *session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    // A deleted share no longer counts towards the original
+    if let Some(shared_id) = &post.shared_post {
+        let (filter, change) = match to {
+            Some(_) => (
+                doc!{"uuid": shared_id, "share_count": {"$gt": 0}},
+                -1
+            ),
+            None => (doc!{"uuid": shared_id}, 1),
+        };
+
+        let collection = db.collection::<PostStat>("post_stat");
+        let result = collection.update_one(
+            filter,
+            doc!{
+                "$inc": {"share_count": change},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut *session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+async fn comment_ids(
+    collection: &Collection<CommentCore>,
+    post_id: &str
+) -> Result<Vec<String>, HttpResponse> {
+    let result = collection.find(doc!{"post_id": post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut comment_ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        comment_ids.push(result.unwrap().uuid);
+    }
+
+    Ok(comment_ids)
+}
+
+/* Images of the comments or replies matching `filter` */
+async fn cascaded_images<T: Send + Sync>(
+    collection: &Collection<T>,
+    session: &mut ClientSession,
+    filter: Document
+) -> Result<Vec<Bson>, HttpResponse> {
+    let result = collection.distinct("images", filter)
+    .session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(result.unwrap())
+}
+
+/*
+    Removes a deleted post and everything cascading from it for good,
+    its images are marked deleted.
+    The post itself goes last, so a purge failing halfway is picked up
+    again by the next run.
+*/
+pub async fn purge_post(db: &Database, post: &PostCore) -> Result<(), HttpResponse> {
+    let comment_collection = db.collection::<CommentCore>("comment_core");
+    let comment_ids = comment_ids(&comment_collection, &post.uuid).await?;
+
+    let reply_collection = db.collection::<ReplyCore>("reply_core");
+    let result = reply_collection.distinct(
+        "uuid",
+        doc!{"comment_id": {"$in": &comment_ids}}
     ).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
-        return Ok(Response::internal_server_error(&error.to_string()));
+        return Err(Response::internal_server_error(&error.to_string()));
     }
 
-    let update_result = result.unwrap();
-    if update_result.matched_count == 0 {
-        return Ok(Response::not_found("post not found"));
+    let reply_ids = result.unwrap();
+
+    delete_all(
+        &db.collection::<ReplyLike>("reply_like"),
+        doc!{"reply_id": {"$in": &reply_ids}}
+    ).await?;
+    delete_all(
+        &db.collection::<ReplyStat>("reply_stat"),
+        doc!{"uuid": {"$in": &reply_ids}}
+    ).await?;
+    delete_all(
+        &reply_collection,
+        doc!{"comment_id": {"$in": &comment_ids}}
+    ).await?;
+
+    delete_all(
+        &db.collection::<CommentLike>("comment_like"),
+        doc!{"comment_id": {"$in": &comment_ids}}
+    ).await?;
+    delete_all(
+        &db.collection::<CommentStat>("comment_stat"),
+        doc!{"uuid": {"$in": &comment_ids}}
+    ).await?;
+    delete_all(
+        &comment_collection,
+        doc!{"post_id": &post.uuid}
+    ).await?;
+
+    if let Some(poll_id) = &post.poll {
+        delete_all(
+            &db.collection::<PollVote>("poll_vote"),
+            doc!{"poll_id": poll_id}
+        ).await?;
+        delete_all(
+            &db.collection::<PollStat>("poll_stat"),
+            doc!{"uuid": poll_id}
+        ).await?;
+        delete_all(
+            &db.collection::<Poll>("poll"),
+            doc!{"uuid": poll_id}
+        ).await?;
     }
 
-    Ok(HttpResponse::Ok().content_type("application/json").json(
-        Response { message: "Successfully Deleted".to_string() }
-    ))
-}
\ No newline at end of file
+    delete_all(
+        &db.collection::<PostLike>("post_like"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<PostBookmark>("post_bookmark"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<PostMention>("post_mention"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<PostTag>("post_tag"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<PostRevision>("post_revision"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<PostPin>("post_pin"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<CommentPin>("comment_pin"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<FeedSeen>("feed_seen"),
+        doc!{"post_id": &post.uuid}
+    ).await?;
+    delete_all(
+        &db.collection::<PostStat>("post_stat"),
+        doc!{"uuid": &post.uuid}
+    ).await?;
+
+    let assets: Vec<String> = post.images.iter()
+    .chain(post.videos.iter())
+    .cloned()
+    .collect();
+
+    if assets.len() > 0 {
+        let collection = db.collection::<ImageStruct>("image");
+        let result = collection.update_many(
+            doc!{"uuid": {"$in": assets}},
+            doc!{"$set": {"deleted": true}},
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    delete_all(
+        &db.collection::<PostCore>("post_core"),
+        doc!{"uuid": &post.uuid}
+    ).await
+}
+
+async fn delete_all<T: Send + Sync>(
+    collection: &Collection<T>,
+    filter: Document
+) -> Result<(), HttpResponse> {
+    let result = collection.delete_many(filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use futures::StreamExt;
+use mongodb::bson::{doc, Bson};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::{unpublished_states, Viewer};
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::{PostCore, PostOwnerType};
+use super::Get::build_posts;
+use crate::utils::page_access;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    page_id: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+/*
+    Drafts and scheduled posts of the user, or of `page_id` for its
+    owner and admins, most recently modified first.
+*/
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let pagination = match Pagination::new(
+        Keyset::desc("modified_at", "uuid"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20),
+        Legacy::Page(req_query.page),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+
+    let mut filter = match &req_query.page_id {
+        Some(page_id) => {
+            match page_access::can_manage(&db, page_id, &user_id).await {
+                Ok(true) => (),
+                Ok(false) => return Ok(Response::forbidden(
+                    "You are not authorized to see drafts of this page"
+                )),
+                Err(error) => return Ok(error),
+            }
+
+            doc!{
+                "owner": page_id,
+                "owner_type": PostOwnerType::Page.to_string(),
+            }
+        },
+        None => doc!{
+            "owner": &user_id,
+            "owner_type": PostOwnerType::User.to_string(),
+        },
+    };
+
+    filter.insert("publish_state", doc!{"$in": unpublished_states()});
+    filter.insert("deleted_at", Bson::Null);
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = pagination.find(&collection, filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    let mut post_cores = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        post_cores.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut post_cores,
+        |post_core| (post_core.modified_at, post_core.uuid.clone())
+    );
+
+    let posts = match build_posts(&db, &mut viewer, &post_cores).await {
+        Ok(posts) => posts,
+        Err(error) => return Ok(error),
+    };
+
+    Ok(pagination.response("posts", posts, page_info))
+}
@@ -0,0 +1,357 @@
+use uuid::Uuid;
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::{Database, bson::doc};
+use crate::utils::{link_preview, mention, page_access, tag};
+use crate::utils::mention::MentionedIn;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Mention,
+    Post::{
+        PostCore,
+        PostMention,
+        PostOwnerType,
+        PostPublishState,
+        PostRevision,
+        PostTag
+    },
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    caption: Option<String>,
+    mentions: Vec<Mention>,
+    tags: Vec<String>,
+    content_warning: Option<String>,
+    is_nsfw: bool,
+}
+
+/*
+    Replaces the editable fields of a post.
+    The previous version is kept in `post_revision` and only mentions
+    added by this edit notify the mentioned users.
+*/
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find_one(doc!{
+        "uuid": &post_id,
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("post not found"));
+    }
+
+    let post_core = option.unwrap();
+
+    match can_edit(&db, &post_core, &user_id).await {
+        Ok(true) => (),
+        Ok(false) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::forbidden(
+                "You are not authorized to edit this post"
+            ));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    }
+
+    if req_body.caption.is_none() &&
+    post_core.images.len() == 0 &&
+    post_core.videos.len() == 0 &&
+    post_core.audio.is_none() &&
+    post_core.poll.is_none() {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::bad_request("Nothing to post here"));
+    }
+
+    let new_tags = match tag::collect(&req_body.tags, &req_body.caption) {
+        Ok(tags) => tags,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::bad_request(&error));
+        }
+    };
+
+    let old_mentions = match get_mentions(&db, &post_id).await {
+        Ok(mentions) => mentions,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let old_tags = match get_tags(&db, &post_id).await {
+        Ok(tags) => tags,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    // Only newly mentioned users need to allow being mentioned
+    let exempt: Vec<String> = old_mentions.iter()
+    .map(|mention| mention.user_id.clone())
+    .collect();
+
+    let new_mentions = match mention::resolve(
+        &db,
+        &user_id,
+        &req_body.caption,
+        &req_body.mentions,
+        &exempt
+    ).await {
+        Ok(mentions) => mentions,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let now = Utc::now().timestamp_millis();
+
+    let revision = PostRevision {
+        uuid: Uuid::new_v4().to_string(),
+        post_id: post_id.clone(),
+        edited_by: user_id.clone(),
+        caption: post_core.caption.clone(),
+        mentions: old_mentions.iter().map(|mention| Mention {
+            user_id: mention.user_id.clone(),
+            start: mention.start,
+            end: mention.end,
+        }).collect(),
+        tags: old_tags,
+        content_warning: post_core.content_warning.clone(),
+        is_nsfw: post_core.is_nsfw,
+        revised_at: now,
+    };
+
+    let collection = db.collection::<PostRevision>("post_revision");
+    let result = collection.insert_one(&revision)
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.update_one(
+        doc!{"uuid": &post_id},
+        doc!{"$set": {
+            "caption": req_body.caption.clone(),
+            "content_warning": req_body.content_warning.clone(),
+            "is_nsfw": req_body.is_nsfw,
+            "modified_at": now,
+            "edited_at": now,
+        }},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostMention>("post_mention");
+    let result = collection.delete_many(doc!{"post_id": &post_id})
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mentions: Vec<PostMention> = new_mentions.iter()
+    .map(|mention| PostMention {
+        post_id: post_id.clone(),
+        user_id: mention.user_id.clone(),
+        start: mention.start,
+        end: mention.end,
+    })
+    .collect();
+
+    if mentions.len() > 0 {
+        let result = collection.insert_many(&mentions)
+        .session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = collection.delete_many(doc!{"post_id": &post_id})
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let published = post_core.publish_state == PostPublishState::Published;
+    let tags: Vec<PostTag> = new_tags.iter()
+    .map(|tag| PostTag {
+        post_id: post_id.clone(),
+        tag: tag.clone(),
+        created_at: if published { Some(post_core.created_at) } else { None },
+    })
+    .collect();
+
+    if tags.len() > 0 {
+        let result = collection.insert_many(&tags)
+        .session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    // Links are unfurled in the background, reads pick the previews up
+    if let Some(caption) = req_body.caption.clone() {
+        let db = db.clone();
+        actix::spawn(async move {
+            link_preview::unfurl_text(&db, &caption).await;
+        });
+    }
+
+    // Users notified for an earlier version aren't notified again,
+    // drafts notify once they are published
+    if published {
+        if let Err(error) = mention::notify(
+            &db,
+            &user_id,
+            &new_mentions,
+            MentionedIn::Post { post_id: &post_id }
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "uuid": &post_id,
+            "caption": &req_body.caption,
+            "mentions": &mentions,
+            "tags": &new_tags,
+            "content_warning": &req_body.content_warning,
+            "is_nsfw": req_body.is_nsfw,
+            "edited": true,
+            "edited_at": now,
+        }))
+    )
+}
+
+/* Post owner, or the owner and admins of the page owning the post */
+pub async fn can_edit(
+    db: &Database,
+    post_core: &PostCore,
+    user_id: &str
+) -> Result<bool, HttpResponse> {
+    if let PostOwnerType::User = post_core.owner_type {
+        return Ok(post_core.owner == user_id);
+    }
+
+    page_access::can_manage(db, &post_core.owner, user_id).await
+}
+
+async fn get_mentions(
+    db: &Database,
+    post_id: &str
+) -> Result<Vec<PostMention>, HttpResponse> {
+    let collection = db.collection::<PostMention>("post_mention");
+    let result = collection.find(doc!{"post_id": post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut mentions = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        mentions.push(result.unwrap());
+    }
+
+    Ok(mentions)
+}
+
+async fn get_tags(
+    db: &Database,
+    post_id: &str
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = collection.find(doc!{"post_id": post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut tags = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        tags.push(result.unwrap().tag);
+    }
+
+    Ok(tags)
+}
@@ -1,17 +1,65 @@
+use chrono::Utc;
 use futures::StreamExt;
 use serde_json::Map;
-use mongodb::{Database, bson::{Bson, doc}};
+use std::collections::HashMap;
+use crate::utils::visibility::{unpublished_states, Viewer};
+use serde::de::DeserializeOwned;
+use crate::utils::{content_filter, link_preview, mongo, query_counter};
+use crate::utils::content_filter::{ContentFilter, Verdict};
+use crate::utils::fields::{Fields, Section};
+use crate::utils::pagination::{Keyset, Legacy, PageInfo, Pagination, Shape};
+use mongodb::{Collection, Database, bson::{doc, Document}};
 use crate::builtins::mongo::MongoDB;
 use crate::utils::response::Response;
 use serde::{ Serialize, Deserialize };
 use actix_web::{ web, Error, HttpResponse, HttpRequest };
+use actix_web::http::header::{HeaderName, HeaderValue};
 use crate::Middleware::Auth::{require_access, AccessRequirement};
+use super::Pin::get_pins;
 use crate::model::{
+    Account,
+    Account::AccountRole,
     ImageStruct,
+    LinkPreview,
     Post,
     Poll,
 };
 
+/* Sections of a post, for `fields=(core(caption,...),owner,stat,...)` */
+pub const FIELDS: &[Section] = &[
+    Section {
+        name: "core",
+        fields: &[
+            "uuid", "caption", "images", "tags", "mentions", "videos",
+            "audio", "poll", "content_warning", "is_nsfw", "created_at",
+            "publish_state", "publish_at", "edited", "edited_at",
+            "shared_post_id", "owner_id", "link_previews",
+        ],
+    },
+    Section {
+        name: "owner",
+        fields: &[
+            "uuid", "name", "username", "image", "owner_type",
+            "profile_verified",
+        ],
+    },
+    Section {
+        name: "stat",
+        fields: &[
+            "uuid", "like_count", "comment_count", "share_count",
+            "view_count", "modified_at",
+        ],
+    },
+    Section {
+        name: "meta",
+        fields: &["liked", "bookmarked"],
+    },
+    Section {
+        name: "shared_post",
+        fields: &[],
+    },
+];
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Query {
     uuid: Option<String>,
@@ -20,10 +68,20 @@ pub struct Query {
     owner_type: Option<Post::PostOwnerType>,
     visibility: Option<Post::PostVisibility>,
     is_nsfw: Option<bool>,
-    limit: i64,
-    page: i64,
+    debug: Option<bool>,
+    cursor: Option<String>,
+    paginated: Option<bool>,
+    limit: Option<u32>,
+    page: Option<u32>,
 }
 
+/*
+    `fields` trims the posts to some sections, see `FIELDS`.
+    Filtered by `owner`, the first page starts with the owner's pinned
+    posts, flagged `pinned`.
+    `debug` (administrators only) reports the number of database
+    queries the request made in the `X-Query-Count` header.
+*/
 pub async fn task(
     req: HttpRequest,
     query: web::Query<Query>
@@ -33,14 +91,62 @@ pub async fn task(
         AccessRequirement::AnyToken
     )?;
 
-    let user_id = user.user_id;
+    let debug = query.debug.unwrap_or(false);
+
+    if debug && user.role != AccountRole::Administrator {
+        return Ok(Response::forbidden("Debug mode is for administrators only"));
+    }
+
+    let pagination = match Pagination::new(
+        Keyset::desc("created_at", "uuid"),
+        &query.cursor,
+        query.limit.unwrap_or(20),
+        Legacy::Page(query.page),
+        Shape::Legacy(query.paginated)
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let fields = match Fields::parse(&query.fields, FIELDS) {
+        Ok(fields) => fields,
+        Err(error) => return Ok(error),
+    };
+
+    let (result, query_count) = query_counter::count(
+        get_posts(&user.user_id, &query, &pagination, &fields)
+    ).await;
+
+    let (posts, page_info) = match result {
+        Ok(page) => page,
+        Err(error) => return Ok(error),
+    };
+
+    let mut response = pagination.response("posts", posts, page_info);
+    if debug {
+        response.headers_mut().insert(
+            HeaderName::from_static("x-query-count"),
+            HeaderValue::from(query_count)
+        );
+    }
+
+    Ok(response)
+}
 
+async fn get_posts(
+    user_id: &str,
+    query: &Query,
+    pagination: &Pagination,
+    fields: &Fields
+) -> Result<(Vec<Map<String, serde_json::Value>>, PageInfo), HttpResponse> {
     let db = MongoDB.connect();
 
     let mut filter = doc!{};
 
-    if let Some(uuid) = query.uuid.clone() {
-        filter.insert("uuid", uuid);
+    // Drafts are listed by `Post::DraftList`, only direct reads return them
+    match query.uuid.clone() {
+        Some(uuid) => { filter.insert("uuid", uuid); },
+        None => { filter.insert("publish_state", doc!{"$nin": unpublished_states()}); },
     }
     if let Some(owner) = query.owner.clone() {
         filter.insert("owner", owner);
@@ -55,201 +161,523 @@ pub async fn task(
         filter.insert("is_nsfw", is_nsfw);
     }
 
+    let mut viewer = Viewer::load(&db, user_id).await?;
+
     let collection = db.collection::<Post::PostCore>("post_core");
-    
-    let result = collection.find(
-        filter,
-    ).sort(doc! { "created_at": -1 })
-    .limit(query.limit)
-    .skip((query.limit * (query.page - 1)) as u64).await;
+
+    // Pins of the owner lead the first page and are left out of the rest
+    let mut pinned = Vec::new();
+    if let (Some(owner), None) = (&query.owner, &query.uuid) {
+        let pinned_ids: Vec<String> = get_pins(&db, owner).await?
+        .into_iter()
+        .map(|pin| pin.post_id)
+        .collect();
+
+        if pinned_ids.len() > 0 {
+            if pagination.cursor().is_none() && query.page.unwrap_or(1) <= 1 {
+                let mut pin_filter = filter.clone();
+                pin_filter.insert("uuid", doc!{"$in": &pinned_ids});
+
+                let mut post_cores = fetch(&collection, pin_filter).await?;
+                post_cores.sort_by_key(|post_core| pinned_ids.iter()
+                    .position(|post_id| *post_id == post_core.uuid));
+
+                for post_core in post_cores {
+                    if viewer.can_view_post(&db, &post_core).await? {
+                        pinned.push(post_core);
+                    }
+                }
+            }
+
+            filter.insert("uuid", doc!{"$nin": &pinned_ids});
+        }
+    }
+
+    let result = pagination.find(&collection, filter).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
-        return Ok(Response::internal_server_error(&error.to_string()));
+        return Err(Response::internal_server_error(&error.to_string()));
     }
 
     let mut cursor = result.unwrap();
 
-    let mut posts = Vec::new();
-
+    let mut post_cores = Vec::new();
     while let  Some(result) = cursor.next().await {
         if let Err(error) = result {
             log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
+            return Err(Response::internal_server_error(&error.to_string()));
         }
 
-        let mut response = Map::new();
-        let post_core = result.unwrap();
+        post_cores.push(result.unwrap());
+    }
 
-        let collection = db.collection::<Post::PostStat>("post_stat");
-        let result = collection.find_one(
-            doc!{"uuid": post_core.uuid.clone()}
-        ).await;
+    let page_info = pagination.finish(
+        &mut post_cores,
+        |post_core| (post_core.created_at, post_core.uuid.clone())
+    );
 
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
+    let pinned_count = pinned.len();
+    let mut visible = pinned;
+    for post_core in post_cores {
+        if viewer.can_view_post(&db, &post_core).await? {
+            visible.push(post_core);
+        }
+    }
+
+    let mut posts = build_selected_posts(&db, &mut viewer, &visible, fields).await?;
+    for post in posts.iter_mut().take(pinned_count) {
+        post.insert("pinned".to_string(), serde_json::json!(true));
+    }
+
+    Ok((posts, page_info))
+}
+
+/*
+    Builds the responses of a page of posts as seen by `viewer`, in order.
+    Everything the posts embed is fetched with one `$in` query per
+    collection for the whole page, so the number of queries stays the
+    same whatever the page size.
+    Shares embed the original post, or a tombstone once it's gone.
+    Posts hidden by the viewer's content preferences come as a
+    placeholder carrying the warning, blurred ones are flagged `blurred`.
+*/
+pub async fn build_posts(
+    db: &Database,
+    viewer: &mut Viewer,
+    post_cores: &[Post::PostCore]
+) -> Result<Vec<Map<String, serde_json::Value>>, HttpResponse> {
+    build_selected_posts(db, viewer, post_cores, &Fields::all(FIELDS)).await
+}
+
+/* `build_posts` loading and returning only the sections in `fields` */
+pub async fn build_selected_posts(
+    db: &Database,
+    viewer: &mut Viewer,
+    post_cores: &[Post::PostCore],
+    fields: &Fields
+) -> Result<Vec<Map<String, serde_json::Value>>, HttpResponse> {
+    if post_cores.len() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let shared_ids = unique(post_cores.iter()
+    .filter_map(|post_core| post_core.shared_post.as_ref()));
+
+    let mut originals: HashMap<String, Post::PostCore> = HashMap::new();
+    if shared_ids.len() > 0 && fields.includes("shared_post") {
+        let collection = db.collection::<Post::PostCore>("post_core");
+        let shared = fetch(&collection, doc!{"uuid": {"$in": &shared_ids}}).await?;
+
+        for original in shared {
+            if original.deleted_at.is_none() &&
+            viewer.can_view_post(db, &original).await? {
+                originals.insert(original.uuid.clone(), original);
+            }
         }
+    }
 
-        let option = result.unwrap();
-        if let None = option {
-            return Ok(Response::not_found("Post stat found"));
+    // Posts the viewer chose not to see are replaced, not loaded
+    let content_filter = ContentFilter::load(db, &viewer.user_id).await?;
+    let verdicts: HashMap<&str, Verdict> = post_cores.iter()
+    .chain(originals.values())
+    .map(|post_core| (post_core.uuid.as_str(), content_filter.check(post_core)))
+    .collect();
+
+    let all: Vec<&Post::PostCore> = post_cores.iter()
+    .chain(originals.values())
+    .filter(|post_core| !matches!(verdicts[post_core.uuid.as_str()], Verdict::Hide(_)))
+    .collect();
+
+    let page = PageData::load(db, &viewer.user_id, &all, fields).await?;
+
+    let mut responses = Vec::new();
+    for post_core in post_cores {
+        let verdict = &verdicts[post_core.uuid.as_str()];
+        if let Verdict::Hide(reason) = verdict {
+            responses.push(content_filter::placeholder(post_core, reason));
+            continue;
         }
 
-        let post_stat = option.unwrap();
+        let mut response = page.render(post_core, fields)?;
+        if *verdict == Verdict::Blur {
+            response.insert("blurred".to_string(), serde_json::json!(true));
+        }
 
-        // Getting The images
-        let images = match get_images(&db, post_core.images.clone()).await {
-            Ok(images) => images,
-            Err(error) => return Ok(error),
-        };
+        if let (Some(shared_id), true) = (
+            &post_core.shared_post,
+            fields.includes("shared_post")
+        ) {
+            let shared = match originals.get(shared_id) {
+                Some(original) => match &verdicts[original.uuid.as_str()] {
+                    Verdict::Hide(reason) => serde_json::Value::Object(
+                        content_filter::placeholder(original, reason)
+                    ),
+                    verdict => {
+                        let mut shared = fields.apply(page.render(original, fields)?);
+                        if *verdict == Verdict::Blur {
+                            shared.insert("blurred".to_string(), serde_json::json!(true));
+                        }
+
+                        serde_json::Value::Object(shared)
+                    }
+                },
+                None => serde_json::json!({
+                    "uuid": shared_id,
+                    "tombstone": true,
+                }),
+            };
+
+            response.insert("shared_post".to_string(), shared);
+        }
+
+        responses.push(fields.apply(response));
+    }
 
-        // Getting The video thumbnails
-        let video_thumbnails = match get_images(&db, post_core.videos.clone()).await {
-            Ok(images) => images,
-            Err(error) => return Ok(error),
+    Ok(responses)
+}
+
+/* Everything embedded by a page of posts, keyed by the owning document */
+struct PageData {
+    stats: HashMap<String, Post::PostStat>,
+    images: HashMap<String, ImageStruct>,
+    polls: HashMap<String, Poll::Poll>,
+    poll_stats: HashMap<String, Poll::PollStat>,
+    poll_votes: HashMap<String, Vec<String>>,
+    mentions: HashMap<String, Vec<Post::PostMention>>,
+    tags: HashMap<String, Vec<String>>,
+    link_previews: HashMap<String, LinkPreview>,
+    owners: HashMap<String, serde_json::Value>,
+    liked: Vec<String>,
+    bookmarked: Vec<String>,
+}
+
+impl PageData {
+    async fn load(
+        db: &Database,
+        user_id: &str,
+        post_cores: &Vec<&Post::PostCore>,
+        fields: &Fields
+    ) -> Result<Self, HttpResponse> {
+        let post_ids = unique(post_cores.iter()
+        .map(|post_core| &post_core.uuid));
+
+        let poll_ids = match fields.wants("core", "poll") {
+            true => unique(post_cores.iter()
+            .filter_map(|post_core| post_core.poll.as_ref())),
+            false => Vec::new(),
         };
 
-        //Getting poll information
-        let poll = match get_poll(&db, &post_core.poll.clone()).await {
-            Ok(poll) => poll,
-            Err(error) => return Ok(error),
+        let user_ids = match fields.includes("owner") {
+            true => unique(post_cores.iter()
+            .filter(|post_core| matches!(post_core.owner_type, Post::PostOwnerType::User))
+            .map(|post_core| &post_core.owner)),
+            false => Vec::new(),
         };
 
-        //getting the mentions
-        let collection = db.collection::<Post::PostMention>("post_mention");
-        let result = collection.find(doc!{
-            "post_id": &post_core.uuid
-        }).await;
+        let mut stats = HashMap::new();
+        if fields.includes("stat") {
+            let collection = db.collection::<Post::PostStat>("post_stat");
+            for stat in fetch(&collection, doc!{"uuid": {"$in": &post_ids}}).await? {
+                stats.insert(stat.uuid.clone(), stat);
+            }
+        }
+
+        let mut polls = HashMap::new();
+        let mut poll_stats = HashMap::new();
+        let mut poll_votes: HashMap<String, Vec<String>> = HashMap::new();
+        if poll_ids.len() > 0 {
+            let collection = db.collection::<Poll::Poll>("poll");
+            for poll in fetch(&collection, doc!{"uuid": {"$in": &poll_ids}}).await? {
+                polls.insert(poll.uuid.clone(), poll);
+            }
 
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
+            let collection = db.collection::<Poll::PollStat>("poll_stat");
+            for stat in fetch(&collection, doc!{"uuid": {"$in": &poll_ids}}).await? {
+                poll_stats.insert(stat.uuid.clone(), stat);
+            }
+
+            let collection = db.collection::<Poll::PollVote>("poll_vote");
+            for vote in fetch(&collection, doc!{
+                "poll_id": {"$in": &poll_ids},
+                "user_id": user_id,
+            }).await? {
+                poll_votes.entry(vote.poll_id).or_default().push(vote.option);
+            }
         }
 
-        let mut mention_cursor = result.unwrap();
+        let mut mentions: HashMap<String, Vec<Post::PostMention>> = HashMap::new();
+        if fields.wants("core", "mentions") {
+            let collection = db.collection::<Post::PostMention>("post_mention");
+            for mention in fetch(&collection, doc!{"post_id": {"$in": &post_ids}}).await? {
+                mentions.entry(mention.post_id.clone()).or_default().push(mention);
+            }
+        }
 
-        let mut mentions = Vec::new();
-        
-        while let Some(result) = mention_cursor.next().await {
-            if let Err(error) = result {
-                log::error!("{:?}", error);
-                return Ok(Response::internal_server_error(&error.to_string()));
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        if fields.wants("core", "tags") {
+            let collection = db.collection::<Post::PostTag>("post_tag");
+            for tag in fetch(&collection, doc!{"post_id": {"$in": &post_ids}}).await? {
+                tags.entry(tag.post_id).or_default().push(tag.tag);
             }
+        }
 
-            mentions.push(result.unwrap());
+        // Only previews already unfurled, posts never wait on a fetch
+        let mut link_previews = HashMap::new();
+        if fields.wants("core", "link_previews") {
+            let urls: Vec<String> = post_cores.iter()
+            .filter_map(|post_core| post_core.caption.as_ref())
+            .flat_map(|caption| link_preview::extract_urls(caption))
+            .collect();
+
+            link_previews = link_preview::get_cached(db, &unique(urls.iter())).await?;
         }
 
-        //getting the tags
-        let collection = db.collection::<Post::PostTag>("post_tag");
-        let result = collection.find(doc!{
-            "post_id": &post_core.uuid
-        }).await;
+        let mut liked = Vec::new();
+        if fields.wants("meta", "liked") {
+            let collection = db.collection::<Post::PostLike>("post_like");
+            liked = fetch(&collection, doc!{
+                "post_id": {"$in": &post_ids},
+                "liked_by": user_id,
+            }).await?
+            .into_iter()
+            .map(|like| like.post_id)
+            .collect();
+        }
 
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
+        let mut bookmarked = Vec::new();
+        if fields.wants("meta", "bookmarked") {
+            let collection = db.collection::<Post::PostBookmark>("post_bookmark");
+            bookmarked = fetch(&collection, doc!{
+                "post_id": {"$in": &post_ids},
+                "bookmarked_by": user_id,
+            }).await?
+            .into_iter()
+            .map(|bookmark| bookmark.post_id)
+            .collect();
         }
 
-        let mut tag_cursor = result.unwrap();
+        let mut account_cores = HashMap::new();
+        let mut account_profiles = HashMap::new();
+        if user_ids.len() > 0 {
+            let collection = db.collection::<Account::AccountCore>("account_core");
+            for account in fetch(&collection, doc!{"uuid": {"$in": &user_ids}}).await? {
+                account_cores.insert(account.uuid.clone(), account);
+            }
 
-        let mut tags = Vec::new();
-        
-        while let Some(result) = tag_cursor.next().await {
-            if let Err(error) = result {
-                log::error!("{:?}", error);
-                return Ok(Response::internal_server_error(&error.to_string()));
+            let collection = db.collection::<Account::AccountProfile>("account_profile");
+            for profile in fetch(&collection, doc!{"uuid": {"$in": &user_ids}}).await? {
+                account_profiles.insert(profile.uuid.clone(), profile);
             }
+        }
 
-            tags.push(result.unwrap().tag);
+        // Post images, video thumbnails and owner pictures in one go
+        let mut image_ids: Vec<String> = Vec::new();
+        for post_core in post_cores {
+            if fields.wants("core", "images") {
+                image_ids.extend(post_core.images.iter().cloned());
+            }
+
+            if fields.wants("core", "videos") {
+                image_ids.extend(post_core.videos.iter().cloned());
+            }
         }
 
-        response.insert(
-            "core".to_string(),
-            serde_json::json!({
-                "uuid": &post_core.uuid,
-                "caption": &post_core.caption,
-                "images": &images,
-                "tags": &tags,
-                "mentions": &mentions,
-                "videos": &video_thumbnails,
-                "audio": &post_core.audio,
-                "poll": &poll,
-                "created_at": &post_core.created_at,
-                "owner_id": &post_core.owner,
-            }),
-        );
+        if fields.wants("owner", "image") {
+            for profile in account_profiles.values() {
+                if let Some(image_id) = &profile.profile_picture {
+                    image_ids.push(image_id.clone());
+                }
+            }
+        }
 
-        response.insert(
-            "stat".to_string(),
-            serde_json::to_value(
-                post_stat
-            ).unwrap()
-        );
+        let image_ids = unique(image_ids.iter());
+        let mut images = HashMap::new();
+        if image_ids.len() > 0 {
+            let collection = db.collection::<ImageStruct>("image");
+            for image in fetch(&collection, doc!{"uuid": {"$in": &image_ids}}).await? {
+                images.insert(image.uuid.clone(), image);
+            }
+        }
 
-        // Check if post liked
-        let collection = db.collection::<Post::PostLike>("post_like");
-        let result = collection.count_documents(
-            doc!{
-                "post_id": post_core.uuid.clone(),
-                "liked_by": user_id.clone(),
+        let mut owners = HashMap::new();
+        for post_core in post_cores {
+            if !fields.includes("owner") || owners.contains_key(&post_core.owner) {
+                continue;
             }
-        ).await;
 
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
+            let owner = match post_core.owner_type {
+                Post::PostOwnerType::Page => serde_json::json!({
+                    "uuid": &post_core.owner,
+                    "owner_type": Post::PostOwnerType::Page,
+                    "profile_verified": false,
+                }),
+                Post::PostOwnerType::User => {
+                    let account_core = match account_cores.get(&post_core.owner) {
+                        Some(account_core) => account_core,
+                        None => return Err(Response::not_found(
+                            "Account core not found"
+                        )),
+                    };
+
+                    let account_profile = match account_profiles.get(&post_core.owner) {
+                        Some(account_profile) => account_profile,
+                        None => return Err(Response::not_found(
+                            "Account profile not found"
+                        )),
+                    };
+
+                    let profile_picture = account_profile.profile_picture.as_ref()
+                    .and_then(|image_id| images.get(image_id));
+
+                    serde_json::json!({
+                        "uuid": &post_core.owner,
+                        "name": format!(
+                            "{} {}",
+                            account_profile.first_name,
+                            account_profile.last_name
+                        ),
+                        "username": &account_core.username,
+                        "image": profile_picture,
+                        "owner_type": Post::PostOwnerType::User,
+                        "profile_verified": account_profile.profile_verified,
+                    })
+                }
+            };
+
+            owners.insert(post_core.owner.clone(), owner);
         }
 
-        let liked = match result.unwrap() {
-            0 => false,
-            _ => true,
-        };
+        Ok(PageData {
+            stats,
+            images,
+            polls,
+            poll_stats,
+            poll_votes,
+            mentions,
+            tags,
+            link_previews,
+            owners,
+            liked,
+            bookmarked,
+        })
+    }
 
-        // Check if post bookmarked
-        let collection = db.collection::<Post::PostBookmark>("post_bookmark");
-        let result = collection.count_documents(
-            doc!{
-                "post_id": post_core.uuid.clone(),
-                "bookmarked_by": user_id.clone(),
-            }
-        ).await;
+    fn render(
+        &self,
+        post_core: &Post::PostCore,
+        fields: &Fields
+    ) -> Result<Map<String, serde_json::Value>, HttpResponse> {
+        let mut response = Map::new();
 
+        if fields.includes("stat") {
+            let post_stat = match self.stats.get(&post_core.uuid) {
+                Some(post_stat) => post_stat,
+                None => return Err(Response::not_found("Post stat found")),
+            };
 
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
+            response.insert(
+                "stat".to_string(),
+                serde_json::to_value(post_stat).unwrap()
+            );
         }
 
-        let bookmarked = match result.unwrap() {
-            0 => false,
-            _ => true,
+        if fields.includes("owner") {
+            response.insert(
+                "owner".to_string(),
+                self.owners.get(&post_core.owner).cloned()
+                .unwrap_or(serde_json::Value::Null)
+            );
+        }
+
+        if fields.includes("meta") {
+            response.insert(
+                "meta".to_string(),
+                serde_json::json!({
+                    "liked": self.liked.contains(&post_core.uuid),
+                    "bookmarked": self.bookmarked.contains(&post_core.uuid),
+                })
+            );
+        }
+
+        if !fields.includes("core") {
+            return Ok(response);
+        }
+
+        let poll = match (&post_core.poll, fields.wants("core", "poll")) {
+            (Some(_), false) => None,
+            (Some(poll_id), true) => match self.polls.get(poll_id) {
+                Some(poll) => Some(render_poll(
+                    poll,
+                    self.poll_stats.get(poll_id),
+                    self.poll_votes.get(poll_id).cloned().unwrap_or_default()
+                )),
+                None => return Err(Response::not_found("Poll not found")),
+            },
+            (None, _) => None,
         };
 
+        let images: Vec<&ImageStruct> = post_core.images.iter()
+        .filter_map(|image_id| self.images.get(image_id))
+        .collect();
+
+        let video_thumbnails: Vec<&ImageStruct> = post_core.videos.iter()
+        .filter_map(|image_id| self.images.get(image_id))
+        .collect();
+
+        let no_mentions = Vec::new();
+        let mentions = self.mentions.get(&post_core.uuid).unwrap_or(&no_mentions);
+
+        let no_tags = Vec::new();
+        let tags = self.tags.get(&post_core.uuid).unwrap_or(&no_tags);
+
         response.insert(
-            "meta".to_string(),
-            serde_json::json!({"liked": liked, "bookmarked": bookmarked})
+            "core".to_string(),
+            serde_json::json!({
+                "uuid": &post_core.uuid,
+                "caption": &post_core.caption,
+                "images": &images,
+                "tags": tags,
+                "mentions": mentions,
+                "videos": &video_thumbnails,
+                "audio": &post_core.audio,
+                "poll": &poll,
+                "content_warning": &post_core.content_warning,
+                "is_nsfw": post_core.is_nsfw,
+                "created_at": &post_core.created_at,
+                "publish_state": &post_core.publish_state,
+                "publish_at": &post_core.publish_at,
+                "edited": post_core.edited_at.is_some(),
+                "edited_at": &post_core.edited_at,
+                "shared_post_id": &post_core.shared_post,
+                "owner_id": &post_core.owner,
+                "link_previews": link_preview::previews_of(
+                    &post_core.caption,
+                    &self.link_previews
+                ),
+            }),
         );
 
-        posts.push(response);
+        Ok(response)
     }
-
-    Ok(
-        HttpResponse::Ok()
-        .content_type("application/json")
-        .json(posts)
-    )
 }
 
-async fn get_poll(db: &Database, poll_id: &Option<String>) -> Result<Option<serde_json::Value>, HttpResponse> {
-    if poll_id.is_none() {
-        return Ok(None);
-    }
+/*
+    Poll with its per option tallies and the viewer's own selection.
+    Tallies come from the `poll_stat` counters kept by the vote endpoint.
+*/
+pub async fn get_poll(
+    db: &Database,
+    poll_id: &Option<String>,
+    user_id: &str
+) -> Result<Option<serde_json::Value>, HttpResponse> {
+    let poll_id = match poll_id {
+        Some(poll_id) => poll_id,
+        None => return Ok(None),
+    };
 
     let collection = db.collection::<Poll::Poll>("poll");
-    let result = collection.find_one(
-        doc!{"uuid": poll_id.clone().unwrap()}
-    ).await;
+    let result = collection.find_one(doc!{"uuid": poll_id}).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -262,23 +690,43 @@ async fn get_poll(db: &Database, poll_id: &Option<String>) -> Result<Option<serd
     }
 
     let poll = option.unwrap();
-    let mut options = Vec::new();
-    let mut total_vote = 0;
-    for option in poll.options.iter() {
-        let collection = db.collection::<Poll::PollVote>("poll_vote");
-        let result = collection.count_documents(
-            doc!{
-                "poll_id": poll.uuid.clone(),
-                "option": option.clone(),
-            }
-        ).await;
 
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Err(Response::internal_server_error(&error.to_string()));
-        }
+    let collection = db.collection::<Poll::PollStat>("poll_stat");
+    let result = collection.find_one(doc!{"uuid": &poll.uuid}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let poll_stat = result.unwrap();
+
+    let collection = db.collection::<Poll::PollVote>("poll_vote");
+    let selected_option = fetch(&collection, doc!{
+        "poll_id": &poll.uuid,
+        "user_id": user_id,
+    }).await?
+    .into_iter()
+    .map(|vote| vote.option)
+    .collect();
+
+    Ok(Some(render_poll(&poll, poll_stat.as_ref(), selected_option)))
+}
 
-        let vote = result.unwrap();
+fn render_poll(
+    poll: &Poll::Poll,
+    poll_stat: Option<&Poll::PollStat>,
+    selected_option: Vec<String>
+) -> serde_json::Value {
+    let (option_votes, voter_count) = match poll_stat {
+        Some(stat) => (stat.option_votes.clone(), stat.voter_count),
+        None => (vec![0; poll.options.len()], 0),
+    };
+
+    let mut options = Vec::new();
+    let mut total_vote = 0;
+    for (i, option) in poll.options.iter().enumerate() {
+        let vote = option_votes.get(i).cloned().unwrap_or(0);
         total_vote += vote;
 
         options.push(serde_json::json!({
@@ -287,121 +735,181 @@ async fn get_poll(db: &Database, poll_id: &Option<String>) -> Result<Option<serd
         }));
     }
 
-    let value = serde_json::json!({
+    let closed = match poll.closes_at {
+        Some(closes_at) => closes_at <= Utc::now().timestamp_millis(),
+        None => false,
+    };
+
+    serde_json::json!({
         "uuid": &poll.uuid,
         "question": &poll.question,
         "type": &poll.r#type,
         "can_add_option": false,
         "options": &options,
         "total_vote": total_vote,
-        "selected_option": []
-    });
+        "voter_count": voter_count,
+        "selected_option": selected_option,
+        "closes_at": &poll.closes_at,
+        "closed": closed,
+        "anonymous": poll.anonymous,
+    })
+}
 
-    Ok(Some(value))
+async fn fetch<T>(
+    collection: &Collection<T>,
+    filter: Document
+) -> Result<Vec<T>, HttpResponse>
+where
+    T: DeserializeOwned + Unpin + Send + Sync,
+{
+    match mongo::find_all(collection, filter).await {
+        Ok(documents) => Ok(documents),
+        Err(error) => {
+            log::error!("{:?}", error);
+            Err(Response::internal_server_error(&error.to_string()))
+        }
+    }
 }
 
-// async fn get_post_owner(db: &Database, owner_id: &str) -> Result<PostOwner, HttpResponse> {
-//     let collection = db.collection::<Account::AccountCore>("account_core");
-
-//     let result = collection.find_one(
-//         doc!{"uuid": &owner_id}
-//     ).await;
-
-//     if let Err(error) = result {
-//         log::error!("{:?}", error);
-//         return Err(Response::internal_server_error(&error.to_string()));
-//     }
-
-//     let option = result.unwrap();
-//     if let None = option {
-//         return Err(Response::not_found("Account core not found"));
-//     }
-
-//     let account_core = option.unwrap();
-
-//     let collection = db.collection::<Account::AccountProfile>("account_profile");
-
-//     let result = collection.find_one(
-//         doc!{"uuid": &owner_id}
-//     ).await;
-
-//     if let Err(error) = result {
-//         log::error!("{:?}", error);
-//         return Err(Response::internal_server_error(&error.to_string()));
-//     }
-
-//     let option = result.unwrap();
-//     if let None = option {
-//         return Err(Response::not_found("Account profile not found"));
-//     }
-
-//     let account_profile = option.unwrap();
-
-//     let profile_picture: Option<ImageStruct> = match account_profile.profile_picture {
-//         Some(image_id) => {
-//             let collection = db.collection::<ImageStruct>("image");
-//             let result = collection.find_one(doc!{"uuid": &image_id}).await;
-
-//             if let Err(error) = result {
-//                 log::error!("{:?}", error);
-//                 return Err(Response::internal_server_error(&error.to_string()));
-//             }
-
-//             let option = result.unwrap();
-//             if let None = option {
-//                 None
-//             } else {
-//                 Some(option.unwrap())
-//             }
-//         },
-//         None => None
-//     };
-
-//     let post_owner = PostOwner {
-//         uuid: owner_id.to_string(),
-//         name: format!("{} {}", account_profile.first_name.clone(), account_profile.last_name.clone()),
-//         image: profile_picture,
-//         owner_type: PostOwnerType::User,
-//         username: account_core.username.clone(),
-//         is_me: false,
-//         following: false,
-//         friend: false
-//     };
-
-//     Ok(post_owner)
-// }
-
-async fn get_images(db: &Database, image_ids: Vec<String>) -> Result<Vec<ImageStruct>, HttpResponse> {
-    let mut images = Vec::new();
-
-    if image_ids.len() == 0 {
-        return Ok(images);
+/* Distinct values in first seen order, for `$in` filters */
+fn unique<'a>(values: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for value in values {
+        if !unique.contains(value) {
+            unique.push(value.clone());
+        }
     }
 
-    let filter = doc! {
-        "uuid": {
-            "$in": image_ids.iter().map(|s| Bson::String(s.clone())).collect::<Vec<Bson>> ()
+    unique
+}
+
+
+/*
+    Queries `build_posts` sends for pages of different sizes, the figures
+    of docs/benchmarks.md. Needs a MongoDB server (MONGO_HOST, MONGO_PORT),
+    seeds a throwaway `<app>_bench` database and drops it afterwards:
+    `cargo test --release query_count_per_page -- --ignored --nocapture`
+*/
+#[cfg(test)]
+mod benchmark {
+    use super::*;
+    use crate::model::Account::{AccountCore, AccountProfile};
+
+    const PAGE_SIZES: &[usize] = &[1, 10, 20, 50];
+    const OWNERS: usize = 10;
+
+    fn post_core(index: usize, now: i64) -> Post::PostCore {
+        Post::PostCore {
+            uuid: format!("bench-post-{}", index),
+            owner: format!("bench-owner-{}", index % OWNERS),
+            caption: Some(format!("Benchmark post {}", index)),
+            images: vec![format!("bench-image-{}", index)],
+            videos: Vec::new(),
+            audio: None,
+            poll: None,
+            shared_post: None,
+            owner_type: Post::PostOwnerType::User,
+            visibility: Post::PostVisibility::Public,
+            is_nsfw: false,
+            content_warning: None,
+            publish_state: Post::PostPublishState::Published,
+            publish_at: None,
+            created_at: now - index as i64,
+            modified_at: now,
+            edited_at: None,
+            deleted_at: None,
+            suspended_at: None,
+            suspended_by: None,
         }
-    };
+    }
 
-    let collection = db.collection::<ImageStruct>("image");
-    let result = collection.find(filter).await;
-        
-    if let Err(error) = result {
-        log::error!("{:?}", error);
-        return Err(Response::internal_server_error(&error.to_string()));
+    async fn seed(db: &Database, now: i64) {
+        let max = *PAGE_SIZES.iter().max().unwrap();
+
+        let post_cores: Vec<Post::PostCore> = (0..max)
+        .map(|index| post_core(index, now))
+        .collect();
+        let post_stats: Vec<Post::PostStat> = post_cores.iter()
+        .map(|post_core| Post::PostStat {
+            uuid: post_core.uuid.clone(),
+            like_count: 0,
+            comment_count: 0,
+            share_count: 0,
+            view_count: 0,
+            modified_at: now,
+        })
+        .collect();
+
+        let account_cores: Vec<AccountCore> = (0..OWNERS)
+        .map(|index| AccountCore {
+            uuid: format!("bench-owner-{}", index),
+            email_address: format!("bench-{}@example.com", index),
+            username: format!("bench_{}", index),
+            password: String::new(),
+            email_verified: true,
+            role: AccountRole::User,
+            two_a_factor_auth_enabled: false,
+            two_a_factor_auth_updated: None,
+            created_at: now,
+            suspended_at: None,
+            suspended_by: None,
+        })
+        .collect();
+        let account_profiles: Vec<AccountProfile> = (0..OWNERS)
+        .map(|index| AccountProfile {
+            uuid: format!("bench-owner-{}", index),
+            first_name: "Bench".to_string(),
+            last_name: index.to_string(),
+            phone_number: None,
+            date_of_birth: None,
+            gender: None,
+            profile_picture: Some(format!("bench-picture-{}", index)),
+            biography: None,
+            profile_verified: false,
+            modified_at: now,
+        })
+        .collect();
+
+        db.collection("post_core").insert_many(post_cores).await.unwrap();
+        db.collection("post_stat").insert_many(post_stats).await.unwrap();
+        db.collection("account_core").insert_many(account_cores).await.unwrap();
+        db.collection("account_profile").insert_many(account_profiles).await.unwrap();
     }
 
-    let mut cursor = result.unwrap();
-    while let Some(result) = cursor.next().await {
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Err(Response::internal_server_error(&error.to_string()));
+    #[actix_web::test]
+    #[ignore = "needs a MongoDB server"]
+    async fn query_count_per_page() {
+        dotenv::from_filename(".env.dev").ok();
+
+        let db = MongoDB.connect_with(&format!("{}_bench", env!("CARGO_PKG_NAME")));
+        db.drop().await.unwrap();
+
+        let now = Utc::now().timestamp_millis();
+        seed(&db, now).await;
+
+        let collection = db.collection::<Post::PostCore>("post_core");
+        let mut post_cores = fetch(&collection, doc!{}).await.unwrap();
+        post_cores.sort_by_key(|post_core| -post_core.created_at);
+
+        let mut counts = Vec::new();
+        for size in PAGE_SIZES {
+            let page = &post_cores[..*size];
+
+            let (result, queries) = query_counter::count(async {
+                let mut viewer = Viewer::load(&db, "bench-viewer").await?;
+                build_posts(&db, &mut viewer, page).await
+            }).await;
+
+            assert_eq!(result.unwrap().len(), *size);
+            counts.push(queries);
         }
 
-        let image = result.unwrap();
-        images.push(image);
-    }
+        db.drop().await.unwrap();
 
-    Ok(images)
-}
\ No newline at end of file
+        // One query per collection, however many posts are on the page
+        assert!(
+            counts.iter().all(|queries| *queries == counts[0]),
+            "queries per page of {:?} posts: {:?}", PAGE_SIZES, counts
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use futures::StreamExt;
+use mongodb::Database;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::model::Post::PostCore;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use crate::utils::view_counter::ViewCounter;
+use crate::utils::visibility::{unpublished_states, Viewer};
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+
+const MAX_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    post_ids: Vec<String>,
+}
+
+/*
+    Records the posts a client displayed.
+    Only posts that exist and the caller may read are counted.
+    Views are deduplicated per viewer and written to `post_stat`
+    in batches by the flush routine.
+*/
+pub async fn task(
+    req: HttpRequest,
+    view_counter: web::Data<ViewCounter>,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    if req_body.post_ids.len() > MAX_BATCH_SIZE {
+        return Ok(Response::bad_request(&format!(
+            "At most {} posts can be reported at once", MAX_BATCH_SIZE
+        )));
+    }
+
+    let db = MongoDB.connect();
+
+    let post_ids = match viewable_ids(&db, &user_id, &req_body.post_ids).await {
+        Ok(post_ids) => post_ids,
+        Err(error) => return Ok(error),
+    };
+
+    let counted = view_counter.record(&user_id, &post_ids);
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({"counted": counted}))
+    )
+}
+
+/* Which of `post_ids` are published, not deleted and readable by the viewer */
+async fn viewable_ids(
+    db: &Database,
+    user_id: &str,
+    post_ids: &Vec<String>
+) -> Result<Vec<String>, HttpResponse> {
+    if post_ids.len() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find(doc!{
+        "uuid": {"$in": post_ids},
+        "publish_state": {"$nin": unpublished_states()},
+        "deleted_at": null,
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut viewer = Viewer::load(db, user_id).await?;
+
+    let mut viewable = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let post_core = result.unwrap();
+        if viewer.can_view_post(db, &post_core).await? {
+            viewable.push(post_core.uuid);
+        }
+    }
+
+    Ok(viewable)
+}
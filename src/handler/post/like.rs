@@ -0,0 +1,152 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::{mongo, notification};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Notification::NotificationType,
+    Post::{PostCore, PostLike, PostOwnerType, PostStat},
+};
+
+/*
+    Likes the post `post_id`.
+    Liking an already liked post is a no-op.
+*/
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find_one(doc!{"uuid": &post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("post not found"));
+    }
+
+    let post_core = option.unwrap();
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    match viewer.can_view_post(&db, &post_core).await {
+        Ok(true) => (),
+        Ok(false) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::not_found("post not found"));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    }
+
+    let now = Utc::now().timestamp_millis();
+
+    // Upsert keeps repeated likes from creating duplicates
+    let collection = db.collection::<PostLike>("post_like");
+    let result = collection.update_one(
+        doc!{"post_id": &post_id, "liked_by": &user_id},
+        doc!{
+            "$setOnInsert": {
+                "post_id": &post_id,
+                "liked_by": &user_id,
+                "liked_at": now
+            }
+        },
+    ).upsert(true).session(&mut session).await;
+
+    let newly_liked = match result {
+        Ok(result) => result.upserted_id.is_some(),
+        // A concurrent like of the same post won the unique index
+        Err(error) if mongo::is_duplicate_key(&error) => {
+            session.abort_transaction().await.ok();
+            return Ok(liked_response(&post_id));
+        },
+        Err(error) => {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    };
+
+    if newly_liked {
+        let collection = db.collection::<PostStat>("post_stat");
+        let result = collection.update_one(
+            doc!{"uuid": &post_id},
+            doc!{
+                "$inc": {"like_count": 1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if newly_liked {
+        if let PostOwnerType::User = post_core.owner_type {
+            if let Err(error) = notification::send(
+                &db,
+                &post_core.owner,
+                &user_id,
+                NotificationType::Like,
+                Some("liked your post".to_string()),
+                Some(json!({"post_id": &post_id}))
+            ).await {
+                return Ok(error);
+            }
+        }
+    }
+
+    Ok(liked_response(&post_id))
+}
+
+fn liked_response(post_id: &str) -> HttpResponse {
+    HttpResponse::Ok()
+    .content_type("application/json")
+    .json(json!({
+        "post_id": post_id,
+        "liked": true
+    }))
+}
@@ -0,0 +1,86 @@
+use futures::StreamExt;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostLike;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    cursor: Option<String>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+/* Lists who liked the post `post_id`, newest first */
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let pagination = match Pagination::new(
+        Keyset::desc("liked_at", "liked_by"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20),
+        Legacy::Page(req_query.page),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    match viewer.can_view_post_id(&db, &post_id).await {
+        Ok(true) => (),
+        Ok(false) => return Ok(Response::not_found("post not found")),
+        Err(error) => return Ok(error),
+    }
+
+    let collection = db.collection::<PostLike>("post_like");
+    let result = pagination.find(&collection, doc!{
+        "post_id": &post_id,
+        "liked_by": {"$nin": viewer.blocked()},
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut likes = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        likes.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut likes,
+        |like| (like.liked_at, like.liked_by.clone())
+    );
+
+    Ok(pagination.response("likes", likes, page_info))
+}
@@ -0,0 +1,244 @@
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::error::TRANSIENT_TRANSACTION_ERROR;
+use mongodb::{Database, bson::{doc, Document}};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::{PostCore, PostPin, PostPublishState};
+use crate::utils::{mongo, page_access};
+
+/* Posts a profile or page can pin at once */
+pub const MAX_PINS: usize = 3;
+
+/* Pins a published post to the top of its owner's profile or page */
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find_one(doc!{
+        "uuid": &post_id,
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Ok(Response::not_found("post not found"));
+    }
+
+    let post_core = option.unwrap();
+
+    match can_pin_on(&db, &post_core.owner, &user_id).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(Response::forbidden(
+            "You are not authorized to pin this post"
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    if post_core.publish_state != PostPublishState::Published ||
+    post_core.suspended_at.is_some() {
+        return Ok(Response::conflict("Only published posts can be pinned"));
+    }
+
+    let pins = match get_pins(&db, &post_core.owner).await {
+        Ok(pins) => pins,
+        Err(error) => return Ok(error),
+    };
+
+    if pins.iter().any(|pin| pin.post_id == post_id) {
+        return Ok(Response::conflict("Post is already pinned"));
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    // Every pin of the owner writes this document first, so concurrent
+    // pins conflict instead of both passing the count
+    let collection = db.collection::<Document>("post_pin_lock");
+    let result = collection.update_one(
+        doc!{"owner": &post_core.owner},
+        doc!{"$set": {"locked_at": Utc::now().timestamp_millis()}},
+    ).upsert(true).session(&mut session).await;
+
+    if let Err(error) = result {
+        session.abort_transaction().await.ok();
+        return Ok(pin_write_error(error));
+    }
+
+    let collection = db.collection::<PostPin>("post_pin");
+    let result = collection.find(doc!{"owner": &post_core.owner})
+    .sort(doc!{"position": 1}).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut pins = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next(&mut session).await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        pins.push(result.unwrap());
+    }
+
+    if pins.len() >= MAX_PINS {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::conflict(
+            &format!("At most {} posts can be pinned", MAX_PINS)
+        ));
+    }
+
+    let pin = PostPin {
+        owner: post_core.owner.clone(),
+        post_id: post_id.clone(),
+        position: pins.last().map(|pin| pin.position + 1).unwrap_or(0),
+        pinned_at: Utc::now().timestamp_millis(),
+    };
+
+    let result = collection.insert_one(&pin).session(&mut session).await;
+
+    if let Err(error) = result {
+        session.abort_transaction().await.ok();
+        return Ok(pin_write_error(error));
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        return Ok(pin_write_error(error));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!(pin))
+    )
+}
+
+/* A concurrent pin of the same owner or post is a conflict, not a failure */
+fn pin_write_error(error: mongodb::error::Error) -> HttpResponse {
+    if mongo::is_duplicate_key(&error) {
+        return Response::conflict("Post is already pinned");
+    }
+
+    if error.contains_label(TRANSIENT_TRANSACTION_ERROR) {
+        return Response::conflict("Pins changed at the same time, try again");
+    }
+
+    log::error!("{:?}", error);
+    Response::internal_server_error(&error.to_string())
+}
+
+/* Users pin on their own profile, owner and admins on their page */
+pub async fn can_pin_on(
+    db: &Database,
+    owner: &str,
+    user_id: &str
+) -> Result<bool, HttpResponse> {
+    if owner == user_id {
+        return Ok(true);
+    }
+
+    page_access::can_manage(db, owner, user_id).await
+}
+
+/*
+    Pins of a profile or page in order.
+    Pins whose post got deleted or suspended are dropped on the way.
+*/
+pub async fn get_pins(
+    db: &Database,
+    owner: &str
+) -> Result<Vec<PostPin>, HttpResponse> {
+    let collection = db.collection::<PostPin>("post_pin");
+    let result = collection.find(doc!{"owner": owner})
+    .sort(doc!{"position": 1}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut pins = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        pins.push(result.unwrap());
+    }
+
+    if pins.len() == 0 {
+        return Ok(pins);
+    }
+
+    let post_ids: Vec<String> = pins.iter()
+    .map(|pin| pin.post_id.clone())
+    .collect();
+
+    let post_collection = db.collection::<PostCore>("post_core");
+    let result = post_collection.distinct("uuid", doc!{
+        "uuid": {"$in": &post_ids},
+        "deleted_at": null,
+        "suspended_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let live: Vec<String> = result.unwrap().iter()
+    .filter_map(|uuid| uuid.as_str().map(|uuid| uuid.to_string()))
+    .collect();
+
+    let (pins, stale): (Vec<PostPin>, Vec<PostPin>) = pins.into_iter()
+    .partition(|pin| live.contains(&pin.post_id));
+
+    if stale.len() > 0 {
+        let stale_ids: Vec<String> = stale.into_iter()
+        .map(|pin| pin.post_id)
+        .collect();
+
+        let result = collection.delete_many(
+            doc!{"post_id": {"$in": stale_ids}}
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    Ok(pins)
+}
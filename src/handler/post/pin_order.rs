@@ -0,0 +1,81 @@
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostPin;
+use super::Pin::{can_pin_on, get_pins};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    owner: String,
+    post_ids: Vec<String>,
+}
+
+/* Reorders the pins of a profile or page, `post_ids` lists all of them */
+pub async fn task(
+    req: HttpRequest,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let db = MongoDB.connect();
+
+    match can_pin_on(&db, &req_body.owner, &user_id).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(Response::forbidden(
+            "You are not authorized to reorder these pins"
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    let mut pins = match get_pins(&db, &req_body.owner).await {
+        Ok(pins) => pins,
+        Err(error) => return Ok(error),
+    };
+
+    let mut requested = req_body.post_ids.clone();
+    requested.sort();
+    requested.dedup();
+
+    if requested.len() != req_body.post_ids.len() ||
+    requested.len() != pins.len() ||
+    !pins.iter().all(|pin| requested.contains(&pin.post_id)) {
+        return Ok(Response::bad_request(
+            "post_ids must list every pinned post once"
+        ));
+    }
+
+    let collection = db.collection::<PostPin>("post_pin");
+    for (position, post_id) in req_body.post_ids.iter().enumerate() {
+        let result = collection.update_one(
+            doc!{"owner": &req_body.owner, "post_id": post_id},
+            doc!{"$set": {"position": position as i64}},
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    for pin in pins.iter_mut() {
+        pin.position = req_body.post_ids.iter()
+        .position(|post_id| *post_id == pin.post_id)
+        .unwrap_or(0) as i64;
+    }
+    pins.sort_by_key(|pin| pin.position);
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({ "pins": pins }))
+    )
+}
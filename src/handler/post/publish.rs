@@ -0,0 +1,211 @@
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::{Database, bson::doc};
+use crate::utils::mention;
+use crate::utils::mention::MentionedIn;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    ImageStruct,
+    Mention,
+    Post::{PostCore, PostMention, PostPublishState, PostTag},
+};
+use super::Edit::can_edit;
+
+/* Publishes a draft or scheduled post right away */
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let post_core = match get_unpublished(&db, &post_id, &user_id).await {
+        Ok(post_core) => post_core,
+        Err(error) => return Ok(error),
+    };
+
+    let published_at = match publish_post(&db, &post_core).await {
+        Ok(Some(published_at)) => published_at,
+        Ok(None) => return Ok(Response::conflict("Post is already published")),
+        Err(error) => return Ok(error),
+    };
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "uuid": &post_id,
+            "publish_state": PostPublishState::Published,
+            "publish_at": null,
+            "created_at": published_at,
+        }))
+    )
+}
+
+/*
+    Draft or scheduled post `post_id` the user may manage.
+    Shared by the publish, schedule and unschedule endpoints.
+*/
+pub async fn get_unpublished(
+    db: &Database,
+    post_id: &str,
+    user_id: &str
+) -> Result<PostCore, HttpResponse> {
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find_one(doc!{
+        "uuid": post_id,
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Err(Response::not_found("post not found"));
+    }
+
+    let post_core = option.unwrap();
+
+    if !can_edit(db, &post_core, user_id).await? {
+        return Err(Response::not_found("post not found"));
+    }
+
+    if post_core.publish_state == PostPublishState::Published {
+        return Err(Response::conflict("Post is already published"));
+    }
+
+    Ok(post_core)
+}
+
+/*
+    Turns an unpublished post into a published one: the post is dated
+    to now so it lands on top of feeds, its images stop being temporary,
+    its tags become visible and mentioned users are notified.
+    Returns `None` when someone else published it first.
+*/
+pub async fn publish_post(
+    db: &Database,
+    post_core: &PostCore
+) -> Result<Option<i64>, HttpResponse> {
+    let now = Utc::now().timestamp_millis();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db_acid, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db_acid.collection::<PostCore>("post_core");
+    let result = collection.update_one(
+        doc!{
+            "uuid": &post_core.uuid,
+            "publish_state": {"$in": [
+                PostPublishState::Draft.to_string(),
+                PostPublishState::Scheduled.to_string(),
+            ]}
+        },
+        doc!{"$set": {
+            "publish_state": PostPublishState::Published.to_string(),
+            "publish_at": null,
+            "created_at": now,
+            "modified_at": now,
+        }},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().matched_count == 0 {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(None);
+    }
+
+    let media: Vec<String> = [
+        post_core.images.as_slice(),
+        post_core.videos.as_slice(),
+    ].concat();
+
+    if media.len() > 0 {
+        let collection = db_acid.collection::<ImageStruct>("image");
+        let result = collection.update_many(
+            doc!{"uuid": {"$in": &media}},
+            doc!{"$set": {"temporary": false}},
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    let collection = db_acid.collection::<PostTag>("post_tag");
+    let result = collection.update_many(
+        doc!{"post_id": &post_core.uuid},
+        doc!{"$set": {"created_at": now}},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostMention>("post_mention");
+    let result = collection.find(doc!{"post_id": &post_core.uuid}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut mentions = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let post_mention = result.unwrap();
+        mentions.push(Mention {
+            user_id: post_mention.user_id,
+            start: post_mention.start,
+            end: post_mention.end,
+        });
+    }
+
+    // The scheduler has no acting user, page posts notify as the page
+    mention::notify(
+        db,
+        &post_core.owner,
+        &mentions,
+        MentionedIn::Post { post_id: &post_core.uuid }
+    ).await?;
+
+    Ok(Some(now))
+}
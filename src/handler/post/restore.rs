@@ -0,0 +1,93 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostCore;
+use super::Delete::{can_delete, set_deleted, RESTORE_WINDOW_DAYS};
+
+/*
+    Brings back a deleted post with everything its deletion took,
+    as long as it's within the restore window.
+*/
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find_one(doc!{"uuid": &post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Ok(Response::not_found("post not found"));
+    }
+
+    let post = option.unwrap();
+
+    match can_delete(&db, &user.user_id, &user.role, &post).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(Response::forbidden(
+            "You are not authorized to restore this post"
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    let deleted_at = match post.deleted_at {
+        Some(deleted_at) => deleted_at,
+        None => return Ok(Response::conflict("Post isn't deleted")),
+    };
+
+    let window = Duration::days(RESTORE_WINDOW_DAYS).num_milliseconds();
+    if Utc::now().timestamp_millis() - deleted_at > window {
+        return Ok(Response::gone("The restore window of this post has passed"));
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if let Err(error) = set_deleted(
+        &db,
+        &mut session,
+        &post,
+        Some(deleted_at),
+        None
+    ).await {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(error);
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "uuid": &post_id,
+            "restored": true,
+        }))
+    )
+}
@@ -0,0 +1,61 @@
+use futures::StreamExt;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostRevision;
+
+/* Previous versions of the post `post_id`, newest first */
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    match viewer.can_view_post_id(&db, &post_id).await {
+        Ok(true) => (),
+        Ok(false) => return Ok(Response::not_found("post not found")),
+        Err(error) => return Ok(error),
+    }
+
+    let collection = db.collection::<PostRevision>("post_revision");
+    let result = collection.find(doc!{"post_id": &post_id})
+    .sort(doc!{"revised_at": -1}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut revisions = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        revisions.push(result.unwrap());
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(revisions)
+    )
+}
@@ -0,0 +1,79 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::{PostCore, PostPublishState};
+use super::Publish::get_unpublished;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    publish_at: i64,
+}
+
+/* Schedules a draft, or moves the publishing time of a scheduled post */
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let now = Utc::now().timestamp_millis();
+    if req_body.publish_at <= now {
+        return Ok(Response::bad_request(
+            "Publishing time must be in the future"
+        ));
+    }
+
+    let db = MongoDB.connect();
+
+    if let Err(error) = get_unpublished(&db, &post_id, &user_id).await {
+        return Ok(error);
+    }
+
+    // The scheduler may publish it in between, only unpublished posts move
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.update_one(
+        doc!{
+            "uuid": &post_id,
+            "publish_state": {"$in": [
+                PostPublishState::Draft.to_string(),
+                PostPublishState::Scheduled.to_string(),
+            ]}
+        },
+        doc!{"$set": {
+            "publish_state": PostPublishState::Scheduled.to_string(),
+            "publish_at": req_body.publish_at,
+            "modified_at": now,
+        }},
+    ).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().matched_count == 0 {
+        return Ok(Response::conflict("Post is already published"));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "uuid": &post_id,
+            "publish_state": PostPublishState::Scheduled,
+            "publish_at": req_body.publish_at,
+        }))
+    )
+}
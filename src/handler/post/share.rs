@@ -0,0 +1,205 @@
+use uuid::Uuid;
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::notification;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Notification::NotificationType,
+    Post::{PostCore, PostOwnerType, PostPublishState, PostStat, PostVisibility},
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    caption: Option<String>,
+    visibility: PostVisibility,
+}
+
+/*
+    Reposts `post_id` as a new post of the user.
+    With a caption it becomes a quote, without one a plain repost.
+*/
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find_one(doc!{
+        "uuid": &post_id,
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("post not found"));
+    }
+
+    let original = option.unwrap();
+
+    // Even the owner can't share a post that isn't out yet
+    if original.publish_state != PostPublishState::Published {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("post not found"));
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    match viewer.can_view_post(&db, &original).await {
+        Ok(true) => (),
+        Ok(false) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::not_found("post not found"));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let caption = req_body.caption.clone()
+    .map(|caption| caption.trim().to_string())
+    .filter(|caption| !caption.is_empty());
+
+    let post_core = PostCore {
+        uuid: Uuid::new_v4().to_string(),
+        owner: user_id.clone(),
+        owner_type: PostOwnerType::User,
+        caption,
+        images: vec![],
+        videos: vec![],
+        audio: None,
+        poll: None,
+        shared_post: Some(original.uuid.clone()),
+        visibility: req_body.visibility.clone(),
+        is_nsfw: original.is_nsfw,
+        content_warning: original.content_warning.clone(),
+        publish_state: PostPublishState::Published,
+        publish_at: None,
+        modified_at: now,
+        created_at: now,
+        edited_at: None,
+        deleted_at: None,
+        suspended_at: None,
+        suspended_by: None,
+    };
+
+    let result = collection.insert_one(&post_core)
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let post_stat = PostStat {
+        uuid: post_core.uuid.clone(),
+        like_count: 0,
+        comment_count: 0,
+        share_count: 0,
+        view_count: 0,
+        modified_at: now,
+    };
+
+    let collection = db.collection::<PostStat>("post_stat");
+    let result = collection.insert_one(&post_stat)
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let result = collection.update_one(
+        doc!{"uuid": &original.uuid},
+        doc!{
+            "$inc": {"share_count": 1},
+            "$set": {"modified_at": now}
+        },
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if let PostOwnerType::User = original.owner_type {
+        if let Err(error) = notification::send(
+            &db,
+            &original.owner,
+            &user_id,
+            NotificationType::Shared,
+            Some("shared your post".to_string()),
+            Some(json!({
+                "post_id": &original.uuid,
+                "shared_post_id": &post_core.uuid
+            }))
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "core": json!({
+                "uuid": &post_core.uuid,
+                "owner_id": &post_core.owner,
+                "caption": &post_core.caption,
+                "shared_post_id": &post_core.shared_post,
+                "created_at": &post_core.created_at,
+                "edited": false,
+                "edited_at": null,
+            }),
+            "stat": &post_stat,
+            "meta": json!({
+                "bookmarked": false,
+                "liked": false,
+            }),
+        }))
+    )
+}
@@ -0,0 +1,62 @@
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostPin;
+use super::Pin::can_pin_on;
+
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::<PostPin>("post_pin");
+    let result = collection.find_one(doc!{"post_id": &post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        return Ok(Response::not_found("Post isn't pinned"));
+    }
+
+    let pin = option.unwrap();
+
+    match can_pin_on(&db, &pin.owner, &user_id).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(Response::forbidden(
+            "You are not authorized to unpin this post"
+        )),
+        Err(error) => return Ok(error),
+    }
+
+    let result = collection.delete_one(doc!{"post_id": &post_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "post_id": &post_id,
+            "pinned": false
+        }))
+    )
+}
@@ -0,0 +1,61 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::{PostCore, PostPublishState};
+use super::Publish::get_unpublished;
+
+/* Cancels the schedule of a post, it goes back to being a draft */
+pub async fn task(
+    req: HttpRequest,
+    post_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let post_id = post_id.into_inner();
+
+    let db = MongoDB.connect();
+
+    if let Err(error) = get_unpublished(&db, &post_id, &user_id).await {
+        return Ok(error);
+    }
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.update_one(
+        doc!{
+            "uuid": &post_id,
+            "publish_state": PostPublishState::Scheduled.to_string()
+        },
+        doc!{"$set": {
+            "publish_state": PostPublishState::Draft.to_string(),
+            "publish_at": null,
+            "modified_at": Utc::now().timestamp_millis(),
+        }},
+    ).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().matched_count == 0 {
+        return Ok(Response::conflict("Post isn't scheduled"));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "uuid": &post_id,
+            "publish_state": PostPublishState::Draft,
+            "publish_at": null,
+        }))
+    )
+}
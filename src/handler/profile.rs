@@ -8,4 +8,35 @@ pub mod update;
 pub use update as Update;
 
 pub mod search;
-pub use search as Search;
\ No newline at end of file
+pub use search as Search;
+
+pub mod get_privacy;
+pub use get_privacy as GetPrivacy;
+
+pub mod update_privacy;
+pub use update_privacy as UpdatePrivacy;
+
+pub mod get_content_preferences;
+pub use get_content_preferences as GetContentPreferences;
+
+pub mod update_content_preferences;
+pub use update_content_preferences as UpdateContentPreferences;
+
+pub mod follow;
+pub use follow as Follow;
+
+pub mod follow_request_list;
+pub use follow_request_list as FollowRequestList;
+
+pub mod follow_request_respond;
+pub use follow_request_respond as FollowRequestRespond;
+
+
+pub mod like;
+pub use like as Like;
+
+pub mod unlike;
+pub use unlike as Unlike;
+
+pub mod like_list;
+pub use like_list as LikeList;
\ No newline at end of file
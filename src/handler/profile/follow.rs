@@ -0,0 +1,287 @@
+use uuid::Uuid;
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::{notification, privacy};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Account::{AccountCore, AccountFollow, AccountFollowRequest, AccountSocial},
+    Notification::NotificationType,
+};
+
+/*
+    Toggles following `target_id`.
+    Private accounts receive a follow request instead, calling this
+    again while the request is pending cancels it.
+*/
+pub async fn task(
+    req: HttpRequest,
+    target_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let target_id = target_id.into_inner();
+
+    if target_id == user_id {
+        return Ok(Response::bad_request("You can't follow yourself"));
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<AccountCore>("account_core");
+    let result = collection.find_one(doc!{"uuid": &target_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().is_none() {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("user not found"));
+    }
+
+    match privacy::is_blocked_between(&db, &target_id, &user_id).await {
+        Ok(false) => (),
+        Ok(true) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::forbidden("You can't follow this user"));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let follow_collection = db.collection::<AccountFollow>("account_follow");
+    let request_collection = db.collection::
+    <AccountFollowRequest>("account_follow_request");
+    let social_collection = db.collection::<AccountSocial>("account_social");
+
+    // Already following — unfollow
+    let result = follow_collection.delete_one(doc!{
+        "user_id": &target_id,
+        "followed_by": &user_id
+    }).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().deleted_count > 0 {
+        let result = social_collection.update_one(
+            doc!{"uuid": &target_id},
+            doc!{
+                "$inc": {"follower_count": -1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let result = social_collection.update_one(
+            doc!{"uuid": &user_id},
+            doc!{
+                "$inc": {"following_count": -1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        /* DATABASE ACID COMMIT */
+        if let Err(error) = session.commit_transaction().await {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        return Ok(
+            HttpResponse::Ok()
+            .content_type("application/json")
+            .json(json!({
+                "user_id": &target_id,
+                "is_following": false,
+                "is_requested": false
+            }))
+        );
+    }
+
+    // Pending request — cancel it
+    let result = request_collection.delete_one(doc!{
+        "user_id": &target_id,
+        "requested_by": &user_id
+    }).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().deleted_count > 0 {
+        /* DATABASE ACID COMMIT */
+        if let Err(error) = session.commit_transaction().await {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        return Ok(
+            HttpResponse::Ok()
+            .content_type("application/json")
+            .json(json!({
+                "user_id": &target_id,
+                "is_following": false,
+                "is_requested": false
+            }))
+        );
+    }
+
+    let settings = match privacy::get_settings(&db, &target_id).await {
+        Ok(settings) => settings,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    // Private account — ask for approval
+    if settings.private_account {
+        let request = AccountFollowRequest {
+            uuid: Uuid::new_v4().to_string(),
+            user_id: target_id.clone(),
+            requested_by: user_id.clone(),
+            requested_at: now,
+        };
+
+        let result = request_collection.insert_one(&request)
+        .session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        /* DATABASE ACID COMMIT */
+        if let Err(error) = session.commit_transaction().await {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        if let Err(error) = notification::send(
+            &db,
+            &target_id,
+            &user_id,
+            NotificationType::Follow,
+            Some("requested to follow you".to_string()),
+            Some(json!({"request_id": &request.uuid}))
+        ).await {
+            return Ok(error);
+        }
+
+        return Ok(
+            HttpResponse::Ok()
+            .content_type("application/json")
+            .json(json!({
+                "user_id": &target_id,
+                "is_following": false,
+                "is_requested": true
+            }))
+        );
+    }
+
+    // Public account — follow right away
+    let follow = AccountFollow {
+        user_id: target_id.clone(),
+        followed_by: user_id.clone(),
+        followed_at: now,
+    };
+
+    let result = follow_collection.insert_one(&follow)
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let result = social_collection.update_one(
+        doc!{"uuid": &target_id},
+        doc!{
+            "$inc": {"follower_count": 1},
+            "$set": {"modified_at": now}
+        },
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let result = social_collection.update_one(
+        doc!{"uuid": &user_id},
+        doc!{
+            "$inc": {"following_count": 1},
+            "$set": {"modified_at": now}
+        },
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if let Err(error) = notification::send(
+        &db,
+        &target_id,
+        &user_id,
+        NotificationType::Follow,
+        Some("started following you".to_string()),
+        None
+    ).await {
+        return Ok(error);
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "user_id": &target_id,
+            "is_following": true,
+            "is_requested": false
+        }))
+    )
+}
@@ -0,0 +1,68 @@
+use futures::StreamExt;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Account::AccountFollowRequest;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    cursor: Option<String>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let pagination = match Pagination::new(
+        Keyset::desc("requested_at", "uuid"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20),
+        Legacy::Page(req_query.page),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+    let collection = db.collection::
+    <AccountFollowRequest>("account_follow_request");
+
+    let result = pagination.find(&collection, doc!{"user_id": &user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut requests = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        requests.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut requests,
+        |request| (request.requested_at, request.uuid.clone())
+    );
+
+    Ok(pagination.response("requests", requests, page_info))
+}
@@ -0,0 +1,136 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::notification;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Account::{AccountFollow, AccountFollowRequest, AccountSocial},
+    Notification::NotificationType,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    request_id: String,
+    accept: bool,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::
+    <AccountFollowRequest>("account_follow_request");
+    let result = collection.find_one_and_delete(doc!{
+        "uuid": &req_body.request_id,
+        "user_id": &user_id
+    }).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("follow request not found"));
+    }
+
+    let request = option.unwrap();
+
+    if req_body.accept {
+        let now = Utc::now().timestamp_millis();
+        let follow = AccountFollow {
+            user_id: user_id.clone(),
+            followed_by: request.requested_by.clone(),
+            followed_at: now,
+        };
+
+        let collection = db.collection::<AccountFollow>("account_follow");
+        let result = collection.insert_one(&follow)
+        .session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let collection = db.collection::<AccountSocial>("account_social");
+        let result = collection.update_one(
+            doc!{"uuid": &user_id},
+            doc!{
+                "$inc": {"follower_count": 1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let result = collection.update_one(
+            doc!{"uuid": &request.requested_by},
+            doc!{
+                "$inc": {"following_count": 1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if req_body.accept {
+        if let Err(error) = notification::send(
+            &db,
+            &request.requested_by,
+            &user_id,
+            NotificationType::Follow,
+            Some("accepted your follow request".to_string()),
+            None
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "request_id": &request.uuid,
+            "user_id": &request.requested_by,
+            "accepted": req_body.accept
+        }))
+    )
+}
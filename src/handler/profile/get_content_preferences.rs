@@ -0,0 +1,36 @@
+use serde_json::json;
+use crate::utils::content_filter;
+use crate::BuiltIns::mongo::MongoDB;
+use actix_web::{Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+
+/* `nsfw_locked` tells clients NSFW stays hidden whatever is saved */
+pub async fn task(req: HttpRequest) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let db = MongoDB.connect();
+
+    let preferences = match content_filter::get_preferences(&db, &user_id).await {
+        Ok(preferences) => preferences,
+        Err(error) => return Ok(error),
+    };
+
+    let nsfw_locked = match content_filter::is_minor(&db, &user_id).await {
+        Ok(minor) => minor,
+        Err(error) => return Ok(error),
+    };
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "preferences": preferences,
+            "nsfw_locked": nsfw_locked,
+        }))
+    )
+}
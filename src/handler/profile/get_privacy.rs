@@ -0,0 +1,26 @@
+use crate::utils::privacy;
+use crate::BuiltIns::mongo::MongoDB;
+use actix_web::{Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+
+pub async fn task(req: HttpRequest) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let db = MongoDB.connect();
+
+    let settings = match privacy::get_settings(&db, &user_id).await {
+        Ok(settings) => settings,
+        Err(error) => return Ok(error),
+    };
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(settings)
+    )
+}
@@ -0,0 +1,142 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::{mongo, notification, privacy};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Account::{AccountCore, AccountLike, AccountSocial},
+    Notification::NotificationType,
+};
+
+/*
+    Likes the profile of `target_id`.
+    Liking an already liked profile is a no-op.
+*/
+pub async fn task(
+    req: HttpRequest,
+    target_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let target_id = target_id.into_inner();
+
+    if target_id == user_id {
+        return Ok(Response::bad_request("You can't like your own profile"));
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<AccountCore>("account_core");
+    let result = collection.find_one(doc!{"uuid": &target_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().is_none() {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("user not found"));
+    }
+
+    match privacy::is_blocked_between(&db, &target_id, &user_id).await {
+        Ok(false) => (),
+        Ok(true) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::forbidden("You can't like this profile"));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    }
+
+    let now = Utc::now().timestamp_millis();
+
+    // Upsert keeps repeated likes from creating duplicates
+    let collection = db.collection::<AccountLike>("account_like");
+    let result = collection.update_one(
+        doc!{"user_id": &target_id, "liked_by": &user_id},
+        doc!{
+            "$setOnInsert": {
+                "user_id": &target_id,
+                "liked_by": &user_id,
+                "liked_at": now
+            }
+        },
+    ).upsert(true).session(&mut session).await;
+
+    let newly_liked = match result {
+        Ok(result) => result.upserted_id.is_some(),
+        // A concurrent like of the same profile won the unique index
+        Err(error) if mongo::is_duplicate_key(&error) => {
+            session.abort_transaction().await.ok();
+            return Ok(liked_response(&target_id));
+        },
+        Err(error) => {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    };
+
+    if newly_liked {
+        let collection = db.collection::<AccountSocial>("account_social");
+        let result = collection.update_one(
+            doc!{"uuid": &target_id},
+            doc!{
+                "$inc": {"like_count": 1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if newly_liked {
+        if let Err(error) = notification::send(
+            &db,
+            &target_id,
+            &user_id,
+            NotificationType::Like,
+            Some("liked your profile".to_string()),
+            Some(json!({"profile_id": &target_id}))
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    Ok(liked_response(&target_id))
+}
+
+fn liked_response(target_id: &str) -> HttpResponse {
+    HttpResponse::Ok()
+    .content_type("application/json")
+    .json(json!({
+        "user_id": target_id,
+        "liked": true
+    }))
+}
@@ -0,0 +1,79 @@
+use futures::StreamExt;
+use mongodb::bson::doc;
+use crate::utils::privacy;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Account::AccountLike;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    cursor: Option<String>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+/* Lists who liked the profile of `target_id`, newest first */
+pub async fn task(
+    req: HttpRequest,
+    target_id: web::Path<String>,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let target_id = target_id.into_inner();
+
+    let pagination = match Pagination::new(
+        Keyset::desc("liked_at", "liked_by"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20),
+        Legacy::Page(req_query.page),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+
+    match privacy::can_view_content(&db, &target_id, &user_id).await {
+        Ok(true) => (),
+        Ok(false) => {
+            return Ok(Response::forbidden("You can't view this profile"));
+        },
+        Err(error) => return Ok(error),
+    }
+
+    let collection = db.collection::<AccountLike>("account_like");
+    let result = pagination.find(&collection, doc!{"user_id": &target_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut likes = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        likes.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut likes,
+        |like| (like.liked_at, like.liked_by.clone())
+    );
+
+    Ok(pagination.response("likes", likes, page_info))
+}
@@ -1,7 +1,9 @@
 use mongodb::Database;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use mongodb::bson::doc;
+use serde_json::{json, Map};
+use mongodb::bson::{doc, Document};
+use crate::utils::privacy;
+use crate::utils::fields::{Fields, Section};
 use crate::BuiltIns::mongo::MongoDB;
 use crate::utils::response::Response;
 use actix_web::{web, Error, HttpResponse, HttpRequest};
@@ -11,16 +13,58 @@ use crate::model::{
     Account::{
         AccountCore,
         AccountProfile,
-        AccountSocial,
         Friends,
         AccountFollow,
         AccountLike,
         AccountBlocked,
+        AccountFollowRequest,
     },
     ImageStruct,
 };
 
-pub async fn task(req: HttpRequest, target_ids: web::Json<Vec<String>>) -> Result<HttpResponse, Error> {
+/* Sections of a profile, for `fields=(core,profile(first_name,...),...)` */
+pub const FIELDS: &[Section] = &[
+    Section {
+        name: "core",
+        fields: &["uuid", "username", "role"],
+    },
+    Section {
+        name: "profile",
+        fields: &[
+            "first_name", "last_name", "biography", "profile_picture",
+            "gender", "profile_verified", "private_account",
+        ],
+    },
+    Section {
+        name: "social",
+        fields: &[
+            "like_count", "follower_count", "following_count",
+            "friend_count",
+        ],
+    },
+    Section {
+        name: "liked",
+        fields: &[],
+    },
+    Section {
+        name: "stat",
+        fields: &[
+            "is_friend", "is_following", "is_follower", "is_liked",
+            "is_blocked", "is_follow_requested", "myself",
+        ],
+    },
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    fields: Option<String>,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    target_ids: web::Json<Vec<String>>,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
     let user = require_access(
         &req,
         AccessRequirement::AnyToken
@@ -28,6 +72,11 @@ pub async fn task(req: HttpRequest, target_ids: web::Json<Vec<String>>) -> Resul
 
     let user_id = user.user_id;
 
+    let fields = match Fields::parse(&req_query.fields, FIELDS) {
+        Ok(fields) => fields,
+        Err(error) => return Ok(error),
+    };
+
     let db = MongoDB.connect();
 
     let mut profiles = Vec::new();
@@ -67,79 +116,86 @@ pub async fn task(req: HttpRequest, target_ids: web::Json<Vec<String>>) -> Resul
 
         let account_profile = option.unwrap();
 
-        // Getting social
-        let collection = db.collection::<AccountSocial>("account_social");
-        let result = collection.find_one(doc!{"uuid": &target_id}).await;
+        let mut response = Map::new();
 
-        if let Err(error) = result {
-            log::error!("{:?}", error);
-            return Ok(Response::internal_server_error(&error.to_string()));
-        }
+        response.insert("core".to_string(), json!({
+            "uuid": &account_core.uuid,
+            "username": &account_core.username,
+            "role": &account_core.role,
+        }));
 
-        let option = result.unwrap();
-        if let None = option {
-            return Ok(Response::not_found("user not found"));
+        if fields.includes("profile") {
+            let profile_picture = match (
+                &account_profile.profile_picture,
+                fields.wants("profile", "profile_picture")
+            ) {
+                (Some(image_id), true) => match get_image(&db, image_id).await {
+                    Ok(image) => image,
+                    Err(error) => return Ok(error),
+                },
+                _ => None,
+            };
+
+            let private_account = match fields.wants("profile", "private_account") {
+                true => match privacy::get_settings(&db, &target_id).await {
+                    Ok(settings) => settings.private_account,
+                    Err(error) => return Ok(error),
+                },
+                false => false,
+            };
+
+            response.insert("profile".to_string(), json!({
+                "first_name": &account_profile.first_name,
+                "last_name": &account_profile.last_name,
+                "biography": &account_profile.biography,
+                "profile_picture": profile_picture,
+                "gender": &account_profile.gender,
+                "profile_verified": &account_profile.profile_verified,
+                "private_account": private_account,
+            }));
         }
 
-        let account_social = option.unwrap();
-
-        let profile_picture: Option<ImageStruct> = match account_profile.profile_picture {
-            Some(image_id) => {
-                let collection = db.collection::<ImageStruct>("image");
-                let result = collection.find_one(doc!{"uuid": &image_id}).await;
+        if fields.includes("social") {
+            let collection = db.collection::<Document>("account_social");
+            let result = collection.find_one(doc!{"uuid": &target_id})
+            .projection(fields.projection("social")).await;
 
-                if let Err(error) = result {
-                    log::error!("{:?}", error);
-                    return Ok(Response::internal_server_error(
-                        &error.to_string()
-                    ));
-                }
+            if let Err(error) = result {
+                log::error!("{:?}", error);
+                return Ok(Response::internal_server_error(&error.to_string()));
+            }
 
-                let option = result.unwrap();
-                if let None = option {
-                    None
-                } else {
-                    Some(option.unwrap())
-                }
-            },
-            None => None
-        };
-
-        let profile_stat = match get_profile_stat(
-            &user_id,
-            &target_id,
-            &db
-        ).await {
-            Ok(stat) => stat,
-            Err(error) => {
-                return Ok(error);
+            let option = result.unwrap();
+            if let None = option {
+                return Ok(Response::not_found("user not found"));
             }
-        };
 
+            response.insert(
+                "social".to_string(),
+                serde_json::to_value(option.unwrap()).unwrap()
+            );
+        }
 
-        profiles.push(json!({
-            "core": json!({
-                "uuid": &account_core.uuid,
-                "username": &account_core.username,
-                "role": &account_core.role,
-            }),
-            "profile": json!({
-                "first_name": &account_profile.first_name,
-                "last_name": &account_profile.last_name,
-                "biography": &account_profile.biography,
-                "profile_picture": profile_picture,
-                "gender": &account_profile.gender,
-                "profile_verified": &account_profile.profile_verified,
-            }),
-            "social": json!({
-                "like_count": &account_social.like_count,
-                "follower_count": &account_social.follower_count,
-                "following_count": &account_social.following_count,
-                "friend_count": &account_social.friend_count,
-            }),
-            "stat": profile_stat,
-        }));
+        if fields.includes("stat") || fields.includes("liked") {
+            let profile_stat = match get_profile_stat(
+                &user_id,
+                &target_id,
+                &db
+            ).await {
+                Ok(stat) => stat,
+                Err(error) => {
+                    return Ok(error);
+                }
+            };
+
+            response.insert("liked".to_string(), json!(profile_stat.is_liked));
+            response.insert(
+                "stat".to_string(),
+                serde_json::to_value(profile_stat).unwrap()
+            );
+        }
 
+        profiles.push(fields.apply(response));
     }
 
     
@@ -150,6 +206,21 @@ pub async fn task(req: HttpRequest, target_ids: web::Json<Vec<String>>) -> Resul
     )
 }
 
+async fn get_image(
+    db: &Database,
+    image_id: &str
+) -> Result<Option<ImageStruct>, HttpResponse> {
+    let collection = db.collection::<ImageStruct>("image");
+    let result = collection.find_one(doc!{"uuid": image_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(result.unwrap())
+}
+
 fn remove_duplicates(items: Vec<String>) -> Vec<String> {
     let mut unique_items: Vec<String> = Vec::new();
     for item in items {
@@ -168,6 +239,7 @@ pub struct ProfileStat {
     pub is_follower: bool,
     pub is_liked: bool,
     pub is_blocked: bool,
+    pub is_follow_requested: bool,
     pub myself: bool
 }
 
@@ -198,7 +270,7 @@ async fn get_profile_stat(user_id: &String, target_id: &String, db: &Database) -
     let is_following = match user_id == target_id {
         true => false,
         false => {
-            let collection = db.collection::<AccountFollow>("follow");
+            let collection = db.collection::<AccountFollow>("account_follow");
             let result = collection.count_documents(doc!{
                 "followed_by": user_id,
                 "user_id": target_id
@@ -264,12 +336,28 @@ async fn get_profile_stat(user_id: &String, target_id: &String, db: &Database) -
     let count = result.unwrap();
     let is_blocked = count > 0;
 
+    //Check if follow request is pending
+    let collection = db.collection::<AccountFollowRequest>("account_follow_request");
+    let result = collection.count_documents(doc!{
+        "user_id": target_id,
+        "requested_by": user_id
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let count = result.unwrap();
+    let is_follow_requested = count > 0;
+
     Ok(ProfileStat {
         is_friend,
         is_following,
         is_follower,
         is_liked,
         is_blocked,
+        is_follow_requested,
         myself: user_id == target_id
     })
 }
\ No newline at end of file
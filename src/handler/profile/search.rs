@@ -281,6 +281,7 @@ pub async fn task(req: HttpRequest, query: web::Query<SearchQuery>) -> Result<Ht
             "first_name": &profile.first_name,
             "last_name": &profile.last_name,
             "profile_picture": profile_picture,
+            "profile_verified": profile.profile_verified,
         }));
     }
 
@@ -3,6 +3,7 @@ use futures::StreamExt;
 use serde_json::json;
 use uuid::Uuid;
 use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::page_access;
 use crate::utils::response::Response;
 use serde::{ Serialize, Deserialize };
 use actix_web::{web, Error, HttpResponse, HttpRequest};
@@ -108,6 +109,8 @@ pub async fn task(
                 question: poll.question.clone(),
                 options: poll.options.clone(),
                 r#type: poll.r#type.clone(),
+                closes_at: None,
+                anonymous: false,
             }
         ).await;
 
@@ -129,11 +132,15 @@ pub async fn task(
         videos: form_data.videos.clone(),
         audio: form_data.audio.clone(),
         poll: poll_id,
+        shared_post: None,
         visibility: form_data.visibility.clone(),
         is_nsfw: form_data.is_nsfw.clone(),
         content_warning: form_data.content_warning.clone(),
+        publish_state: Post::PostPublishState::Published,
+        publish_at: None,
         modified_at: now,
         created_at: now,
+        edited_at: None,
         deleted_at: None,
         suspended_at: None,
         suspended_by: None,
@@ -287,38 +294,25 @@ pub async fn task(
     )
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct PageStruct {
-  uuid: String,
-  owner: String,
-  admins: Vec<String>,
-}
-
 async fn check_page_authority(
     db: &Database,
     session: &mut ClientSession,
     page_id: &str,
     user_id: &str
 ) -> Result<(), HttpResponse> {
-    let collection = db.collection::<PageStruct>("page");
-    let result = collection.find_one(
-        doc!{"uuid": page_id},
-    ).await;
-
-    if let Err(error) = result {
-        log::error!("{:?}", error);
-        session.abort_transaction().await.ok().unwrap();
-        return Err(Response::internal_server_error(&error.to_string()));
-    }
-
-    let option = result.unwrap();
-    if let None = option {
-        session.abort_transaction().await.ok().unwrap();
-        return Err(Response::not_found("page not found"));
-    }
+    let page = match page_access::find(db, page_id).await {
+        Ok(Some(page)) => page,
+        Ok(None) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Err(Response::not_found("page not found"));
+        },
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Err(error);
+        }
+    };
 
-    let page = option.unwrap();
-    if page.owner != user_id && !page.admins.contains(&user_id.to_string()) {
+    if !page.is_manager(user_id) {
         session.abort_transaction().await.ok().unwrap();
         return Err(Response::forbidden(
             "You don't have permission to post on this page"
@@ -0,0 +1,83 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::content_filter;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Account::{AccountContentPreferences, NsfwPreference};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    nsfw: Option<NsfwPreference>,
+    filtered_keywords: Option<Vec<String>>,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let db = MongoDB.connect();
+
+    let current = match content_filter::get_preferences(&db, &user_id).await {
+        Ok(preferences) => preferences,
+        Err(error) => return Ok(error),
+    };
+
+    let nsfw = req_body.nsfw.clone().unwrap_or(current.nsfw.clone());
+    if nsfw != NsfwPreference::Hide && nsfw != current.nsfw {
+        let minor = match content_filter::is_minor(&db, &user_id).await {
+            Ok(minor) => minor,
+            Err(error) => return Ok(error),
+        };
+
+        if minor {
+            return Ok(Response::forbidden(&format!(
+                "NSFW content is hidden for users under {}",
+                content_filter::ADULT_AGE
+            )));
+        }
+    }
+
+    let filtered_keywords = match &req_body.filtered_keywords {
+        Some(keywords) => match content_filter::normalize_keywords(keywords) {
+            Ok(keywords) => keywords,
+            Err(error) => return Ok(Response::bad_request(&error)),
+        },
+        None => current.filtered_keywords,
+    };
+
+    let preferences = AccountContentPreferences {
+        uuid: user_id.clone(),
+        nsfw,
+        filtered_keywords,
+        modified_at: Utc::now().timestamp_millis(),
+    };
+
+    let collection = db.collection::
+    <AccountContentPreferences>("account_content_preference");
+    let result = collection.replace_one(
+        doc!{"uuid": &user_id},
+        &preferences,
+    ).upsert(true).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!(preferences))
+    )
+}
@@ -0,0 +1,203 @@
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::privacy;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use mongodb::{ClientSession, Database};
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Account::{
+    AccountFollow,
+    AccountFollowRequest,
+    AccountPrivacySettings,
+    AccountSocial,
+    PrivacyAudience,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    private_account: Option<bool>,
+    last_seen_visibility: Option<PrivacyAudience>,
+    online_status_visibility: Option<PrivacyAudience>,
+    who_can_message: Option<PrivacyAudience>,
+    who_can_add_to_group: Option<PrivacyAudience>,
+    who_can_mention: Option<PrivacyAudience>,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let current = match privacy::get_settings(&db, &user_id).await {
+        Ok(settings) => settings,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        },
+    };
+
+    let settings = AccountPrivacySettings {
+        uuid: user_id.clone(),
+        private_account: req_body.private_account
+            .unwrap_or(current.private_account),
+        last_seen_visibility: req_body.last_seen_visibility.clone()
+            .unwrap_or(current.last_seen_visibility.clone()),
+        online_status_visibility: req_body.online_status_visibility.clone()
+            .unwrap_or(current.online_status_visibility.clone()),
+        who_can_message: req_body.who_can_message.clone()
+            .unwrap_or(current.who_can_message.clone()),
+        who_can_add_to_group: req_body.who_can_add_to_group.clone()
+            .unwrap_or(current.who_can_add_to_group.clone()),
+        who_can_mention: req_body.who_can_mention.clone()
+            .unwrap_or(current.who_can_mention.clone()),
+        modified_at: Utc::now().timestamp_millis(),
+    };
+
+    let collection = db.collection::
+    <AccountPrivacySettings>("account_privacy_setting");
+    let result = collection.replace_one(
+        doc!{"uuid": &user_id},
+        &settings,
+    ).upsert(true).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    // Going public approves everyone who is still waiting
+    if current.private_account && !settings.private_account {
+        if let Err(error) = approve_pending_requests(
+            &db,
+            &mut session,
+            &user_id
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!(settings))
+    )
+}
+
+async fn approve_pending_requests(
+    db: &Database,
+    session: &mut ClientSession,
+    user_id: &str
+) -> Result<(), HttpResponse> {
+    let collection = db.collection::
+    <AccountFollowRequest>("account_follow_request");
+    let result = collection.find(doc!{"user_id": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut requests = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        requests.push(result.unwrap());
+    }
+
+    if requests.len() == 0 {
+        return Ok(());
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let follows: Vec<AccountFollow> = requests.iter().map(|request| {
+        AccountFollow {
+            user_id: user_id.to_string(),
+            followed_by: request.requested_by.clone(),
+            followed_at: now,
+        }
+    }).collect();
+
+    let follow_collection = db.collection::<AccountFollow>("account_follow");
+    let result = follow_collection.insert_many(&follows)
+    .session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let result = collection.delete_many(doc!{"user_id": user_id})
+    .session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let social_collection = db.collection::<AccountSocial>("account_social");
+    let result = social_collection.update_one(
+        doc!{"uuid": user_id},
+        doc!{
+            "$inc": {"follower_count": requests.len() as i64},
+            "$set": {"modified_at": now}
+        },
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let followers: Vec<String> = requests.iter()
+        .map(|request| request.requested_by.clone())
+        .collect();
+
+    let result = social_collection.update_many(
+        doc!{"uuid": {"$in": followers}},
+        doc!{
+            "$inc": {"following_count": 1},
+            "$set": {"modified_at": now}
+        },
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(())
+}
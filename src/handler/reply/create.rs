@@ -3,13 +3,23 @@ use chrono::Utc;
 use serde_json::json;
 use mongodb::Database;
 use mongodb::bson::doc;
+use crate::utils::{comment_rank, mention, notification};
+use crate::utils::mention::MentionedIn;
+use crate::utils::visibility::Viewer;
 use crate::BuiltIns::mongo::MongoDB;
 // use crate::Integrations::Firebase;
 use serde::{ Serialize, Deserialize };
 use crate::utils::response::Response;
 use actix_web::{web, Error, HttpResponse, HttpRequest};
 use crate::middleware::auth::{require_access, AccessRequirement};
-use crate::model::{Comment, AudioStruct, Mention, Reply, Account::AccountRole};
+use crate::Handler::Comment::Create::get_visible_post;
+use crate::model::{
+    Comment,
+    AudioStruct,
+    Mention,
+    Reply,
+    Notification::NotificationType,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ReqBody {
@@ -26,7 +36,7 @@ pub async fn task(
 ) -> Result<HttpResponse, Error> {
     let user = require_access(
         &req,
-        AccessRequirement::Role(AccountRole::Administrator)
+        AccessRequirement::AnyToken
     )?;
 
     let user_id = user.user_id;
@@ -43,19 +53,55 @@ pub async fn task(
         return Ok(Response::internal_server_error(&error.to_string()));
     }
 
+    // Anyone who can read the post can reply to its comments
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let comment_core = match get_visible_comment(
+        &db,
+        &mut viewer,
+        &form_data.comment_id
+    ).await {
+        Ok(comment_core) => comment_core,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    //Validate the mentions and resolve the written @usernames
+    let mentions = match mention::resolve(
+        &db,
+        &user_id,
+        &form_data.text,
+        &form_data.mentions,
+        &Vec::new()
+    ).await {
+        Ok(mentions) => mentions,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
     let reply_id = Uuid::new_v4().to_string();
     let now = Utc::now().timestamp_millis();
 
     // insert comment core
     let collection = db.collection::<Reply::ReplyCore>("reply_core");
-    let comment_core = Reply::ReplyCore {
+    let reply_core = Reply::ReplyCore {
         uuid: reply_id.clone(),
         owner: user_id.clone(),
         comment_id: form_data.comment_id.clone(),
         text: form_data.text.clone(),
         images: form_data.images.clone(),
         audio: form_data.audio.clone(),
-        mentions: form_data.mentions.clone(),
+        mentions: mentions.clone(),
         status: Reply::ReplyStatus::Active,
         created_at: now,
         deleted_at: None,
@@ -65,7 +111,8 @@ pub async fn task(
         modified_at: now,
     };
 
-    let result = collection.insert_one(comment_core).await;
+    let result = collection.insert_one(reply_core)
+    .session(&mut session).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -75,13 +122,14 @@ pub async fn task(
 
     // insert comment stat
     let collection = db.collection::<Reply::ReplyStat>("reply_stat");
-    let comment_stat = Reply::ReplyStat {
+    let reply_stat = Reply::ReplyStat {
         uuid: reply_id.clone(),
         modified_at: now,
         like_count: 0,
     };
 
-    let result = collection.insert_one(comment_stat).await;
+    let result = collection.insert_one(reply_stat)
+    .session(&mut session).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -97,7 +145,7 @@ pub async fn task(
             "$inc":{ "reply_count": 1 },
             "$set":{ "modified_at": now }
         },
-    ).await;
+    ).session(&mut session).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -108,21 +156,16 @@ pub async fn task(
     let count = result.unwrap().modified_count;
     if count == 0 {
         session.abort_transaction().await.ok().unwrap();
-        return Ok(Response::not_found("post not found"));
+        return Ok(Response::not_found("comment not found"));
     }
 
-    //Finding the post owner id
-    let post_owner = match get_comment_owner(&db, &form_data.comment_id).await {
-        Ok(post_owner) => post_owner,
-        Err(error) => {
-            log::error!("{:?}", error);
-            session.abort_transaction().await.ok().unwrap();
-            return Ok(error);
-        },
-    };
-
-    if post_owner != user_id {
-        
+    if let Err(error) = comment_rank::refresh(
+        &db,
+        &mut session,
+        &form_data.comment_id
+    ).await {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(error);
     }
 
     /* DATABASE ACID COMMIT */
@@ -130,6 +173,33 @@ pub async fn task(
         log::error!("{:?}", error);
         return Ok(Response::internal_server_error(&error.to_string()));
     }
+
+    if let Err(error) = notification::send(
+        &db,
+        &comment_core.owner,
+        &user_id,
+        NotificationType::Replied,
+        Some("replied to your comment".to_string()),
+        Some(json!({
+            "post_id": &comment_core.post_id,
+            "comment_id": &form_data.comment_id,
+            "reply_id": &reply_id
+        }))
+    ).await {
+        return Ok(error);
+    }
+
+    if let Err(error) = mention::notify(
+        &db,
+        &user_id,
+        &mentions,
+        MentionedIn::Reply {
+            comment_id: &form_data.comment_id,
+            reply_id: &reply_id
+        }
+    ).await {
+        return Ok(error);
+    }
   
     Ok(
         HttpResponse::Ok()
@@ -140,35 +210,36 @@ pub async fn task(
     )
 }
 
-async fn get_comment_owner(
+/*
+    Active comment `comment_id`, if the viewer can read its post.
+    Comments they can't read are reported as missing.
+*/
+pub async fn get_visible_comment(
     db: &Database,
+    viewer: &mut Viewer,
     comment_id: &str
-) -> Result<String, HttpResponse> {
-    #[derive(Debug, Deserialize, Serialize)]
-    struct CommentCoreRead {
-        uuid: String,
-        owner: String,
-    }
-
-    let collection = db.collection::<CommentCoreRead>("comment_core");
-    let result = collection.find_one(
-        doc!{ "uuid": comment_id },
-    ).await;
+) -> Result<Comment::CommentCore, HttpResponse> {
+    let collection = db.collection::<Comment::CommentCore>("comment_core");
+    let result = collection.find_one(doc!{"uuid": comment_id}).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
         return Err(Response::internal_server_error(&error.to_string()));
     }
 
-    let option = result.unwrap();
-    if let None = option {
-        return Err(Response::not_found("post not found"));
+    let comment_core = match result.unwrap() {
+        Some(comment_core) => comment_core,
+        None => return Err(Response::not_found("comment not found")),
+    };
+
+    if comment_core.deleted_at.is_some() ||
+    !matches!(comment_core.status, Comment::CommentStatus::Active) {
+        return Err(Response::not_found("comment not found"));
     }
 
-    let post = option.unwrap();
-    let post_owner = post.owner;
+    get_visible_post(db, viewer, &comment_core.post_id).await?;
 
-    Ok(post_owner)
+    Ok(comment_core)
 }
 
 fn check_empty_fields(data: &ReqBody) -> Result<(), String> {
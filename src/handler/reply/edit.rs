@@ -0,0 +1,234 @@
+use uuid::Uuid;
+use chrono::Utc;
+use actix::Addr;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::mention;
+use crate::utils::mention::MentionedIn;
+use crate::utils::visibility::Viewer;
+use crate::BuiltIns::mongo::MongoDB;
+use serde::{ Serialize, Deserialize };
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Handler::WebSocket::lobby::Lobby;
+use crate::handler::web_socket::message::{PostMessage, WsEnvelope, WsEnvelopeType};
+use crate::middleware::auth::{require_access, AccessRequirement};
+use super::Create::get_visible_comment;
+use crate::Handler::Comment::Edit::edit_window_minutes;
+use crate::model::{
+    Mention,
+    Reply::{ReplyCore, ReplyRevision, ReplyStatus},
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    text: Option<String>,
+    images: Vec<String>,
+    mentions: Vec<Mention>,
+}
+
+/*
+    Replaces the text, images and mentions of a reply, within the same
+    window as comments. The previous version is kept in `reply_revision`
+    and the new one is pushed to everyone viewing the post.
+*/
+pub async fn task(
+    req: HttpRequest,
+    reply_id: web::Path<String>,
+    req_body: web::Json<ReqBody>,
+    srv: web::Data<Addr<Lobby>>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let reply_id = reply_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<ReplyCore>("reply_core");
+    let result = collection.find_one(doc!{
+        "uuid": &reply_id,
+        "deleted_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("reply not found"));
+    }
+
+    let reply_core = option.unwrap();
+
+    if !matches!(reply_core.status, ReplyStatus::Active) {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("reply not found"));
+    }
+
+    if reply_core.owner != user_id {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::forbidden(
+            "You are not authorized to edit this reply"
+        ));
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let window = edit_window_minutes();
+    if now > reply_core.created_at + window * 60_000 {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::forbidden(&format!(
+            "Replies can only be edited within {} minutes",
+            window
+        )));
+    }
+
+    if req_body.text.is_none() &&
+    req_body.images.len() == 0 &&
+    reply_core.audio.is_none() {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::bad_request("Nothing to reply here"));
+    }
+
+    // The comment may be gone or its post unreadable by now
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let comment_core = match get_visible_comment(
+        &db,
+        &mut viewer,
+        &reply_core.comment_id
+    ).await {
+        Ok(comment_core) => comment_core,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    // Only newly mentioned users need to allow being mentioned
+    let exempt: Vec<String> = reply_core.mentions.iter()
+    .map(|mention| mention.user_id.clone())
+    .collect();
+
+    let mentions = match mention::resolve(
+        &db,
+        &user_id,
+        &req_body.text,
+        &req_body.mentions,
+        &exempt
+    ).await {
+        Ok(mentions) => mentions,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let revision = ReplyRevision {
+        uuid: Uuid::new_v4().to_string(),
+        reply_id: reply_id.clone(),
+        edited_by: user_id.clone(),
+        text: reply_core.text.clone(),
+        images: reply_core.images.clone(),
+        mentions: reply_core.mentions.clone(),
+        revised_at: now,
+    };
+
+    let collection = db.collection::<ReplyRevision>("reply_revision");
+    let result = collection.insert_one(&revision)
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mentions_bson = match mongodb::bson::to_bson(&mentions) {
+        Ok(mentions_bson) => mentions_bson,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    };
+
+    let collection = db.collection::<ReplyCore>("reply_core");
+    let result = collection.update_one(
+        doc!{"uuid": &reply_id},
+        doc!{"$set": {
+            "text": req_body.text.clone(),
+            "images": req_body.images.clone(),
+            "mentions": mentions_bson,
+            "is_edited": true,
+            "modified_at": now,
+        }},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    // Users notified for an earlier version aren't notified again
+    if let Err(error) = mention::notify(
+        &db,
+        &user_id,
+        &mentions,
+        MentionedIn::Reply {
+            comment_id: &reply_core.comment_id,
+            reply_id: &reply_id
+        }
+    ).await {
+        return Ok(error);
+    }
+
+    let edited = json!({
+        "uuid": &reply_id,
+        "comment_id": &reply_core.comment_id,
+        "post_id": &comment_core.post_id,
+        "text": &req_body.text,
+        "images": &req_body.images,
+        "mentions": &mentions,
+        "is_edited": true,
+        "modified_at": now,
+    });
+
+    srv.do_send(PostMessage {
+        post_id: comment_core.post_id.clone(),
+        msg: WsEnvelope {
+            msg_type: WsEnvelopeType::reply_edited,
+            payload: edited.clone(),
+        },
+    });
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(edited)
+    )
+}
@@ -1,13 +1,37 @@
-use crate::utils::string;
+use futures::StreamExt;
+use crate::utils::fields::{Fields, Section};
+use std::collections::HashMap;
+use crate::utils::visibility::Viewer;
 use serde_json::{ Map, Value};
-use mongodb::{bson::doc, Database};
+use mongodb::{bson::{doc, Document}, Database};
 use crate::builtins::mongo::MongoDB;
 use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
 use serde::{ Serialize, Deserialize };
-use crate::model::{Account::AccountRole, Reply};
+use crate::model::{Account::AccountRole, Comment, Reply};
 use actix_web::{ web, Error, HttpResponse, HttpRequest};
 use crate::middleware::auth::{require_access, AccessRequirement};
 
+/* Sections of a reply, for `fields=(core(text,...),stat)` */
+pub const FIELDS: &[Section] = &[
+    Section {
+        name: "core",
+        fields: &[
+            "uuid", "owner", "comment_id", "text", "images", "audio", "status",
+            "is_edited", "mentions", "created_at", "modified_at",
+            "deleted_at", "suspended_at", "suspended_by",
+        ],
+    },
+    Section {
+        name: "stat",
+        fields: &["uuid", "like_count", "modified_at"],
+    },
+    Section {
+        name: "meta",
+        fields: &["liked"],
+    },
+];
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Query {
     uuid: Option<String>,
@@ -16,8 +40,10 @@ pub struct Query {
     fields: Option<String>,
     status: Option<Reply::ReplyStatus>,
     is_edited: Option<bool>,
-    limit: i64,
-    offset: i64,
+    cursor: Option<String>,
+    paginated: Option<bool>,
+    limit: Option<u32>,
+    offset: Option<u32>,
 }
 
 pub async fn task(
@@ -26,110 +52,161 @@ pub async fn task(
 ) -> Result<HttpResponse, Error> {
     let user = require_access(
         &req,
-        AccessRequirement::Role(AccountRole::Administrator)
+        AccessRequirement::AnyToken
     )?;
 
     let user_id = user.user_id;
+    let is_admin = user.role == AccountRole::Administrator;
 
-    let mut response = Map::new();
+    let pagination = match Pagination::new(
+        Keyset::desc("created_at", "uuid"),
+        &query.cursor,
+        query.limit.unwrap_or(20),
+        Legacy::Offset(query.offset),
+        Shape::Legacy(query.paginated)
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
 
     let db = MongoDB.connect();
-    if let Some(fields) = query.fields.clone() {
-        let fields = match string::parse_comma_separated(&fields) {
-            Ok(fields) => fields,
-            Err(error) => return Ok(Response::bad_request(&error)),
-        };
 
-        for field in fields {
-            if let Ok(value) = string::strip_prefix("core", field) {
-                let sub_fields = match value.len() {
-                    0 => vec![],
-                    _ => match string::parse_comma_separated(&value) {
-                        Ok(sub_fields) => sub_fields,
-                        Err(error) => {
-                            return Ok(Response::bad_request(&error));
-                        },
-                    },
-                };
-                
-                let reply_core = match get_reply_core(
-                    &user_id,
-                    &db,
-                    sub_fields
-                ).await {
-                    Ok(reply_core) => reply_core,
-                    Err(error) => return Ok(error),
-                };
-
-                response.insert("core".to_string(), reply_core);
-            }
-            else if let Ok(value) = string::strip_prefix("stat", field) {
-                let sub_fields = match value.len() {
-                    0 => vec![],
-                    _ => match string::parse_comma_separated(&value) {
-                        Ok(sub_fields) => sub_fields,
-                        Err(error) => {
-                            return Ok(Response::bad_request(&error));
-                        },
-                    },
-                };
-                
-                let reply_stat = match get_reply_stat(
-                    &user_id,
-                    &db,
-                    sub_fields
-                ).await {
-                    Ok(reply_stat) => reply_stat,
-                    Err(error) => return Ok(error),
-                };
-
-                response.insert("stat".to_string(), reply_stat);
-            }
-            else {
-                return Ok(Response::bad_request(
-                    &format!("Invalid field: {}", field)
-                ));
-            }
+    let fields = match Fields::parse(&query.fields, FIELDS) {
+        Ok(fields) => fields,
+        Err(error) => return Ok(error),
+    };
+
+    let mut filter = doc!{};
+
+    if let Some(uuid) = query.uuid.clone() {
+        filter.insert("uuid", uuid);
+    }
+    if let Some(owner) = query.owner.clone() {
+        filter.insert("owner", owner);
+    }
+    if let Some(comment_id) = query.comment_id.clone() {
+        filter.insert("comment_id", comment_id);
+    }
+    if let Some(status) = query.status.clone() {
+        filter.insert("status", status.to_string());
+    }
+    if let Some(is_edited) = query.is_edited.clone() {
+        filter.insert("is_edited", is_edited);
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    let collection = db.collection::<Reply::ReplyCore>("reply_core");
+    let result = pagination.find(&collection, filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut reply_cores = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
         }
+
+        reply_cores.push(result.unwrap());
     }
-    else {
-        let reply_core = match get_reply_core(
-            &user_id,
-            &db,
-            vec![]
-        ).await {
-            Ok(reply_core) => reply_core,
-            Err(error) => return Ok(error),
-        };
 
-        response.insert("core".to_string(), reply_core);
+    let page_info = pagination.finish(
+        &mut reply_cores,
+        |reply_core| (reply_core.created_at, reply_core.uuid.clone())
+    );
+
+    let mut liked = Vec::new();
+    if fields.wants("meta", "liked") {
+        let reply_ids: Vec<String> = reply_cores.iter()
+        .map(|reply_core| reply_core.uuid.clone())
+        .collect();
 
-        let reply_stat = match get_reply_stat(
-            &user_id,
-            &db,
-            vec![]
-        ).await {
-            Ok(reply_stat) => reply_stat,
+        liked = match get_liked(&db, &user_id, &reply_ids).await {
+            Ok(liked) => liked,
             Err(error) => return Ok(error),
         };
+    }
+
+    let mut replys = Vec::new();
+    let mut comment_access: HashMap<String, bool> = HashMap::new();
+    for reply_core in reply_cores {
+        // Replies are only readable by those who can read the post
+        if !is_admin {
+            let allowed = match comment_access.get(&reply_core.comment_id) {
+                Some(allowed) => *allowed,
+                None => {
+                    let allowed = match can_view_comment(
+                        &db,
+                        &mut viewer,
+                        &reply_core.comment_id
+                    ).await {
+                        Ok(allowed) => allowed,
+                        Err(error) => return Ok(error),
+                    };
+
+                    comment_access.insert(reply_core.comment_id.clone(), allowed);
+                    allowed
+                }
+            };
+
+            if !allowed {
+                continue;
+            }
+        }
+
+        let mut response = Map::new();
+
+        if fields.includes("core") {
+            response.insert(
+                "core".to_string(),
+                serde_json::to_value(&reply_core).unwrap()
+            );
+        }
+
+        if fields.includes("stat") {
+            let value = match get_reply_stat(
+                &db,
+                &reply_core.uuid,
+                &fields
+            ).await {
+                Ok(value) => value,
+                Err(error) => return Ok(error),
+            };
+
+            response.insert("stat".to_string(), value);
+        }
 
-        response.insert("stat".to_string(), reply_stat);
+        if fields.includes("meta") {
+            response.insert(
+                "meta".to_string(),
+                serde_json::json!({
+                    "liked": liked.contains(&reply_core.uuid),
+                })
+            );
+        }
+
+        replys.push(Value::Object(fields.apply(response)));
     }
 
-    Ok(
-        HttpResponse::Ok()
-        .content_type("application/json")
-        .json(Value::Object(response))
-    )
+    Ok(pagination.response("replies", replys, page_info))
 }
 
-async fn get_reply_core(
-    user_id: &str,
+async fn get_reply_stat(
     db: &Database,
-    sub_fields: Vec<&str>
-) -> Result<serde_json::Value, HttpResponse> {
-    let collection = db.collection::<Reply::ReplyCore>("reply_core");
-    let result = collection.find_one(doc!{"uuid": user_id}).await;
+    reply_id: &str,
+    fields: &Fields
+) -> Result<Value, HttpResponse> {
+    let collection = db.collection::<Document>("reply_stat");
+    let result = collection.find_one(doc!{"uuid": reply_id})
+    .projection(fields.projection("stat")).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
@@ -138,164 +215,64 @@ async fn get_reply_core(
 
     let option = result.unwrap();
     if let None = option {
-        return Err(Response::not_found("account not found"));
-    }
-
-    if sub_fields.len() > 0 {
-        let reply_core = option.unwrap();
-        let mut response = Map::new();
-        for field in sub_fields {
-            match field {
-                "owner" => response.insert(
-                    "owner".to_string(),
-                    Value::String(reply_core.owner.clone())
-                ),
-                "comment_id" => response.insert(
-                    "comment_id".to_string(),
-                    Value::String(reply_core.comment_id.clone())
-                ),
-                "text" => response.insert(
-                    "text".to_string(),
-                    if reply_core.text.is_none() {
-                        Value::Null
-                    }
-                    else {
-                        Value::String(reply_core.text.clone().unwrap())
-                    }
-                ),
-                "images" => response.insert(
-                    "images".to_string(),
-                    serde_json::to_value(
-                        reply_core.images.clone()
-                    ).unwrap()
-                ),
-                "audio" => response.insert(
-                    "audio".to_string(),
-                    if reply_core.audio.is_none() {
-                        Value::Null
-                    }
-                    else {
-                        serde_json::to_value(
-                            reply_core.audio.clone().unwrap()
-                        ).unwrap()
-                    }
-                ),
-                "status" => response.insert(
-                    "status".to_string(),
-                    Value::String(
-                        reply_core.status.clone().to_string()
-                    )
-                ),
-                "is_edited" => response.insert(
-                    "is_edited".to_string(),
-                    Value::Bool(
-                        reply_core.is_edited.clone()
-                    )
-                ),
-                "mentions" => response.insert(
-                    "mentions".to_string(),
-                    serde_json::to_value(
-                        reply_core.mentions.clone()
-                    ).unwrap()
-                ),
-                "created_at" => response.insert(
-                    "created_at".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        reply_core.created_at.clone()
-                    ))
-                ),
-                "modified_at" => response.insert(
-                    "modified_at".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        reply_core.modified_at.clone()
-                    ))
-                ),
-                "suspended_at" => {
-                    if reply_core.suspended_at.is_none() {
-                        response.insert(
-                            "suspended_at".to_string(),
-                            Value::Null
-                        )
-                    }
-                    else {
-                        response.insert(
-                            "suspended_at".to_string(),
-                            Value::Number(serde_json::Number::from(
-                                reply_core.suspended_at.clone().unwrap()
-                            ))
-                        )
-                    }
-                },
-                "suspended_by" => {
-                    if reply_core.suspended_by.is_none() {
-                        response.insert(
-                            "suspended_by".to_string(),
-                            Value::Null
-                        )
-                    }
-                    else {
-                        response.insert(
-                            "suspended_by".to_string(),
-                            Value::String(reply_core.suspended_by.clone().unwrap())
-                        )
-                    }
-                },
-                others => return Err(Response::bad_request(
-                    &format!("Invalid field: {others}"))
-                ),
-            };
-        }
-
-        return Ok(serde_json::to_value(response).unwrap());
+        return Err(Response::not_found("Reply stat not found"));
     }
 
     Ok(serde_json::to_value(option.unwrap()).unwrap())
 }
 
-async fn get_reply_stat(
-    user_id: &str,
+/* A reply is readable when the post of its comment is */
+async fn can_view_comment(
     db: &Database,
-    sub_fields: Vec<&str>
-) -> Result<serde_json::Value, HttpResponse> {
-    let collection = db.collection::
-    <Reply::ReplyStat>("reply_stat");
-    let result = collection.find_one(doc!{"uuid": user_id}).await;
+    viewer: &mut Viewer,
+    comment_id: &str
+) -> Result<bool, HttpResponse> {
+    let collection = db.collection::<Comment::CommentCore>("comment_core");
+    let result = collection.find_one(doc!{"uuid": comment_id}).await;
 
     if let Err(error) = result {
         log::error!("{:?}", error);
         return Err(Response::internal_server_error(&error.to_string()));
     }
 
-    let option = result.unwrap();
-    if let None = option {
-        return Err(Response::not_found("account not found"));
+    match result.unwrap() {
+        Some(comment_core) => viewer.can_view_post_id(
+            db,
+            &comment_core.post_id
+        ).await,
+        None => Ok(false),
     }
+}
 
-    if sub_fields.len() > 0 {
-        let reply_stat = option.unwrap();
-        let mut response = Map::new();
-        for field in sub_fields {
-            match field {
-                "like_count" => response.insert(
-                    "like_count".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        reply_stat.like_count.clone()
-                    ))
-                ),
-                "modified_at" => response.insert(
-                    "modified_at".to_string(),
-                    Value::Number(serde_json::Number::from(
-                        reply_stat.modified_at.clone()
-                    ))
-                ),
-                others => return Err(Response::bad_request(
-                    &format!("Invalid field: {others}"))
-                ),
-            };
-        }
+/* Which of `reply_ids` the viewer liked */
+async fn get_liked(
+    db: &Database,
+    user_id: &str,
+    reply_ids: &Vec<String>
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<Reply::ReplyLike>("reply_like");
+    let result = collection.find(doc!{
+        "reply_id": {"$in": reply_ids},
+        "liked_by": user_id,
+        "deleted_at": null,
+    }).await;
 
-        return Ok(serde_json::to_value(response).unwrap());
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
     }
 
-    Ok(serde_json::to_value(option.unwrap()).unwrap())
+    let mut liked = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        match result {
+            Ok(like) => liked.push(like.reply_id),
+            Err(error) => {
+                log::error!("{:?}", error);
+                return Err(Response::internal_server_error(&error.to_string()));
+            }
+        }
+    }
+
+    Ok(liked)
 }
\ No newline at end of file
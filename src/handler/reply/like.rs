@@ -0,0 +1,167 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::Database;
+use mongodb::bson::doc;
+use crate::utils::{mongo, notification};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use super::Create::get_visible_comment;
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Notification::NotificationType,
+    Reply::{ReplyCore, ReplyLike, ReplyStat, ReplyStatus},
+};
+
+/*
+    Likes the reply `reply_id`.
+    Liking an already liked reply is a no-op.
+*/
+pub async fn task(
+    req: HttpRequest,
+    reply_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let reply_id = reply_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let reply_core = match get_visible_reply(
+        &db,
+        &mut viewer,
+        &reply_id
+    ).await {
+        Ok(reply_core) => reply_core,
+        Err(error) => {
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(error);
+        }
+    };
+
+    let now = Utc::now().timestamp_millis();
+
+    // Upsert keeps repeated likes from creating duplicates
+    let collection = db.collection::<ReplyLike>("reply_like");
+    let result = collection.update_one(
+        doc!{"reply_id": &reply_id, "liked_by": &user_id},
+        doc!{
+            "$setOnInsert": {
+                "reply_id": &reply_id,
+                "liked_by": &user_id,
+                "liked_at": now
+            }
+        },
+    ).upsert(true).session(&mut session).await;
+
+    let newly_liked = match result {
+        Ok(result) => result.upserted_id.is_some(),
+        // A concurrent like of the same reply won the unique index
+        Err(error) if mongo::is_duplicate_key(&error) => {
+            session.abort_transaction().await.ok();
+            return Ok(liked_response(&reply_id));
+        },
+        Err(error) => {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    };
+
+    if newly_liked {
+        let collection = db.collection::<ReplyStat>("reply_stat");
+        let result = collection.update_one(
+            doc!{"uuid": &reply_id},
+            doc!{
+                "$inc": {"like_count": 1},
+                "$set": {"modified_at": now}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if newly_liked {
+        if let Err(error) = notification::send(
+            &db,
+            &reply_core.owner,
+            &user_id,
+            NotificationType::Like,
+            Some("liked your reply".to_string()),
+            Some(json!({
+                "comment_id": &reply_core.comment_id,
+                "reply_id": &reply_id
+            }))
+        ).await {
+            return Ok(error);
+        }
+    }
+
+    Ok(liked_response(&reply_id))
+}
+
+fn liked_response(reply_id: &str) -> HttpResponse {
+    HttpResponse::Ok()
+    .content_type("application/json")
+    .json(json!({
+        "reply_id": reply_id,
+        "liked": true
+    }))
+}
+
+/* Active reply `reply_id`, if the viewer can read the comment it's on */
+async fn get_visible_reply(
+    db: &Database,
+    viewer: &mut Viewer,
+    reply_id: &str
+) -> Result<ReplyCore, HttpResponse> {
+    let collection = db.collection::<ReplyCore>("reply_core");
+    let result = collection.find_one(doc!{"uuid": reply_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let reply_core = match result.unwrap() {
+        Some(reply_core) => reply_core,
+        None => return Err(Response::not_found("reply not found")),
+    };
+
+    if reply_core.deleted_at.is_some() ||
+    !matches!(reply_core.status, ReplyStatus::Active) {
+        return Err(Response::not_found("reply not found"));
+    }
+
+    get_visible_comment(db, viewer, &reply_core.comment_id).await?;
+
+    Ok(reply_core)
+}
@@ -0,0 +1,77 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Reply::{ReplyLike, ReplyStat};
+
+/*
+    Removes the like on the reply `reply_id`.
+    Unliking a reply that isn't liked is a no-op.
+*/
+pub async fn task(
+    req: HttpRequest,
+    reply_id: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+    let reply_id = reply_id.into_inner();
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let collection = db.collection::<ReplyLike>("reply_like");
+    let result = collection.delete_one(doc!{
+        "reply_id": &reply_id,
+        "liked_by": &user_id,
+        "deleted_at": null
+    }).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap().deleted_count > 0 {
+        let collection = db.collection::<ReplyStat>("reply_stat");
+        let result = collection.update_one(
+            doc!{"uuid": &reply_id, "like_count": {"$gt": 0}},
+            doc!{
+                "$inc": {"like_count": -1},
+                "$set": {"modified_at": Utc::now().timestamp_millis()}
+            },
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "reply_id": &reply_id,
+            "liked": false
+        }))
+    )
+}
@@ -0,0 +1,17 @@
+pub mod posts;
+pub use posts as Posts;
+
+pub mod search;
+pub use search as Search;
+
+pub mod trending;
+pub use trending as Trending;
+
+pub mod follow;
+pub use follow as Follow;
+
+pub mod unfollow;
+pub use unfollow as Unfollow;
+
+pub mod following;
+pub use following as Following;
\ No newline at end of file
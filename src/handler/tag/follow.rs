@@ -0,0 +1,58 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::{mongo, tag};
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::TagFollow;
+
+/* Follows a tag, its posts then show up in the home feed */
+pub async fn task(
+    req: HttpRequest,
+    tag_name: web::Path<String>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let tag_name = match tag::normalize(&tag_name.into_inner()) {
+        Some(tag_name) => tag_name,
+        None => return Ok(Response::bad_request("Invalid tag")),
+    };
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::<TagFollow>("tag_follow");
+    let result = collection.update_one(
+        doc!{"tag": &tag_name, "followed_by": &user_id},
+        doc!{"$setOnInsert": {
+            "tag": &tag_name,
+            "followed_by": &user_id,
+            "followed_at": Utc::now().timestamp_millis()
+        }},
+    ).upsert(true).await;
+
+    match result {
+        Ok(_) => (),
+        // A concurrent follow of the same tag inserted it first
+        Err(error) if mongo::is_duplicate_key(&error) => (),
+        Err(error) => {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        },
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "tag": &tag_name,
+            "following": true,
+        }))
+    )
+}
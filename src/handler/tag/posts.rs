@@ -0,0 +1,172 @@
+use futures::StreamExt;
+use serde_json::json;
+use std::collections::HashMap;
+use mongodb::bson::doc;
+use crate::utils::tag;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::visibility::Viewer;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::Handler::Post::Get::build_posts;
+use crate::model::Post::{PostCore, PostTag, TagFollow};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+/*
+    Tag page: the tag, its post count, whether the user follows it and
+    its posts newest first, walked with a (created_at, post_id) cursor.
+*/
+pub async fn task(
+    req: HttpRequest,
+    tag_name: web::Path<String>,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    let pagination = match Pagination::new(
+        Keyset::desc("created_at", "post_id"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20).min(50),
+        Legacy::Page(None),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let tag_name = match tag::normalize(&tag_name.into_inner()) {
+        Some(tag_name) => tag_name,
+        None => return Ok(Response::bad_request("Invalid tag")),
+    };
+
+    let db = MongoDB.connect();
+
+    let mut viewer = match Viewer::load(&db, &user_id).await {
+        Ok(viewer) => viewer,
+        Err(error) => return Ok(error),
+    };
+
+    let filter = doc!{
+        "tag": &tag_name,
+        "created_at": {"$ne": null}
+    };
+
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = pagination.find(&collection, filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut post_tags = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        post_tags.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut post_tags,
+        |post_tag| (post_tag.created_at.unwrap_or(0), post_tag.post_id.clone())
+    );
+
+    let post_ids: Vec<String> = post_tags.iter()
+    .map(|post_tag| post_tag.post_id.clone())
+    .collect();
+
+    let collection = db.collection::<PostCore>("post_core");
+    let result = collection.find(doc!{
+        "uuid": {"$in": &post_ids},
+        "deleted_at": null,
+        "suspended_at": null
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut post_cores = HashMap::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let post_core = result.unwrap();
+        post_cores.insert(post_core.uuid.clone(), post_core);
+    }
+
+    let mut visible = Vec::new();
+    for post_id in post_ids.iter() {
+        let post_core = match post_cores.remove(post_id) {
+            Some(post_core) => post_core,
+            None => continue,
+        };
+
+        match viewer.can_view_post(&db, &post_core).await {
+            Ok(true) => visible.push(post_core),
+            Ok(false) => continue,
+            Err(error) => return Ok(error),
+        }
+    }
+
+    let posts = match build_posts(&db, &mut viewer, &visible).await {
+        Ok(posts) => posts,
+        Err(error) => return Ok(error),
+    };
+
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = collection.count_documents(doc!{"tag": &tag_name}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let post_count = result.unwrap();
+
+    let collection = db.collection::<TagFollow>("tag_follow");
+    let result = collection.count_documents(doc!{
+        "tag": &tag_name,
+        "followed_by": &user_id
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let following = result.unwrap() > 0;
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "tag": &tag_name,
+            "post_count": post_count,
+            "following": following,
+            "posts": posts,
+            "next_cursor": page_info.next_cursor,
+            "has_more": page_info.has_more,
+        }))
+    )
+}
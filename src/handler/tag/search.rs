@@ -0,0 +1,88 @@
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::bson::{doc, Bson, Document};
+use crate::utils::tag;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostTag;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    q: String,
+    limit: Option<u32>,
+}
+
+/* Tag autocomplete, tags starting with `q` ordered by how often used */
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let limit = req_query.limit.unwrap_or(10).clamp(1, 50) as i64;
+
+    // Normalized prefixes only hold letters, digits and '_'
+    let prefix = match tag::normalize_prefix(&req_query.q) {
+        Some(prefix) => prefix,
+        None => return Ok(Response::bad_request("Invalid tag")),
+    };
+
+    let db = MongoDB.connect();
+
+    let pipeline = vec![
+        doc!{"$match": {
+            "tag": {"$regex": format!("^{}", prefix)}
+        }},
+        doc!{"$group": {
+            "_id": "$tag",
+            "post_count": {"$sum": 1}
+        }},
+        doc!{"$sort": {"post_count": -1, "_id": 1}},
+        doc!{"$limit": limit},
+    ];
+
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = collection.aggregate(pipeline).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut tags = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let document = result.unwrap();
+        tags.push(json!({
+            "tag": document.get_str("_id").unwrap_or_default(),
+            "post_count": get_count(&document, "post_count"),
+        }));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(tags)
+    )
+}
+
+/* `$sum` yields Int32 until it overflows into Int64 */
+pub fn get_count(document: &Document, key: &str) -> i64 {
+    match document.get(key) {
+        Some(Bson::Int32(value)) => *value as i64,
+        Some(Bson::Int64(value)) => *value,
+        Some(Bson::Double(value)) => *value as i64,
+        _ => 0,
+    }
+}
@@ -0,0 +1,119 @@
+use std::env;
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Post::PostTag;
+use super::Search::get_count;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    limit: Option<u32>,
+}
+
+/*
+    Trending tags by velocity: the hourly rate of new posts in the recent
+    window against the rate in the baseline window just before it.
+    Both windows slide with the current time. The baseline is smoothed
+    by one post so brand new tags don't divide by zero.
+*/
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let limit = req_query.limit.unwrap_or(10).clamp(1, 50) as usize;
+
+    let read = |key: &str, default: i64| env::var(key).ok()
+    .and_then(|value| value.parse::<i64>().ok())
+    .filter(|value| *value > 0)
+    .unwrap_or(default);
+
+    let window_hours = read("TRENDING_WINDOW_HOURS", 6);
+    let baseline_hours = read("TRENDING_BASELINE_HOURS", 48);
+    let min_posts = read("TRENDING_MIN_POSTS", 3);
+
+    let now = Utc::now().timestamp_millis();
+    let window_start = now - window_hours * 3_600_000;
+    let baseline_start = window_start - baseline_hours * 3_600_000;
+
+    let pipeline = vec![
+        doc!{"$match": {
+            "created_at": {"$gte": baseline_start, "$lte": now}
+        }},
+        doc!{"$group": {
+            "_id": "$tag",
+            "recent": {"$sum": {
+                "$cond": [{"$gte": ["$created_at", window_start]}, 1, 0]
+            }},
+            "previous": {"$sum": {
+                "$cond": [{"$lt": ["$created_at", window_start]}, 1, 0]
+            }},
+        }},
+        doc!{"$match": {"recent": {"$gte": min_posts}}},
+    ];
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::<PostTag>("post_tag");
+    let result = collection.aggregate(pipeline).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut trending = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let document = result.unwrap();
+        let recent = get_count(&document, "recent");
+        let previous = get_count(&document, "previous");
+
+        let recent_rate = recent as f64 / window_hours as f64;
+        let previous_rate = (previous + 1) as f64 / baseline_hours as f64;
+
+        trending.push((
+            document.get_str("_id").unwrap_or_default().to_string(),
+            recent,
+            previous,
+            recent_rate / previous_rate,
+        ));
+    }
+
+    trending.sort_by(|a, b| b.3.total_cmp(&a.3).then(b.1.cmp(&a.1)));
+    trending.truncate(limit);
+
+    let trending: Vec<serde_json::Value> = trending.into_iter()
+    .map(|(tag, recent, previous, velocity)| json!({
+        "tag": tag,
+        "recent_count": recent,
+        "previous_count": previous,
+        "velocity": velocity,
+    }))
+    .collect();
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "window_hours": window_hours,
+            "baseline_hours": baseline_hours,
+            "tags": trending,
+        }))
+    )
+}
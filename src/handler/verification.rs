@@ -0,0 +1,11 @@
+pub mod submit;
+pub use submit as Submit;
+
+pub mod status;
+pub use status as Status;
+
+pub mod queue;
+pub use queue as Queue;
+
+pub mod review;
+pub use review as Review;
\ No newline at end of file
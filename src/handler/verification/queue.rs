@@ -0,0 +1,120 @@
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use crate::utils::pagination::{Keyset, Legacy, Pagination, Shape};
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::Account::{
+    AccountCore,
+    AccountProfile,
+    AccountRole,
+    ProfileVerificationRequest,
+    VerificationStatus,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqQuery {
+    status: Option<VerificationStatus>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    page: Option<u32>,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_query: web::Query<ReqQuery>
+) -> Result<HttpResponse, Error> {
+    let _ = require_access(
+        &req,
+        AccessRequirement::Role(AccountRole::Administrator)
+    )?;
+
+    let status = req_query.status.clone()
+        .unwrap_or(VerificationStatus::Pending);
+
+    // Oldest requests are reviewed first
+    let pagination = match Pagination::new(
+        Keyset::asc("created_at", "uuid"),
+        &req_query.cursor,
+        req_query.limit.unwrap_or(20).min(50),
+        Legacy::Page(req_query.page.map(|page| page.max(1))),
+        Shape::Paged
+    ) {
+        Ok(pagination) => pagination,
+        Err(error) => return Ok(error),
+    };
+
+    let db = MongoDB.connect();
+
+    let collection = db.collection::
+    <ProfileVerificationRequest>("profile_verification_request");
+    let result = pagination.find(
+        &collection,
+        doc!{"status": status.to_string()}
+    ).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut verification_requests = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        verification_requests.push(result.unwrap());
+    }
+
+    let page_info = pagination.finish(
+        &mut verification_requests,
+        |request| (request.created_at, request.uuid.clone())
+    );
+
+    let core_collection = db.collection::<AccountCore>("account_core");
+    let profile_collection = db.collection::<AccountProfile>("account_profile");
+
+    let mut requests = Vec::new();
+    for request in verification_requests {
+
+        let result = core_collection.find_one(
+            doc!{"uuid": &request.user_id}
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let username = result.unwrap().map(|core| core.username);
+
+        let result = profile_collection.find_one(
+            doc!{"uuid": &request.user_id}
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+
+        let name = result.unwrap().map(|profile| {
+            format!("{} {}", profile.first_name, profile.last_name)
+        });
+
+        requests.push(json!({
+            "request": request,
+            "account": json!({
+                "username": username,
+                "name": name,
+            }),
+        }));
+    }
+
+    Ok(pagination.response("requests", requests, page_info))
+}
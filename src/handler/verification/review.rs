@@ -0,0 +1,140 @@
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::utils::notification;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    Account::{
+        AccountProfile,
+        AccountRole,
+        ProfileVerificationRequest,
+        VerificationStatus,
+    },
+    Notification::NotificationType,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    request_id: String,
+    approve: bool,
+    note: Option<String>,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::Role(AccountRole::Administrator)
+    )?;
+
+    let user_id = user.user_id;
+
+    if !req_body.approve && req_body.note.is_none() {
+        return Ok(Response::bad_request(
+            "A note is required when rejecting a request"
+        ));
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let status = match req_body.approve {
+        true => VerificationStatus::Approved,
+        false => VerificationStatus::Rejected,
+    };
+
+    let now = Utc::now().timestamp_millis();
+    let collection = db.collection::
+    <ProfileVerificationRequest>("profile_verification_request");
+    let result = collection.find_one_and_update(
+        doc!{
+            "uuid": &req_body.request_id,
+            "status": VerificationStatus::Pending.to_string()
+        },
+        doc!{"$set": {
+            "status": status.to_string(),
+            "review_note": req_body.note.clone(),
+            "reviewed_by": &user_id,
+            "reviewed_at": now,
+        }},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("Pending verification request not found"));
+    }
+
+    let request = option.unwrap();
+
+    if req_body.approve {
+        let collection = db.collection::<AccountProfile>("account_profile");
+        let result = collection.update_one(
+            doc!{"uuid": &request.user_id},
+            doc!{"$set": {
+                "profile_verified": true,
+                "modified_at": now,
+            }},
+        ).session(&mut session).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            session.abort_transaction().await.ok().unwrap();
+            return Ok(Response::internal_server_error(&error.to_string()));
+        }
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let message = match req_body.approve {
+        true => "Your profile verification request was approved",
+        false => "Your profile verification request was rejected",
+    };
+
+    if let Err(error) = notification::send(
+        &db,
+        &request.user_id,
+        &user_id,
+        NotificationType::SystemAlert,
+        Some(message.to_string()),
+        Some(json!({
+            "verification_request_id": &request.uuid,
+            "status": &status,
+            "note": &req_body.note,
+        }))
+    ).await {
+        return Ok(error);
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "uuid": &request.uuid,
+            "user_id": &request.user_id,
+            "status": &status,
+            "review_note": req_body.note.clone(),
+            "reviewed_at": now,
+        }))
+    )
+}
@@ -0,0 +1,184 @@
+use uuid::Uuid;
+use chrono::Utc;
+use serde_json::json;
+use mongodb::bson::doc;
+use crate::BuiltIns::mongo::MongoDB;
+use crate::utils::response::Response;
+use serde::{ Serialize, Deserialize };
+use actix_web::{web, Error, HttpResponse, HttpRequest};
+use crate::Middleware::Auth::{require_access, AccessRequirement};
+use crate::model::{
+    AssetUsedAt,
+    ImageStruct,
+    Account::{
+        AccountProfile,
+        ProfileVerificationRequest,
+        VerificationStatus,
+    },
+};
+
+const MAX_DOCUMENTS: usize = 5;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ReqBody {
+    full_name: String,
+    category: String,
+    documents: Vec<String>,
+    note: Option<String>,
+}
+
+pub async fn task(
+    req: HttpRequest,
+    req_body: web::Json<ReqBody>
+) -> Result<HttpResponse, Error> {
+    let user = require_access(
+        &req,
+        AccessRequirement::AnyToken
+    )?;
+
+    let user_id = user.user_id;
+
+    if let Err(res) = check_empty_fields(&req_body) {
+        return Ok(Response::bad_request(&res));
+    }
+
+    /* DATABASE ACID SESSION INIT */
+    let (db, mut session) = MongoDB.connect_acid().await;
+    if let Err(error) = session.start_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    // Already verified accounts have nothing to request
+    let collection = db.collection::<AccountProfile>("account_profile");
+    let result = collection.find_one(doc!{"uuid": &user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let option = result.unwrap();
+    if let None = option {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::not_found("user not found"));
+    }
+
+    if option.unwrap().profile_verified {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::conflict("Profile is already verified"));
+    }
+
+    // Only one request can be under review at a time
+    let collection = db.collection::
+    <ProfileVerificationRequest>("profile_verification_request");
+    let result = collection.count_documents(doc!{
+        "user_id": &user_id,
+        "status": VerificationStatus::Pending.to_string()
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap() > 0 {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::conflict(
+            "A verification request is already under review"
+        ));
+    }
+
+    // Documents must be private verification images the user uploaded
+    let image_collection = db.collection::<ImageStruct>("image");
+    let result = image_collection.count_documents(doc!{
+        "uuid": {"$in": &req_body.documents},
+        "used_at": AssetUsedAt::VerificationDocument.to_string(),
+        "uploaded_by": &user_id,
+        "deleted": false
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    if result.unwrap() != req_body.documents.len() as u64 {
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::bad_request(
+            "Documents must be verification documents uploaded by you"
+        ));
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let request = ProfileVerificationRequest {
+        uuid: Uuid::new_v4().to_string(),
+        user_id: user_id.clone(),
+        full_name: req_body.full_name.trim().to_string(),
+        category: req_body.category.trim().to_string(),
+        documents: req_body.documents.clone(),
+        note: req_body.note.clone(),
+        status: VerificationStatus::Pending,
+        review_note: None,
+        reviewed_by: None,
+        reviewed_at: None,
+        created_at: now,
+    };
+
+    let result = collection.insert_one(&request)
+    .session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    let result = image_collection.update_many(
+        doc!{"uuid": {"$in": &req_body.documents}},
+        doc!{"$set": {"temporary": false}},
+    ).session(&mut session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        session.abort_transaction().await.ok().unwrap();
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    /* DATABASE ACID COMMIT */
+    if let Err(error) = session.commit_transaction().await {
+        log::error!("{:?}", error);
+        return Ok(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(json!({
+            "uuid": &request.uuid,
+            "status": &request.status,
+            "created_at": &request.created_at,
+        }))
+    )
+}
+
+fn check_empty_fields(data: &ReqBody) -> Result<(), String> {
+    if data.full_name.trim().len() == 0 {
+        Err("Full name is required".to_string())
+    }
+    else if data.category.trim().len() == 0 {
+        Err("Category is required".to_string())
+    }
+    else if data.documents.len() == 0 {
+        Err("At least one document is required".to_string())
+    }
+    else if data.documents.len() > MAX_DOCUMENTS {
+        Err(format!("At most {} documents are allowed", MAX_DOCUMENTS))
+    }
+    else {
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ use futures::StreamExt;
 use actix_web_actors::ws;
 use super::WsHandler::WsConn;
 use serde::{Deserialize, Serialize};
+use crate::utils::privacy;
 use crate::utils::response::Response;
 use crate::builtins::mongo::MongoDB;
 use crate::Handler::WebSocket::lobby::Lobby;
@@ -53,9 +54,25 @@ pub async fn task(
         group_ids.push(conversation.conversation_id);
     }
 
+    // Presence is only pushed live to those allowed to see it
+    let settings = match privacy::get_settings(&db, &user_id).await {
+        Ok(settings) => settings,
+        Err(error) => return Ok(error),
+    };
+
+    let presence_audience = match privacy::audience_members(
+        &db,
+        &user_id,
+        &settings.online_status_visibility
+    ).await {
+        Ok(presence_audience) => presence_audience,
+        Err(error) => return Ok(error),
+    };
+
     let ws = WsConn::new(
         &user_id,
         group_ids,
+        presence_audience,
         srv.get_ref().clone()
     );
 
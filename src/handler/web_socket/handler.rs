@@ -4,7 +4,13 @@ use chrono::Utc;
 use super::Lobby::Lobby;
 use mongodb::bson::doc;
 use actix_web_actors::ws;
+use std::collections::HashSet;
 use std::time::{Duration, Instant};
+use futures::StreamExt;
+use futures::channel::mpsc;
+use crate::utils::{link_preview, mention, privacy};
+use crate::utils::mention::MentionedIn;
+use crate::utils::visibility::Viewer;
 use crate::{builtins::mongo::MongoDB, model::conversation::MessageRead};
 use serde::{Deserialize, Serialize};
 use crate::handler::web_socket::message::{WsEnvelope, WsEnvelopeType};
@@ -15,7 +21,9 @@ use super::WsMessage::{
     Disconnect,
     WsMessage,
     DirectMessage,
-    RoomSignalMessage
+    RoomSignalMessage,
+    ViewPost,
+    LeavePost
 };
 use serde_json::Value;
 
@@ -94,6 +102,20 @@ struct TypingPayload {
     name: String,
 }
 
+#[derive(Deserialize)]
+struct PostViewPayload {
+    post_id: String,
+}
+
+/* Work done once a message is delivered and saved: previews and mentions */
+struct FollowUp {
+    user_id: String,
+    room_id: String,
+    message_id: String,
+    text: Option<String>,
+    mentions: Option<Vec<Mention>>,
+}
+
 #[derive(Deserialize, Clone)]
 struct MessageSeenPayload {
     conversation_id: String,
@@ -105,15 +127,28 @@ pub struct WsConn {
     lobby_addr: Addr<Lobby>,
     hb: Instant,
     user_id: String,
+    presence_audience: Option<HashSet<String>>,
+    follow_ups: mpsc::UnboundedSender<FollowUp>,
 }
 
 impl WsConn {
-    pub fn new(user_id: &str, rooms: Vec<String>, lobby_addr: Addr<Lobby>) -> Self {
+    pub fn new(
+        user_id: &str,
+        rooms: Vec<String>,
+        presence_audience: Option<HashSet<String>>,
+        lobby_addr: Addr<Lobby>
+    ) -> Self {
+        // Ends with the connection, once the sender is dropped
+        let (follow_ups, queue) = mpsc::unbounded();
+        actix::spawn(process_follow_ups(queue, lobby_addr.clone()));
+
         Self {
             user_id: user_id.to_string(),
             rooms,
             lobby_addr,
             hb: Instant::now(),
+            presence_audience,
+            follow_ups,
         }
     }
 }
@@ -128,7 +163,8 @@ impl Actor for WsConn {
         self.lobby_addr.send(Connect {
             addr: addr.recipient(),
             rooms: self.rooms.clone(),
-            user_id: self.user_id.clone()
+            user_id: self.user_id.clone(),
+            presence_audience: self.presence_audience.clone(),
         })
         .into_actor(self)
         .then(|res, _act, ctx| {
@@ -144,7 +180,8 @@ impl Actor for WsConn {
     fn stopping(&mut self, _ctx: &mut Self::Context) -> Running {
         self.lobby_addr.do_send(Disconnect {
             user_id: self.user_id.clone(),
-            rooms: self.rooms.clone()
+            rooms: self.rooms.clone(),
+            presence_audience: self.presence_audience.clone(),
         });
 
         Running::Stop
@@ -158,7 +195,8 @@ impl WsConn {
                 println!("Disconnecting due to failed heartbeat: {:?}", act.user_id);
                 act.lobby_addr.do_send(Disconnect {
                 user_id: act.user_id.clone(),
-                rooms: act.rooms.clone()
+                rooms: act.rooms.clone(),
+                presence_audience: act.presence_audience.clone(),
                 });
 
                 ctx.stop();
@@ -169,7 +207,11 @@ impl WsConn {
         });
     }
 
-   fn handle_text_message(&mut self, raw_str: String) {
+   fn handle_text_message(
+        &mut self,
+        raw_str: String,
+        ctx: &mut ws::WebsocketContext<Self>
+    ) {
         // Single parse point — no splitting, no prefix matching
         let envelope: Result<WsEnvelope, _> = serde_json::from_str(&raw_str);
 
@@ -181,7 +223,7 @@ impl WsConn {
         let envelope = envelope.unwrap();
 
         match envelope.msg_type {
-            WsEnvelopeType::text => self.handle_text(envelope.payload),
+            WsEnvelopeType::text => self.handle_text(envelope.payload, ctx),
             WsEnvelopeType::typing => self.handle_typing(envelope.payload),
             WsEnvelopeType::message_seen => self.handle_message_seen(
                 envelope.payload
@@ -189,12 +231,27 @@ impl WsConn {
             WsEnvelopeType::call_signal => self.handle_call_signal(
                 envelope.payload
             ),
+            WsEnvelopeType::view_post => self.handle_view_post(
+                envelope.payload
+            ),
+            WsEnvelopeType::leave_post => self.handle_leave_post(
+                envelope.payload
+            ),
             _   => log::warn!("Unknown message prefix: {}", envelope.msg_type),
         }
     }
 
 
-    fn handle_text(&mut self, payload: Value) {
+    /*
+        Messages of a connection are checked, broadcast and saved one at a
+        time so they reach the room in the order they were sent. Previews
+        and mention notifications follow through the connection's queue.
+    */
+    fn handle_text(
+        &mut self,
+        payload: Value,
+        ctx: &mut ws::WebsocketContext<Self>
+    ) {
         let incoming_text: Result<SocketIncomingTextModel, _> = serde_json::from_value(payload);
 
         if let Err(error) = incoming_text {
@@ -204,41 +261,103 @@ impl WsConn {
 
         let incoming_text = incoming_text.unwrap();
 
-        let room_id = incoming_text.conversation_id.clone();
-
-        let outgoing_message = SocketOutgoingTextModel {
-            uuid:            Uuid::new_v4().to_string(),
-            owner:           self.user_id.clone(),
-            conversation_id: room_id.clone(),
-            text:            incoming_text.text.clone(),
-            emoji:           incoming_text.emoji.clone(),
-            mentions:        incoming_text.mentions.clone(),
-            images:          incoming_text.images.clone(),
-            audio:           incoming_text.audio.clone(),
-            video:           incoming_text.video.clone(),
-            reply_to:        incoming_text.reply_to.clone(),
-            seen_by:         vec![],
-            created_at:      Utc::now().timestamp_millis(),
-            r#type:          incoming_text.r#type,
-            attachment:      incoming_text.attachment.clone(),
-        };
+        let user_id = self.user_id.clone();
+        let lobby_addr = self.lobby_addr.clone();
+        let follow_ups = self.follow_ups.clone();
+
+        // Mentions are validated before the message goes out
+        ctx.wait(async move {
+            let room_id = incoming_text.conversation_id.clone();
+            if !can_send_message(&user_id, &room_id).await {
+                log::warn!("{} may not message conversation {}", user_id, room_id);
+                return;
+            }
+
+            let mentions = resolve_message_mentions(&user_id, &incoming_text).await;
+
+            let outgoing_message = SocketOutgoingTextModel {
+                uuid:            Uuid::new_v4().to_string(),
+                owner:           user_id.clone(),
+                conversation_id: room_id.clone(),
+                text:            incoming_text.text.clone(),
+                emoji:           incoming_text.emoji.clone(),
+                mentions:        mentions.clone(),
+                images:          incoming_text.images.clone(),
+                audio:           incoming_text.audio.clone(),
+                video:           incoming_text.video.clone(),
+                reply_to:        incoming_text.reply_to.clone(),
+                seen_by:         vec![],
+                created_at:      Utc::now().timestamp_millis(),
+                r#type:          incoming_text.r#type,
+                attachment:      incoming_text.attachment.clone(),
+            };
+
+            let message_id = outgoing_message.uuid.clone();
+            let text = outgoing_message.text.clone();
+
+            lobby_addr.do_send(ClientActorMessage {
+                user_id: user_id.clone(),
+                room_id: room_id.clone(),
+                msg: WsEnvelope {
+                    msg_type: WsEnvelopeType::text,
+                    payload:  serde_json::to_value(&outgoing_message).unwrap(),
+                },
+            });
+
+            save_message_in_database(outgoing_message).await;
+
+            let _ = follow_ups.unbounded_send(FollowUp {
+                user_id,
+                room_id,
+                message_id,
+                text,
+                mentions,
+            });
+        }.into_actor(self));
+    } 
 
-        let outgoing_message_clone = outgoing_message.clone();
+    // Only viewers allowed to read the post get its live updates
+    fn handle_view_post(&mut self, payload: Value) {
+        let view_post: Result<PostViewPayload, _> = serde_json::from_value(payload);
+
+        if let Err(error) = view_post {
+            log::error!("Invalid payload structure: {:?}", error);
+            return;
+        }
+
+        let view_post = view_post.unwrap();
+
+        let user_id = self.user_id.clone();
+        let lobby_addr = self.lobby_addr.clone();
         actix::spawn(async move {
-            save_message_in_database(outgoing_message_clone).await;
+            let db = MongoDB.connect();
+            let mut viewer = match Viewer::load(&db, &user_id).await {
+                Ok(viewer) => viewer,
+                Err(_) => return,
+            };
+
+            if let Ok(true) = viewer.can_view_post_id(&db, &view_post.post_id).await {
+                lobby_addr.do_send(ViewPost {
+                    user_id,
+                    post_id: view_post.post_id,
+                });
+            }
         });
+    }
 
-        
+    fn handle_leave_post(&mut self, payload: Value) {
+        let leave_post: Result<PostViewPayload, _> = serde_json::from_value(payload);
 
-        self.lobby_addr.do_send(ClientActorMessage {
+        if let Err(error) = leave_post {
+            log::error!("Invalid payload structure: {:?}", error);
+            return;
+        }
+
+        self.lobby_addr.do_send(LeavePost {
             user_id: self.user_id.clone(),
-            room_id,
-            msg: WsEnvelope {
-                msg_type: WsEnvelopeType::text,
-                payload:  serde_json::to_value(outgoing_message).unwrap(),
-            },
+            post_id: leave_post.unwrap().post_id,
         });
-    } 
+    }
 
     fn handle_typing(&mut self, payload: Value) {
         let typing: Result<TypingPayload, _> =
@@ -437,7 +556,7 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsConn {
         },
         Ok(ws::Message::Nop) => (),
         Ok(ws::Message::Text(text)) => {
-            self.handle_text_message(text.to_string());
+            self.handle_text_message(text.to_string(), ctx);
         }
         Err(error) => {
             log::error!("Error: {:?}", error); ctx.stop();
@@ -480,6 +599,177 @@ async fn add_message_seen(message_seen: MessageSeenPayload, user_id: &str) {
     }
 }
 
+/*
+    Unfurls the links and notifies the mentions of a connection's messages,
+    one message at a time so previews arrive in the order of the messages.
+*/
+async fn process_follow_ups(
+    mut queue: mpsc::UnboundedReceiver<FollowUp>,
+    lobby_addr: Addr<Lobby>
+) {
+    while let Some(follow_up) = queue.next().await {
+        let db = MongoDB.connect();
+
+        if let Some(text) = &follow_up.text {
+            let previews = link_preview::unfurl_text(&db, text).await;
+            if previews.len() > 0 {
+                lobby_addr.do_send(ClientActorMessage {
+                    user_id: follow_up.user_id.clone(),
+                    room_id: follow_up.room_id.clone(),
+                    msg: WsEnvelope {
+                        msg_type: WsEnvelopeType::link_preview,
+                        payload:  serde_json::json!({
+                            "conversation_id": &follow_up.room_id,
+                            "message_id": &follow_up.message_id,
+                            "previews": previews.iter()
+                            .map(link_preview::to_json)
+                            .collect::<Vec<Value>>(),
+                        }),
+                    },
+                });
+            }
+        }
+
+        if let Some(mentions) = &follow_up.mentions {
+            let _ = mention::notify(
+                &db,
+                &follow_up.user_id,
+                mentions,
+                MentionedIn::Message {
+                    conversation_id: &follow_up.room_id,
+                    message_id: &follow_up.message_id
+                }
+            ).await;
+        }
+    }
+}
+
+/*
+    Senders must take part in the conversation, and in a single conversation
+    the other participant's `who_can_message` setting still applies after
+    the conversation was created.
+*/
+async fn can_send_message(user_id: &str, conversation_id: &str) -> bool {
+    let db = MongoDB.connect();
+
+    let collection = db.collection::
+    <Conversation::ConversationCore>("conversation_core");
+    let result = collection.find_one(doc!{"uuid": conversation_id}).await;
+
+    let conversation = match result {
+        Ok(Some(conversation)) => conversation,
+        Ok(None) => return false,
+        Err(error) => {
+            log::error!("{:?}", error);
+            return false;
+        }
+    };
+
+    let collection = db.collection::
+    <Conversation::ConversationParticipant>("conversation_participant");
+    let result = collection.find(doc!{"conversation_id": conversation_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return false;
+    }
+
+    let mut participants = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        match result {
+            Ok(participant) => participants.push(participant.user_id),
+            Err(error) => {
+                log::error!("{:?}", error);
+                return false;
+            }
+        }
+    }
+
+    if !participants.iter().any(|participant| participant == user_id) {
+        return false;
+    }
+
+    if let Conversation::ConversationType::Group = conversation.r#type {
+        return true;
+    }
+
+    for other_user in participants.iter().filter(|id| *id != user_id) {
+        match privacy::can_message(&db, other_user, user_id).await {
+            Ok(true) => {},
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+/*
+    Mentions of a chat message: validated like any other mention and
+    limited to the participants of the conversation. Invalid mentions
+    are dropped instead of losing the message.
+*/
+async fn resolve_message_mentions(
+    user_id: &str,
+    incoming_text: &SocketIncomingTextModel
+) -> Option<Vec<Mention>> {
+    let db = MongoDB.connect();
+
+    let explicit = incoming_text.mentions.clone().unwrap_or_default();
+    let mentions = match mention::resolve(
+        &db,
+        user_id,
+        &incoming_text.text,
+        &explicit,
+        &Vec::new()
+    ).await {
+        Ok(mentions) => mentions,
+        Err(_) => {
+            log::warn!(
+                "Dropping invalid mentions of a message in {}",
+                incoming_text.conversation_id
+            );
+            Vec::new()
+        }
+    };
+
+    if mentions.len() == 0 {
+        return None;
+    }
+
+    let user_ids: Vec<String> = mentions.iter()
+    .map(|mention| mention.user_id.clone())
+    .collect();
+
+    let collection = db.collection::
+    <Conversation::ConversationParticipant>("conversation_participant");
+    let result = collection.find(doc!{
+        "conversation_id": &incoming_text.conversation_id,
+        "user_id": {"$in": &user_ids}
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return None;
+    }
+
+    let mut participants = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(Ok(participant)) = cursor.next().await {
+        participants.push(participant.user_id);
+    }
+
+    let mentions: Vec<Mention> = mentions.into_iter()
+    .filter(|mention| participants.contains(&mention.user_id))
+    .collect();
+
+    if mentions.len() == 0 {
+        return None;
+    }
+
+    Some(mentions)
+}
+
 async fn save_message_in_database(message: SocketOutgoingTextModel) {
     /* DATABASE ACID SESSION INIT */
     let (db, mut session) = MongoDB.connect_acid().await;
@@ -10,7 +10,10 @@ use super::message::{
     ClientActorMessage,
     DirectMessage,
     RoomSignalMessage,
-    AddToRoom
+    AddToRoom,
+    ViewPost,
+    LeavePost,
+    PostMessage
 };
 
 pub type Socket = Recipient<WsMessage>;
@@ -43,6 +46,7 @@ pub struct Lobby {
     pub sessions: HashMap<String, Socket>,
     pub rooms: HashMap<String, HashSet<String>>,
     pub active_calls: HashMap<String, HashSet<String>>,
+    pub post_viewers: HashMap<String, HashSet<String>>,
 }
 
 impl Default for Lobby {
@@ -51,6 +55,7 @@ impl Default for Lobby {
             sessions: HashMap::new(),
             rooms: HashMap::new(),
             active_calls: HashMap::new(),
+            post_viewers: HashMap::new(),
         }
     }
 }
@@ -108,6 +113,14 @@ impl Lobby {
     }
 }
 
+/* `None` means everyone may see the user's presence */
+fn sees_presence(audience: &Option<HashSet<String>>, user_id: &str) -> bool {
+    match audience {
+        Some(audience) => audience.contains(user_id),
+        None => true,
+    }
+}
+
 impl Actor for Lobby {
     type Context = Context<Self>;
 }
@@ -142,6 +155,11 @@ impl Handler<Disconnect> for Lobby {
                 }
             }
 
+            for viewers in self.post_viewers.values_mut() {
+                viewers.remove(&disconnect.user_id);
+            }
+            self.post_viewers.retain(|_, viewers| viewers.len() > 0);
+
             let message = WsEnvelope {
                 msg_type: WsEnvelopeType::disconnect,
                 payload: serde_json::json!({
@@ -150,7 +168,9 @@ impl Handler<Disconnect> for Lobby {
             };
 
             for user_id in autndm {
-                self.send_message(&message, &user_id);
+                if sees_presence(&disconnect.presence_audience, &user_id) {
+                    self.send_message(&message, &user_id);
+                }
             }
 
             actix::spawn(async move {
@@ -204,7 +224,9 @@ impl Handler<Connect> for Lobby {
         };
 
         for user_id in autncm {
-            self.send_message(&message, &user_id);
+            if sees_presence(&connect.presence_audience, &user_id) {
+                self.send_message(&message, &user_id);
+            }
         }
 
         self.sessions.insert(connect.user_id.clone(), connect.addr);
@@ -227,6 +249,43 @@ impl Handler<Connect> for Lobby {
 }
 
 
+impl Handler<ViewPost> for Lobby {
+    type Result = ();
+
+    fn handle(&mut self, msg: ViewPost, _ctx: &mut Self::Context) -> Self::Result {
+        self.post_viewers
+            .entry(msg.post_id)
+            .or_insert_with(HashSet::new)
+            .insert(msg.user_id);
+    }
+}
+
+impl Handler<LeavePost> for Lobby {
+    type Result = ();
+
+    fn handle(&mut self, msg: LeavePost, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(viewers) = self.post_viewers.get_mut(&msg.post_id) {
+            viewers.remove(&msg.user_id);
+
+            if viewers.len() == 0 {
+                self.post_viewers.remove(&msg.post_id);
+            }
+        }
+    }
+}
+
+impl Handler<PostMessage> for Lobby {
+    type Result = ();
+
+    fn handle(&mut self, msg: PostMessage, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(viewers) = self.post_viewers.get(&msg.post_id) {
+            for user_id in viewers {
+                self.send_message(&msg.msg, user_id);
+            }
+        }
+    }
+}
+
 impl Handler<ClientActorMessage> for Lobby {
     type Result = ();
 
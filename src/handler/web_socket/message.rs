@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use actix::prelude::{Message, Recipient};
 use serde_json::Value;
 use serde::{Deserialize, Serialize};
@@ -13,6 +14,11 @@ pub enum WsEnvelopeType {
     new_conversation,
     call_signal,
     message_seen,
+    link_preview,
+    view_post,
+    leave_post,
+    comment_edited,
+    reply_edited,
 }
 
 impl std::fmt::Display for WsEnvelopeType {
@@ -38,6 +44,7 @@ pub struct Connect {
     pub addr: Recipient<WsMessage>,
     pub rooms: Vec<String>,
     pub user_id: String,
+    pub presence_audience: Option<HashSet<String>>,
 }
 
 #[derive(Message)]
@@ -45,6 +52,7 @@ pub struct Connect {
 pub struct Disconnect {
     pub rooms: Vec<String>,
     pub user_id: String,
+    pub presence_audience: Option<HashSet<String>>,
 }
 
 #[derive(Message)]
@@ -80,4 +88,27 @@ pub struct AddToRoom {
     pub user_id: String,
     pub conversation_id: String,
     pub conversation_type: ConversationType,
+}
+
+// Starts pushing live updates of a post (comment edits, ...) to the user
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ViewPost {
+    pub user_id: String,
+    pub post_id: String,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct LeavePost {
+    pub user_id: String,
+    pub post_id: String,
+}
+
+// Sent by http handlers to everyone currently viewing the post
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct PostMessage {
+    pub post_id: String,
+    pub msg: WsEnvelope,
 }
\ No newline at end of file
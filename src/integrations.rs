@@ -5,4 +5,7 @@ pub mod smtp;
 pub use smtp as Smtp;
 
 pub mod resend;
-pub use resend as Resend;
\ No newline at end of file
+pub use resend as Resend;
+
+pub mod unfurl;
+pub use unfurl as Unfurl;
\ No newline at end of file
@@ -0,0 +1,657 @@
+/*
+    Fetches Open Graph / Twitter card metadata of links.
+
+    Every request goes to a url supplied by a user, so the fetcher is strict:
+    - only http(s) on public addresses, the resolved address is pinned so a
+      second DNS lookup can't point the request somewhere else
+    - redirects are followed by hand and every hop is checked again
+    - short timeouts and hard caps on how much is read
+*/
+
+use std::time::Duration;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use regex::Regex;
+use reqwest::{Client, Url, redirect};
+use reqwest::header::{ACCEPT, CONTENT_TYPE, LOCATION, USER_AGENT};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_REDIRECTS: usize = 3;
+
+/* Metadata lives in the head, anything past this is ignored */
+const MAX_HTML_BYTES: usize = 512 * 1024;
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+const MAX_TITLE_LENGTH: usize = 200;
+const MAX_DESCRIPTION_LENGTH: usize = 500;
+const MAX_SITE_NAME_LENGTH: usize = 100;
+
+const AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "-link-preview/1.0");
+
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+    pub image: Option<String>,
+}
+
+pub async fn fetch_metadata(url: &str) -> Result<Metadata, String> {
+    let (final_url, content_type, bytes) = fetch(
+        url,
+        "text/html,application/xhtml+xml",
+        MAX_HTML_BYTES,
+        true,
+        is_public
+    ).await?;
+
+    if !content_type.starts_with("text/html")
+    && !content_type.starts_with("application/xhtml+xml") {
+        return Err(format!("Not an html page: {}", content_type));
+    }
+
+    let html = String::from_utf8_lossy(&bytes);
+    Ok(parse_metadata(&final_url, &html))
+}
+
+pub async fn fetch_image(url: &str) -> Result<Vec<u8>, String> {
+    let (_, content_type, bytes) = fetch(
+        url,
+        "image/*",
+        MAX_IMAGE_BYTES,
+        false,
+        is_public
+    ).await?;
+
+    if !content_type.starts_with("image/") {
+        return Err(format!("Not an image: {}", content_type));
+    }
+
+    Ok(bytes)
+}
+
+/*
+    Reads at most `max_bytes` of the body. When `truncate` is false a larger
+    body is an error instead of being cut. Every hop must reach an address
+    `allow` accepts, `is_public` outside of tests.
+*/
+async fn fetch(
+    url: &str,
+    accept: &str,
+    max_bytes: usize,
+    truncate: bool,
+    allow: fn(&IpAddr) -> bool
+) -> Result<(Url, String, Vec<u8>), String> {
+    let mut url = match Url::parse(url) {
+        Ok(url) => url,
+        Err(error) => return Err(error.to_string()),
+    };
+
+    for _ in 0..=MAX_REDIRECTS {
+        let client = pinned_client(&url, allow).await?;
+        let result = client
+            .get(url.clone())
+            .header(USER_AGENT, AGENT)
+            .header(ACCEPT, accept)
+            .send().await;
+
+        let mut response = match result {
+            Ok(response) => response,
+            Err(error) => return Err(error.to_string()),
+        };
+
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok());
+
+            url = match location.map(|location| url.join(location)) {
+                Some(Ok(next)) => next,
+                _ => return Err("Invalid redirect".to_string()),
+            };
+
+            continue;
+        }
+
+        if !response.status().is_success() {
+            return Err(format!("Unexpected status {}", response.status()));
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .unwrap_or("")
+            .to_lowercase();
+
+        if let Some(length) = response.content_length() {
+            if !truncate && length as usize > max_bytes {
+                return Err("Response is too large".to_string());
+            }
+        }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(error) => return Err(error.to_string()),
+            };
+
+            if bytes.len() + chunk.len() > max_bytes {
+                if !truncate {
+                    return Err("Response is too large".to_string());
+                }
+
+                let left = max_bytes - bytes.len();
+                bytes.extend_from_slice(&chunk[..left]);
+                break;
+            }
+
+            bytes.extend_from_slice(&chunk);
+        }
+
+        return Ok((url, content_type, bytes));
+    }
+
+    Err("Too many redirects".to_string())
+}
+
+/*
+    Builds a client that can only reach the checked address of `url`.
+    Proxies are disabled, they would resolve the host on their own.
+*/
+async fn pinned_client(
+    url: &Url,
+    allow: fn(&IpAddr) -> bool
+) -> Result<Client, String> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(format!("Unsupported scheme: {}", url.scheme()));
+    }
+
+    let port = match url.port_or_known_default() {
+        Some(port) => port,
+        None => return Err("Missing port".to_string()),
+    };
+
+    let builder = Client::builder()
+    .redirect(redirect::Policy::none())
+    .timeout(TIMEOUT)
+    .connect_timeout(CONNECT_TIMEOUT)
+    .no_proxy();
+
+    let host = match url.host_str() {
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+        None => return Err("Missing host".to_string()),
+    };
+
+    /* Literal addresses are checked as they are, names get resolved first */
+    let builder = match host.parse::<IpAddr>() {
+        Ok(ip) => {
+            if !allow(&ip) {
+                return Err("Address is not public".to_string());
+            }
+
+            builder
+        },
+        Err(_) => {
+            let result = tokio::net::lookup_host((host, port)).await;
+            let addresses: Vec<SocketAddr> = match result {
+                Ok(addresses) => addresses.collect(),
+                Err(error) => return Err(error.to_string()),
+            };
+
+            if addresses.len() == 0 {
+                return Err("Host did not resolve".to_string());
+            }
+
+            /* One private record is enough to refuse, it's how rebinding works */
+            if addresses.iter().any(|address| !allow(&address.ip())) {
+                return Err("Address is not public".to_string());
+            }
+
+            builder.resolve(host, addresses[0])
+        },
+    };
+
+    match builder.build() {
+        Ok(client) => Ok(client),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/* Anything not routable on the public internet is refused */
+pub fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(&ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: &Ipv4Addr) -> bool {
+    let octets = ip.octets();
+
+    !(ip.is_private()
+    || ip.is_loopback()
+    || ip.is_link_local()
+    || ip.is_broadcast()
+    || ip.is_documentation()
+    || ip.is_unspecified()
+    || ip.is_multicast()
+    // 0.0.0.0/8
+    || octets[0] == 0
+    // 100.64.0.0/10, carrier grade NAT
+    || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+    // 192.0.0.0/24, protocol assignments
+    || (octets[0] == 192 && octets[1] == 0 && octets[2] == 0)
+    // 198.18.0.0/15, benchmarking
+    || (octets[0] == 198 && (octets[1] & 0xfe) == 18)
+    // 240.0.0.0/4, reserved
+    || octets[0] >= 240)
+}
+
+fn is_public_v6(ip: &Ipv6Addr) -> bool {
+    // Addresses carrying an IPv4 address are as public as that address
+    if let Some(ip) = embedded_v4(ip) {
+        return is_public_v4(&ip);
+    }
+
+    let segments = ip.segments();
+
+    !(ip.is_loopback()
+    || ip.is_unspecified()
+    || ip.is_multicast()
+    // fc00::/7, unique local
+    || (segments[0] & 0xfe00) == 0xfc00
+    // fe80::/10, link local
+    || (segments[0] & 0xffc0) == 0xfe80
+    // 2001:db8::/32, documentation
+    || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+    // 2001::/32, Teredo, the tunneled address can't be checked
+    || (segments[0] == 0x2001 && segments[1] == 0)
+    // 64:ff9b:1::/48, local use NAT64
+    || (segments[0] == 0x64 && segments[1] == 0xff9b && segments[2] == 1))
+}
+
+/* IPv4 address an IPv4-compatible, NAT64 or 6to4 address reaches */
+fn embedded_v4(ip: &Ipv6Addr) -> Option<Ipv4Addr> {
+    let octets = ip.octets();
+    let last = Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]);
+
+    match ip.segments() {
+        // ::a.b.c.d, IPv4-compatible
+        [0, 0, 0, 0, 0, 0, _, _] => Some(last),
+        // 64:ff9b::/96, NAT64
+        [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some(last),
+        // 2002::/16, 6to4
+        [0x2002, ..] => Some(
+            Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])
+        ),
+        _ => None,
+    }
+}
+
+fn parse_metadata(url: &Url, html: &str) -> Metadata {
+    let meta_regex = Regex::new(r"(?is)<meta\b[^>]*>").unwrap();
+    let attribute_regex = Regex::new(
+        r#"(?is)([a-z_:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#
+    ).unwrap();
+
+    let mut tags: Vec<(String, String)> = Vec::new();
+    for meta in meta_regex.find_iter(html) {
+        let mut key: Option<String> = None;
+        let mut content: Option<String> = None;
+
+        for captures in attribute_regex.captures_iter(meta.as_str()) {
+            let name = captures[1].to_lowercase();
+            let value = captures
+                .get(2)
+                .or(captures.get(3))
+                .or(captures.get(4))
+                .map(|value| value.as_str().to_string())
+                .unwrap_or_default();
+
+            match name.as_str() {
+                "property" | "name" => key = Some(value.to_lowercase()),
+                "content" => content = Some(value),
+                _ => {},
+            }
+        }
+
+        if let (Some(key), Some(content)) = (key, content) {
+            tags.push((key, content));
+        }
+    }
+
+    let find = |keys: &[&str]| -> Option<String> {
+        keys.iter().find_map(|key| {
+            tags.iter()
+            .find(|(name, content)| name == key && content.trim().len() > 0)
+            .map(|(_, content)| decode_entities(content))
+        })
+    };
+
+    let title_regex = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+    let title = find(&["og:title", "twitter:title"]).or_else(|| {
+        title_regex
+        .captures(html)
+        .map(|captures| decode_entities(&captures[1]))
+    });
+
+    let description = find(&[
+        "og:description",
+        "twitter:description",
+        "description"
+    ]);
+
+    let site_name = find(&["og:site_name"])
+    .or(url.host_str().map(|host| host.to_string()));
+
+    let image = find(&[
+        "og:image:secure_url",
+        "og:image:url",
+        "og:image",
+        "twitter:image",
+        "twitter:image:src"
+    ])
+    .and_then(|image| url.join(image.trim()).ok())
+    .map(|image| image.to_string());
+
+    Metadata {
+        title: clean(title, MAX_TITLE_LENGTH),
+        description: clean(description, MAX_DESCRIPTION_LENGTH),
+        site_name: clean(site_name, MAX_SITE_NAME_LENGTH),
+        image,
+    }
+}
+
+/* Collapses whitespace and cuts the text to `max` characters */
+fn clean(text: Option<String>, max: usize) -> Option<String> {
+    let text = text?
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join(" ");
+
+    if text.len() == 0 {
+        return None;
+    }
+
+    match text.chars().count() > max {
+        true => Some(format!("{}…", text.chars().take(max - 1).collect::<String>())),
+        false => Some(text),
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let numeric_regex = Regex::new(r"&#([xX]?)([0-9a-fA-F]+);").unwrap();
+    let text = numeric_regex.replace_all(text, |captures: &regex::Captures| {
+        let radix = if captures[1].len() > 0 { 16 } else { 10 };
+        u32::from_str_radix(&captures[2], radix)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|c| c.to_string())
+        .unwrap_or_default()
+    });
+
+    text
+    .replace("&quot;", "\"")
+    .replace("&apos;", "'")
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&nbsp;", " ")
+    .replace("&amp;", "&")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn public_addresses() {
+        for address in [
+            "8.8.8.8",
+            "2606:4700:4700::1111",
+            "::ffff:8.8.8.8",
+            "::8.8.8.8",
+            "64:ff9b::8.8.8.8",
+            "2002:808:808::1",
+        ] {
+            assert!(is_public(&ip(address)), "{} is public", address);
+        }
+    }
+
+    #[test]
+    fn private_addresses() {
+        for address in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::",
+            "::1",
+            "fc00::1",
+            "fe80::1",
+            "2001:db8::1",
+            "::ffff:127.0.0.1",
+            "::127.0.0.1",
+            "::10.0.0.1",
+            "64:ff9b::10.0.0.1",
+            "64:ff9b:1::8.8.8.8",
+            "2002:a00:1::1",
+            "2002:7f00:1::1",
+            "2001:0:4136:e378:8000:63bf:3fff:fdd2",
+        ] {
+            assert!(!is_public(&ip(address)), "{} is not public", address);
+        }
+    }
+
+    #[test]
+    fn open_graph_tags_first() {
+        let url = Url::parse("https://example.com/articles/1").unwrap();
+        let metadata = parse_metadata(&url, r#"
+            <html><head>
+            <title>Page title</title>
+            <meta name="twitter:title" content="Twitter title">
+            <meta property="og:title" content="Tom &amp; Jerry">
+            <meta content='A  short
+                description' name='description'>
+            <meta property="og:image" content="/images/cover.png">
+            </head></html>
+        "#);
+
+        assert_eq!(metadata.title.as_deref(), Some("Tom & Jerry"));
+        assert_eq!(metadata.description.as_deref(), Some("A short description"));
+        assert_eq!(metadata.site_name.as_deref(), Some("example.com"));
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://example.com/images/cover.png")
+        );
+    }
+
+    #[test]
+    fn title_tag_fallback() {
+        let url = Url::parse("https://example.com").unwrap();
+        let metadata = parse_metadata(&url, r#"
+            <title> Only &lt;a&gt; title </title>
+            <meta property="og:site_name" content="Example">
+            <meta property="og:description" content="   ">
+        "#);
+
+        assert_eq!(metadata.title.as_deref(), Some("Only <a> title"));
+        assert_eq!(metadata.description, None);
+        assert_eq!(metadata.site_name.as_deref(), Some("Example"));
+        assert_eq!(metadata.image, None);
+    }
+
+    #[test]
+    fn clean_collapses_and_cuts() {
+        assert_eq!(clean(Some(" a \n\t b ".to_string()), 10).as_deref(), Some("a b"));
+        assert_eq!(clean(Some(" \n ".to_string()), 10), None);
+        assert_eq!(clean(None, 10), None);
+        assert_eq!(clean(Some("abcdef".to_string()), 6).as_deref(), Some("abcdef"));
+        assert_eq!(clean(Some("abcdefg".to_string()), 6).as_deref(), Some("abcde…"));
+        assert_eq!(clean(Some("ééééééé".to_string()), 6).as_deref(), Some("ééééé…"));
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(decode_entities("&quot;a&quot; &apos;b&apos;"), "\"a\" 'b'");
+        assert_eq!(decode_entities("&#65;&#x42;&#X43;"), "ABC");
+        assert_eq!(decode_entities("&#xffffffff;x"), "x");
+        assert_eq!(decode_entities("&amp;lt;"), "&lt;");
+        assert_eq!(decode_entities("a&nbsp;b"), "a b");
+    }
+
+    /*
+        Answers every connection by path:
+        `/hop/N` redirects to `/hop/N-1`, `/hop/0` to `/page`,
+        `/private` redirects to a private address,
+        `/sized` and `/streamed` send 64 bytes with and without a length.
+    */
+    async fn serve() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(_) => return,
+                };
+
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        match stream.read(&mut buffer).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(read) => request.extend_from_slice(&buffer[..read]),
+                        }
+                    }
+
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split(' ').nth(1).unwrap_or("/").to_string();
+                    let body = "x".repeat(64);
+
+                    let response = match path.as_str() {
+                        "/page" => "HTTP/1.1 200 OK\r\n\
+                            Content-Type: text/html\r\n\
+                            Content-Length: 20\r\n\r\n\
+                            <title>Page</title>\n".to_string(),
+                        "/hop/0" => redirect("/page"),
+                        "/private" => redirect("http://10.0.0.1/"),
+                        "/sized" => format!(
+                            "HTTP/1.1 200 OK\r\n\
+                            Content-Type: image/png\r\n\
+                            Content-Length: {}\r\n\r\n{}",
+                            body.len(), body
+                        ),
+                        "/streamed" => format!(
+                            "HTTP/1.1 200 OK\r\n\
+                            Content-Type: image/png\r\n\
+                            Connection: close\r\n\r\n{}",
+                            body
+                        ),
+                        path => match path.strip_prefix("/hop/") {
+                            Some(hops) => {
+                                let hops: u32 = hops.parse().unwrap();
+                                redirect(&format!("/hop/{}", hops - 1))
+                            },
+                            None => "HTTP/1.1 404 Not Found\r\n\
+                                Content-Length: 0\r\n\r\n".to_string(),
+                        },
+                    };
+
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    fn redirect(location: &str) -> String {
+        format!(
+            "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n",
+            location
+        )
+    }
+
+    fn loopback(ip: &IpAddr) -> bool {
+        ip.is_loopback()
+    }
+
+    #[actix_web::test]
+    async fn follows_redirects() {
+        let base = serve().await;
+
+        let (url, content_type, bytes) = fetch(
+            &format!("{}/hop/2", base), "text/html", 1024, true, loopback
+        ).await.unwrap();
+
+        assert_eq!(url.path(), "/page");
+        assert_eq!(content_type, "text/html");
+        assert_eq!(bytes, b"<title>Page</title>\n");
+    }
+
+    #[actix_web::test]
+    async fn stops_after_max_redirects() {
+        let base = serve().await;
+
+        let result = fetch(
+            &format!("{}/hop/3", base), "text/html", 1024, true, loopback
+        ).await;
+
+        assert_eq!(result.unwrap_err(), "Too many redirects");
+    }
+
+    #[actix_web::test]
+    async fn checks_every_hop() {
+        let base = serve().await;
+
+        let result = fetch(
+            &format!("{}/private", base), "text/html", 1024, true, loopback
+        ).await;
+        assert_eq!(result.unwrap_err(), "Address is not public");
+
+        let result = fetch(
+            &format!("{}/page", base), "text/html", 1024, true, is_public
+        ).await;
+        assert_eq!(result.unwrap_err(), "Address is not public");
+    }
+
+    #[actix_web::test]
+    async fn caps_the_body() {
+        let base = serve().await;
+
+        for path in ["/sized", "/streamed"] {
+            let url = format!("{}{}", base, path);
+
+            let result = fetch(&url, "image/*", 16, false, loopback).await;
+            assert_eq!(result.unwrap_err(), "Response is too large", "{}", path);
+
+            let (_, _, bytes) = fetch(&url, "image/*", 16, true, loopback)
+            .await.unwrap();
+            assert_eq!(bytes.len(), 16, "{}", path);
+
+            let (_, _, bytes) = fetch(&url, "image/*", 64, false, loopback)
+            .await.unwrap();
+            assert_eq!(bytes.len(), 64, "{}", path);
+        }
+    }
+}
\ No newline at end of file
@@ -68,6 +68,56 @@ async fn main() -> io::Result<()> {
         });
     */
 
+    /*
+        Buffers post views and flushes them to the database periodically
+    */
+    let view_counter = web::Data::new(Utils::view_counter::ViewCounter::default());
+    let flush_counter = view_counter.clone();
+    tokio::spawn(async move {
+        use tokio::time::{self, Duration};
+        let seconds = env::var("VIEW_FLUSH_INTERVAL_SECONDS").ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(60);
+
+        let mut interval = time::interval(Duration::from_secs(seconds));
+        loop {
+            interval.tick().await;
+            BuiltIns::cron::flush_post_views(&flush_counter).await;
+        }
+    });
+
+    /* Scheduled posts are published by this loop */
+    tokio::spawn(async move {
+        use tokio::time::{self, Duration};
+        let seconds = env::var("PUBLISH_SCHEDULER_INTERVAL_SECONDS").ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(30);
+
+        let mut interval = time::interval(Duration::from_secs(seconds));
+        loop {
+            interval.tick().await;
+            BuiltIns::cron::publish_scheduled_posts().await;
+        }
+    });
+
+    /*
+        Deleted posts are purged by this loop once they can't be restored,
+        stale For You seen marks go with them
+    */
+    tokio::spawn(async move {
+        use tokio::time::{self, Duration};
+        let seconds = env::var("PURGE_SCHEDULER_INTERVAL_SECONDS").ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(3600);
+
+        let mut interval = time::interval(Duration::from_secs(seconds));
+        loop {
+            interval.tick().await;
+            BuiltIns::cron::purge_deleted_posts().await;
+            BuiltIns::cron::prune_feed_seen().await;
+        }
+    });
+
     /*
         Sqlite Database Initialization
         Remove the following code block if you are not using this feature.
@@ -75,6 +125,14 @@ async fn main() -> io::Result<()> {
     log::info!("\nExecuting Sqlite3 Prerequisites...");
     BuiltIns::sqlite::create_initial_tables().expect("Failed to initiate!\n");
 
+    log::info!("\nCreating MongoDB Indexes...");
+    BuiltIns::mongo::create_indexes().await.expect("Failed to create indexes!\n");
+
+    /* Comments written before the `top` order get their rank once */
+    tokio::spawn(async move {
+        BuiltIns::cron::rank_unranked_comments().await;
+    });
+
     let mut listenfd = ListenFd::from_env();
 
     let host = env::var("APP_HOST")
@@ -107,6 +165,7 @@ async fn main() -> io::Result<()> {
 
         App::new()
         .app_data(web::Data::new(lobby.clone()))
+        .app_data(view_counter.clone())
         .wrap_fn(|sreq, srv| {
             let app_http = env::var("APP_HTTP")
             .expect("APP_HTTP must be set on .env file");
@@ -276,9 +335,14 @@ async fn main() -> io::Result<()> {
         .configure(Routes::Video::router)
         .configure(Routes::Conversation::router)
         .configure(Routes::Profile::router)
+        .configure(Routes::Verification::router)
         .configure(Routes::Emoji::router)
         .configure(Routes::Reply::router)
         .configure(Routes::Comment::router)
+        .configure(Routes::Bookmark::router)
+        .configure(Routes::Feed::router)
+        .configure(Routes::Tag::router)
+        .configure(Routes::Poll::router)
         .configure(Routes::Post::router)
         .configure(Routes::Auth::router)
         .configure(Routes::Support::router)
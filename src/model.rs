@@ -30,6 +30,9 @@ pub use poll as Poll;
 pub mod notification;
 pub use notification as Notification;
 
+pub mod page;
+pub use page as Page;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AllowedImageType { Gif, Png, Jpeg, Webp }
 
@@ -72,7 +75,9 @@ pub enum AssetUsedAt {
     Post,
     Comment,
     Chat, 
-    VideoThumbnail
+    VideoThumbnail,
+    VerificationDocument,
+    LinkPreview
 }
 
 impl std::fmt::Display for AssetUsedAt {
@@ -90,6 +95,8 @@ impl AssetUsedAt {
             "Comment" => AssetUsedAt::Comment,
             "Chat" => AssetUsedAt::Chat,
             "VideoThumbnail" => AssetUsedAt::VideoThumbnail,
+            "VerificationDocument" => AssetUsedAt::VerificationDocument,
+            "LinkPreview" => AssetUsedAt::LinkPreview,
             _ => AssetUsedAt::ProfilePic
         }
     }
@@ -107,6 +114,9 @@ pub struct ImageStruct {
     pub original_type:  String,
     pub temporary: bool,
     pub deleted: bool,
+    // Account that uploaded the image, absent on older uploads
+    #[serde(default)]
+    pub uploaded_by: Option<String>,
     pub created_at: i64
 }
 
@@ -143,6 +153,22 @@ pub struct AttachmentStruct {
     pub extension: String,
 }
 
+/*
+    Cached Open Graph / Twitter card metadata of a link.
+    Failed fetches are cached as well so dead links aren't retried
+    on every read.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LinkPreview {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+    pub image: Option<String>,
+    pub failed: bool,
+    pub fetched_at: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Mention {
     pub user_id: String,
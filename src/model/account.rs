@@ -157,4 +157,110 @@ pub struct AccountFollow {
     pub user_id: String,
     pub followed_by: String,
     pub followed_at: i64,
-}
\ No newline at end of file
+}
+//follow request for private accounts
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccountFollowRequest {
+    pub uuid: String,
+    pub user_id: String,
+    pub requested_by: String,
+    pub requested_at: i64,
+}
+
+//audience for privacy controlled actions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PrivacyAudience { Everyone, FriendsAndFollowers, Friends, Nobody }
+impl std::fmt::Display for PrivacyAudience {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt,"{:?}", self)
+    }
+}
+
+//account_privacy_setting
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccountPrivacySettings {
+    pub uuid: String,
+
+    pub private_account: bool,
+    pub last_seen_visibility: PrivacyAudience,
+    pub online_status_visibility: PrivacyAudience,
+    pub who_can_message: PrivacyAudience,
+    pub who_can_add_to_group: PrivacyAudience,
+    pub who_can_mention: PrivacyAudience,
+
+    pub modified_at: i64,
+}
+
+impl AccountPrivacySettings {
+    /* Settings used for accounts that never saved their own */
+    pub fn default_for(user_id: &str) -> Self {
+        Self {
+            uuid: user_id.to_string(),
+            private_account: false,
+            last_seen_visibility: PrivacyAudience::Everyone,
+            online_status_visibility: PrivacyAudience::Everyone,
+            who_can_message: PrivacyAudience::Everyone,
+            who_can_add_to_group: PrivacyAudience::Everyone,
+            who_can_mention: PrivacyAudience::Everyone,
+            modified_at: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum NsfwPreference { Show, Blur, Hide }
+impl std::fmt::Display for NsfwPreference {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt,"{:?}", self)
+    }
+}
+
+//account_content_preference
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccountContentPreferences {
+    pub uuid: String,
+
+    pub nsfw: NsfwPreference,
+    pub filtered_keywords: Vec<String>,
+
+    pub modified_at: i64,
+}
+
+impl AccountContentPreferences {
+    /* Preferences used for accounts that never saved their own */
+    pub fn default_for(user_id: &str) -> Self {
+        Self {
+            uuid: user_id.to_string(),
+            nsfw: NsfwPreference::Blur,
+            filtered_keywords: Vec::new(),
+            modified_at: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum VerificationStatus { Pending, Approved, Rejected }
+impl std::fmt::Display for VerificationStatus {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt,"{:?}", self)
+    }
+}
+
+//profile_verification_request
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProfileVerificationRequest {
+    pub uuid: String,
+    pub user_id: String,
+
+    pub full_name: String,
+    pub category: String,
+    pub documents: Vec<String>,
+    pub note: Option<String>,
+
+    pub status: VerificationStatus,
+    pub review_note: Option<String>,
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<i64>,
+
+    pub created_at: i64,
+}
@@ -24,6 +24,10 @@ pub struct CommentCore {
     pub is_edited: bool,
     pub mentions: Vec<Mention>,
 
+    // Sort key of the `top` comment order, see `utils::comment_rank`
+    #[serde(default)]
+    pub top_rank: i64,
+
     pub created_at: i64,
     pub modified_at: i64,
     pub deleted_at: Option<i64>,
@@ -48,4 +52,31 @@ pub struct CommentLike {
     pub comment_id: String,
     pub liked_by: String,
     pub liked_at: i64,
+
+    // Set while the post is deleted and can still be restored
+    #[serde(default)]
+    pub deleted_at: Option<i64>,
+}
+
+//comment_revision
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CommentRevision {
+    pub uuid: String,
+    pub comment_id: String,
+    pub edited_by: String,
+
+    pub text: Option<String>,
+    pub images: Vec<String>,
+    pub mentions: Vec<Mention>,
+
+    pub revised_at: i64,
+}
+
+//comment_pin
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CommentPin {
+    pub post_id: String,
+    pub comment_id: String,
+    pub pinned_by: String,
+    pub pinned_at: i64,
 }
\ No newline at end of file
@@ -65,8 +65,9 @@ pub struct PageBlocked {
     pub blocked_at: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub enum PageVisibility {
+    #[default]
     Public,
     Private,
     Unlisted,
@@ -17,6 +17,21 @@ pub struct Poll {
     pub question: String,
     pub options: Vec<String>,
     pub r#type: PollType,
+    pub closes_at: Option<i64>,
+    #[serde(default)]
+    pub anonymous: bool,
+}
+
+//poll_stat
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PollStat {
+    pub uuid: String,
+
+    // Vote count of every option, in the order of `Poll.options`
+    pub option_votes: Vec<i64>,
+    pub voter_count: i64,
+
+    pub modified_at: i64,
 }
 
 //poll_vote
@@ -25,4 +40,5 @@ pub struct PollVote {
     pub poll_id: String,
     pub user_id: String,
     pub option: String,
+    pub voted_at: Option<i64>,
 }
\ No newline at end of file
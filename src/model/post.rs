@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use super::{ VideoStruct, AudioStruct};
+use super::{ VideoStruct, AudioStruct, Mention};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PostOwnerType { User, Page }
@@ -25,6 +25,14 @@ impl std::fmt::Display for PostVisibility {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum PostPublishState { Draft, Scheduled, #[default] Published }
+impl std::fmt::Display for PostPublishState {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt,"{:?}", self)
+    }
+}
+
 //post_core
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PostCore {
@@ -36,19 +44,42 @@ pub struct PostCore {
     pub videos: Vec<String>,
     pub audio: Option<AudioStruct>,
     pub poll: Option<String>,
+    pub shared_post: Option<String>,
 
     pub owner_type: PostOwnerType,
     pub visibility: PostVisibility,
     pub is_nsfw: bool,
     pub content_warning: Option<String>,
 
+    // Posts written before drafts existed have no state and are published
+    #[serde(default)]
+    pub publish_state: PostPublishState,
+    pub publish_at: Option<i64>,
+
     pub created_at: i64,
     pub modified_at: i64,
+    pub edited_at: Option<i64>,
     pub deleted_at: Option<i64>,
     pub suspended_at: Option<i64>,
     pub suspended_by: Option<String>,
 }
 
+//post_revision
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PostRevision {
+    pub uuid: String,
+    pub post_id: String,
+    pub edited_by: String,
+
+    pub caption: Option<String>,
+    pub mentions: Vec<Mention>,
+    pub tags: Vec<String>,
+    pub content_warning: Option<String>,
+    pub is_nsfw: bool,
+
+    pub revised_at: i64,
+}
+
 //post_mention
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PostMention {
@@ -63,6 +94,17 @@ pub struct PostMention {
 pub struct PostTag {
     pub post_id: String,
     pub tag: String,
+
+    // Creation time of the post, tags written before it existed have none
+    pub created_at: Option<i64>,
+}
+
+//tag_follow
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TagFollow {
+    pub tag: String,
+    pub followed_by: String,
+    pub followed_at: i64,
 }
 
 //post_stat
@@ -84,6 +126,10 @@ pub struct PostLike {
     pub post_id: String,
     pub liked_by: String,
     pub liked_at: i64,
+
+    // Set while the post is deleted and can still be restored
+    #[serde(default)]
+    pub deleted_at: Option<i64>,
 }
 
 //post_bookmark
@@ -91,5 +137,40 @@ pub struct PostLike {
 pub struct PostBookmark {
     pub post_id: String,
     pub bookmarked_by: String,
+    pub collection_id: Option<String>,
     pub bookmarked_at: i64,
+
+    // Set while the post is deleted and can still be restored
+    #[serde(default)]
+    pub deleted_at: Option<i64>,
+}
+
+//bookmark_collection
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BookmarkCollection {
+    pub uuid: String,
+    pub owner: String,
+    pub name: String,
+    pub position: i64,
+
+    pub created_at: i64,
+    pub modified_at: i64,
+}
+
+//feed_seen
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FeedSeen {
+    pub post_id: String,
+    pub user_id: String,
+    pub seen_at: i64,
+}
+
+//post_pin
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PostPin {
+    // User or page the post is pinned on, same as `PostCore.owner`
+    pub owner: String,
+    pub post_id: String,
+    pub position: i64,
+    pub pinned_at: i64,
 }
\ No newline at end of file
@@ -46,4 +46,22 @@ pub struct ReplyLike {
     pub reply_id: String,
     pub liked_by: String,
     pub liked_at: i64,
+
+    // Set while the post is deleted and can still be restored
+    #[serde(default)]
+    pub deleted_at: Option<i64>,
+}
+
+//reply_revision
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReplyRevision {
+    pub uuid: String,
+    pub reply_id: String,
+    pub edited_by: String,
+
+    pub text: Option<String>,
+    pub images: Vec<String>,
+    pub mentions: Vec<Mention>,
+
+    pub revised_at: i64,
 }
\ No newline at end of file
@@ -38,4 +38,19 @@ pub mod report;
 pub use report as Report;
 
 pub mod support;
-pub use support as Support;
\ No newline at end of file
+pub use support as Support;
+
+pub mod verification;
+pub use verification as Verification;
+
+pub mod feed;
+pub use feed as Feed;
+
+pub mod bookmark;
+pub use bookmark as Bookmark;
+
+pub mod poll;
+pub use poll as Poll;
+
+pub mod tag;
+pub use tag as Tag;
\ No newline at end of file
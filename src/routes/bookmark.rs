@@ -0,0 +1,43 @@
+use actix_web::web;
+use crate::Handler;
+
+pub fn router(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/bookmark")
+        //Saved posts
+        .route(
+          "",
+          web::get().to(Handler::Bookmark::List::task)
+        )
+        //Collections
+        .route(
+          "/collection",
+          web::get().to(Handler::Bookmark::CollectionList::task)
+        )
+        .route(
+          "/collection",
+          web::post().to(Handler::Bookmark::CollectionCreate::task)
+        )
+        .route(
+          "/collection/reorder",
+          web::post().to(Handler::Bookmark::CollectionReorder::task)
+        )
+        .route(
+          "/collection/{uuid}",
+          web::patch().to(Handler::Bookmark::CollectionRename::task)
+        )
+        .route(
+          "/collection/{uuid}",
+          web::delete().to(Handler::Bookmark::CollectionDelete::task)
+        )
+        //Bookmark
+        .route(
+          "/{post_id}",
+          web::post().to(Handler::Bookmark::Add::task)
+        )
+        .route(
+          "/{post_id}",
+          web::delete().to(Handler::Bookmark::Remove::task)
+        )
+    );
+}
\ No newline at end of file
@@ -19,5 +19,28 @@ pub fn router(cfg: &mut web::ServiceConfig) {
           "/{uuid}",
           web::delete().to(Handler::Comment::Delete::task)
         )
+        //Edit
+        .route(
+          "/{uuid}",
+          web::patch().to(Handler::Comment::Edit::task)
+        )
+        //Like
+        .route(
+          "/{uuid}/like",
+          web::post().to(Handler::Comment::Like::task)
+        )
+        .route(
+          "/{uuid}/like",
+          web::delete().to(Handler::Comment::Unlike::task)
+        )
+        //Pin
+        .route(
+          "/{uuid}/pin",
+          web::post().to(Handler::Comment::Pin::task)
+        )
+        .route(
+          "/{uuid}/pin",
+          web::delete().to(Handler::Comment::Unpin::task)
+        )
     );
 }
\ No newline at end of file
@@ -0,0 +1,17 @@
+use actix_web::web;
+use crate::Handler;
+
+pub fn router(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/feed")
+        //Get
+        .route(
+          "/home",
+          web::get().to(Handler::Feed::Home::task)
+        )
+        .route(
+          "/for-you",
+          web::get().to(Handler::Feed::ForYou::task)
+        )
+    );
+}
\ No newline at end of file
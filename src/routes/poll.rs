@@ -0,0 +1,23 @@
+use actix_web::web;
+use crate::Handler;
+
+pub fn router(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/poll")
+        //Vote
+        .route(
+          "/{uuid}/vote",
+          web::post().to(Handler::Poll::Vote::task)
+        )
+        //Close
+        .route(
+          "/{uuid}/close",
+          web::post().to(Handler::Poll::Close::task)
+        )
+        //Voters
+        .route(
+          "/{uuid}/voters",
+          web::get().to(Handler::Poll::Voters::task)
+        )
+    );
+}
@@ -9,15 +9,81 @@ pub fn router(cfg: &mut web::ServiceConfig) {
           "",
           web::post().to(Handler::Post::Create::task)
         )
+        //Views
+        .route(
+          "/impressions",
+          web::post().to(Handler::Post::Impression::task)
+        )
         //Get
         .route(
           "",
           web::get().to(Handler::Post::Get::task)
         )
+        .route(
+          "/drafts",
+          web::get().to(Handler::Post::DraftList::task)
+        )
+        .route(
+          "/{uuid}/revisions",
+          web::get().to(Handler::Post::RevisionList::task)
+        )
+        .route(
+          "/{uuid}/likes",
+          web::get().to(Handler::Post::LikeList::task)
+        )
+        //Drafts
+        .route(
+          "/{uuid}/publish",
+          web::post().to(Handler::Post::Publish::task)
+        )
+        .route(
+          "/{uuid}/schedule",
+          web::post().to(Handler::Post::Schedule::task)
+        )
+        .route(
+          "/{uuid}/schedule",
+          web::delete().to(Handler::Post::Unschedule::task)
+        )
+        //Share
+        .route(
+          "/{uuid}/share",
+          web::post().to(Handler::Post::Share::task)
+        )
+        //Pin
+        .route(
+          "/pins/order",
+          web::put().to(Handler::Post::PinOrder::task)
+        )
+        .route(
+          "/{uuid}/pin",
+          web::post().to(Handler::Post::Pin::task)
+        )
+        .route(
+          "/{uuid}/pin",
+          web::delete().to(Handler::Post::Unpin::task)
+        )
+        //Like
+        .route(
+          "/{uuid}/like",
+          web::post().to(Handler::Post::Like::task)
+        )
+        .route(
+          "/{uuid}/like",
+          web::delete().to(Handler::Post::Unlike::task)
+        )
+        //Edit
+        .route(
+          "/{uuid}",
+          web::patch().to(Handler::Post::Edit::task)
+        )
         //Delete
         .route(
           "/{uuid}",
           web::delete().to(Handler::Post::Delete::task)
         )
+        .route(
+          "/{uuid}/restore",
+          web::post().to(Handler::Post::Restore::task)
+        )
     );
 }
\ No newline at end of file
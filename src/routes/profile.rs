@@ -22,5 +22,49 @@ pub fn router(cfg: &mut web::ServiceConfig) {
           "",
           web::patch().to(Handler::Profile::Update::task)
         )
+        //Privacy
+        .route(
+          "/privacy",
+          web::get().to(Handler::Profile::GetPrivacy::task)
+        )
+        .route(
+          "/privacy",
+          web::patch().to(Handler::Profile::UpdatePrivacy::task)
+        )
+        //Content preferences
+        .route(
+          "/content-preferences",
+          web::get().to(Handler::Profile::GetContentPreferences::task)
+        )
+        .route(
+          "/content-preferences",
+          web::patch().to(Handler::Profile::UpdateContentPreferences::task)
+        )
+        //Follow
+        .route(
+          "/follow/{user_id}",
+          web::post().to(Handler::Profile::Follow::task)
+        )
+        .route(
+          "/follow-request/list",
+          web::get().to(Handler::Profile::FollowRequestList::task)
+        )
+        .route(
+          "/follow-request/respond",
+          web::post().to(Handler::Profile::FollowRequestRespond::task)
+        )
+        //Like
+        .route(
+          "/like/{user_id}",
+          web::post().to(Handler::Profile::Like::task)
+        )
+        .route(
+          "/like/{user_id}",
+          web::delete().to(Handler::Profile::Unlike::task)
+        )
+        .route(
+          "/like/{user_id}/list",
+          web::get().to(Handler::Profile::LikeList::task)
+        )
     );
 }
\ No newline at end of file
@@ -19,5 +19,19 @@ pub fn router(cfg: &mut web::ServiceConfig) {
           "/{uuid}",
           web::delete().to(Handler::Reply::Delete::task)
         )
+        //Edit
+        .route(
+          "/{uuid}",
+          web::patch().to(Handler::Reply::Edit::task)
+        )
+        //Like
+        .route(
+          "/{uuid}/like",
+          web::post().to(Handler::Reply::Like::task)
+        )
+        .route(
+          "/{uuid}/like",
+          web::delete().to(Handler::Reply::Unlike::task)
+        )
     );
 }
\ No newline at end of file
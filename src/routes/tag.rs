@@ -0,0 +1,35 @@
+use actix_web::web;
+use crate::Handler;
+
+pub fn router(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/tag")
+        //Discovery
+        .route(
+          "/trending",
+          web::get().to(Handler::Tag::Trending::task)
+        )
+        .route(
+          "/search",
+          web::get().to(Handler::Tag::Search::task)
+        )
+        //Followed tags
+        .route(
+          "/following",
+          web::get().to(Handler::Tag::Following::task)
+        )
+        .route(
+          "/{tag}/follow",
+          web::post().to(Handler::Tag::Follow::task)
+        )
+        .route(
+          "/{tag}/follow",
+          web::delete().to(Handler::Tag::Unfollow::task)
+        )
+        //Tag page
+        .route(
+          "/{tag}",
+          web::get().to(Handler::Tag::Posts::task)
+        )
+    );
+}
@@ -0,0 +1,27 @@
+use actix_web::web;
+use crate::Handler;
+
+pub fn router(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/verification")
+        //Submit
+        .route(
+          "",
+          web::post().to(Handler::Verification::Submit::task)
+        )
+        //Own request status
+        .route(
+          "/myself",
+          web::get().to(Handler::Verification::Status::task)
+        )
+        //Admin review queue
+        .route(
+          "/queue",
+          web::get().to(Handler::Verification::Queue::task)
+        )
+        .route(
+          "/review",
+          web::post().to(Handler::Verification::Review::task)
+        )
+    );
+}
\ No newline at end of file
@@ -1,4 +1,18 @@
 pub mod response;
 pub mod string;
 pub mod validation;
-pub mod mongo;
\ No newline at end of file
+pub mod mongo;
+pub mod privacy;
+pub mod notification;
+
+pub mod visibility;
+pub mod view_counter;
+pub mod tag;
+pub mod mention;
+pub mod query_counter;
+pub mod pagination;
+pub mod fields;
+pub mod content_filter;
+pub mod link_preview;
+pub mod comment_rank;
+pub mod page_access;
\ No newline at end of file
@@ -0,0 +1,110 @@
+use futures::StreamExt;
+use mongodb::{ClientSession, Database};
+use mongodb::bson::doc;
+use actix_web::HttpResponse;
+use crate::utils::response::Response;
+use crate::model::Comment::{CommentCore, CommentStat};
+
+/* A reply takes more than a like and counts as this many likes */
+const REPLY_WEIGHT: i64 = 2;
+
+/* Freshness ten times the engagement makes up for, 12.5 hours */
+const DECAY_MILLIS: f64 = 45_000_000.0;
+
+/*
+    Sort key of the `top` comment order: likes and replies, decayed by age.
+    Instead of old comments losing rank over time, newer ones start higher,
+    so the rank only moves when the counters do and can be stored on the
+    comment and paginated like any other field.
+*/
+pub fn top_rank(created_at: i64, like_count: i64, reply_count: i64) -> i64 {
+    let engagement = (like_count + REPLY_WEIGHT * reply_count).max(1) as f64;
+    created_at + (engagement.log10() * DECAY_MILLIS) as i64
+}
+
+/* Recomputes the rank of a comment after its counters changed */
+pub async fn refresh(
+    db: &Database,
+    session: &mut ClientSession,
+    comment_id: &str
+) -> Result<(), HttpResponse> {
+    let collection = db.collection::<CommentCore>("comment_core");
+    let result = collection.find_one(doc!{"uuid": comment_id})
+    .session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let comment_core = match result.unwrap() {
+        Some(comment_core) => comment_core,
+        None => return Ok(()),
+    };
+
+    let collection = db.collection::<CommentStat>("comment_stat");
+    let result = collection.find_one(doc!{"uuid": comment_id})
+    .session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let rank = match result.unwrap() {
+        Some(stat) => top_rank(
+            comment_core.created_at,
+            stat.like_count,
+            stat.reply_count
+        ),
+        None => top_rank(comment_core.created_at, 0, 0),
+    };
+
+    let collection = db.collection::<CommentCore>("comment_core");
+    let result = collection.update_one(
+        doc!{"uuid": comment_id},
+        doc!{"$set": {"top_rank": rank}}
+    ).session(&mut *session).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(())
+}
+
+/*
+    Ranks the comments written before comments had a rank, so the `top`
+    order doesn't skip them. A one-off migration run at startup, it only
+    touches comments that still have no rank.
+*/
+pub async fn backfill(db: &Database) -> Result<u64, mongodb::error::Error> {
+    let collection = db.collection::<CommentCore>("comment_core");
+    let stat_collection = db.collection::<CommentStat>("comment_stat");
+
+    let mut cursor = collection.find(doc!{"top_rank": {"$exists": false}}).await?;
+
+    let mut ranked = 0;
+    while let Some(comment_core) = cursor.next().await {
+        let comment_core = comment_core?;
+
+        let rank = match stat_collection.find_one(doc!{"uuid": &comment_core.uuid}).await? {
+            Some(stat) => top_rank(
+                comment_core.created_at,
+                stat.like_count,
+                stat.reply_count
+            ),
+            None => top_rank(comment_core.created_at, 0, 0),
+        };
+
+        collection.update_one(
+            doc!{"uuid": &comment_core.uuid, "top_rank": {"$exists": false}},
+            doc!{"$set": {"top_rank": rank}}
+        ).await?;
+
+        ranked += 1;
+    }
+
+    Ok(ranked)
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, Months, Utc};
+use serde_json::{json, Map, Value};
+use mongodb::Database;
+use mongodb::bson::doc;
+use actix_web::HttpResponse;
+use crate::utils::response::Response;
+use crate::model::Account::{
+    AccountContentPreferences,
+    AccountProfile,
+    NsfwPreference,
+};
+use crate::model::Post::PostCore;
+
+/* Users younger than this never get NSFW posts, whatever they saved */
+pub const ADULT_AGE: u32 = 18;
+
+/* Upper bounds of the keyword filter */
+pub const MAX_KEYWORDS: usize = 100;
+pub const MAX_KEYWORD_LENGTH: usize = 50;
+
+pub async fn get_preferences(
+    db: &Database,
+    user_id: &str
+) -> Result<AccountContentPreferences, HttpResponse> {
+    let collection = db.collection::
+    <AccountContentPreferences>("account_content_preference");
+    let result = collection.find_one(doc!{"uuid": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    match result.unwrap() {
+        Some(preferences) => Ok(preferences),
+        None => Ok(AccountContentPreferences::default_for(user_id)),
+    }
+}
+
+/* Accounts without a date of birth are treated as adults */
+pub async fn is_minor(
+    db: &Database,
+    user_id: &str
+) -> Result<bool, HttpResponse> {
+    let collection = db.collection::<AccountProfile>("account_profile");
+    let result = collection.find_one(doc!{"uuid": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let date_of_birth = match result.unwrap() {
+        Some(profile) => profile.date_of_birth,
+        None => None,
+    };
+
+    let born = match date_of_birth.and_then(DateTime::from_timestamp_millis) {
+        Some(born) => born,
+        None => return Ok(false),
+    };
+
+    Ok(match born.checked_add_months(Months::new(ADULT_AGE * 12)) {
+        Some(adult_at) => adult_at > Utc::now(),
+        None => false,
+    })
+}
+
+/*
+    Trims and lowercases keywords, dropping empty ones and duplicates.
+    Matching is case insensitive, so this is what gets stored.
+*/
+pub fn normalize_keywords(keywords: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for keyword in keywords {
+        let keyword = keyword.trim().to_lowercase();
+
+        if keyword.len() == 0 || normalized.contains(&keyword) {
+            continue;
+        }
+
+        if keyword.chars().count() > MAX_KEYWORD_LENGTH {
+            return Err(format!(
+                "Keywords can't be longer than {} characters",
+                MAX_KEYWORD_LENGTH
+            ));
+        }
+
+        normalized.push(keyword);
+    }
+
+    if normalized.len() > MAX_KEYWORDS {
+        return Err(format!("At most {} keywords can be filtered", MAX_KEYWORDS));
+    }
+
+    Ok(normalized)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Show,
+    Blur,
+    Hide(HideReason),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HideReason { Nsfw, Keyword }
+impl std::fmt::Display for HideReason {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt,"{:?}", self)
+    }
+}
+
+/*
+    Content preferences of a viewer, applied to every post and feed read.
+    Viewers always see their own posts as they are.
+*/
+pub struct ContentFilter {
+    user_id: String,
+    nsfw: NsfwPreference,
+    keywords: Vec<String>,
+}
+
+impl ContentFilter {
+    pub async fn load(
+        db: &Database,
+        user_id: &str
+    ) -> Result<Self, HttpResponse> {
+        let preferences = get_preferences(db, user_id).await?;
+
+        let nsfw = match is_minor(db, user_id).await? {
+            true => NsfwPreference::Hide,
+            false => preferences.nsfw,
+        };
+
+        Ok(ContentFilter {
+            user_id: user_id.to_string(),
+            nsfw,
+            keywords: preferences.filtered_keywords,
+        })
+    }
+
+    pub fn check(&self, post_core: &PostCore) -> Verdict {
+        if post_core.owner == self.user_id {
+            return Verdict::Show;
+        }
+
+        if self.matches_keyword(post_core) {
+            return Verdict::Hide(HideReason::Keyword);
+        }
+
+        if !post_core.is_nsfw {
+            return Verdict::Show;
+        }
+
+        match self.nsfw {
+            NsfwPreference::Show => Verdict::Show,
+            NsfwPreference::Blur => Verdict::Blur,
+            NsfwPreference::Hide => Verdict::Hide(HideReason::Nsfw),
+        }
+    }
+
+    fn matches_keyword(&self, post_core: &PostCore) -> bool {
+        if self.keywords.len() == 0 {
+            return false;
+        }
+
+        let text = [&post_core.caption, &post_core.content_warning]
+        .iter()
+        .filter_map(|text| text.as_ref())
+        .map(|text| text.to_lowercase())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+        self.keywords.iter().any(|keyword| text.contains(keyword))
+    }
+}
+
+/*
+    Stands in for a post the viewer chose not to see.
+    Keeps what clients need to place it in a list and the warning to show.
+*/
+pub fn placeholder(post_core: &PostCore, reason: &HideReason) -> Map<String, Value> {
+    let warning = match (&post_core.content_warning, reason) {
+        (Some(warning), _) => warning.clone(),
+        (None, HideReason::Nsfw) => "Sensitive content".to_string(),
+        (None, HideReason::Keyword) => "Contains a filtered keyword".to_string(),
+    };
+
+    let mut placeholder = Map::new();
+    placeholder.insert("uuid".to_string(), json!(&post_core.uuid));
+    placeholder.insert("created_at".to_string(), json!(post_core.created_at));
+    placeholder.insert("hidden".to_string(), json!(true));
+    placeholder.insert("reason".to_string(), json!(reason.to_string()));
+    placeholder.insert("warning".to_string(), json!(warning));
+    placeholder
+}
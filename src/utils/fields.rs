@@ -0,0 +1,153 @@
+use serde_json::{Map, Value};
+use actix_web::HttpResponse;
+use mongodb::bson::{doc, Document};
+use crate::utils::string;
+use crate::utils::response::Response;
+
+/*
+    Section of a resource response and the fields it can be trimmed to.
+    Sections without fields are values that only come whole.
+*/
+pub struct Section {
+    pub name: &'static str,
+    pub fields: &'static [&'static str],
+}
+
+/*
+    Sparse fieldset of a read endpoint, parsed from
+    `fields=(section(field,...),section,...)`.
+    A section listed without fields comes whole, a section left out
+    isn't loaded at all. Without `fields` every section comes whole.
+*/
+pub struct Fields {
+    schema: &'static [Section],
+    selected: Vec<(&'static str, Vec<String>)>,
+}
+
+impl Fields {
+    pub fn all(schema: &'static [Section]) -> Self {
+        let selected = schema.iter()
+        .map(|section| (section.name, Vec::new()))
+        .collect();
+
+        Fields { schema, selected }
+    }
+
+    pub fn parse(
+        fields: &Option<String>,
+        schema: &'static [Section]
+    ) -> Result<Self, HttpResponse> {
+        let fields = match fields {
+            Some(fields) => fields,
+            None => return Ok(Fields::all(schema)),
+        };
+
+        let parts = match string::parse_comma_separated(fields) {
+            Ok(parts) => parts,
+            Err(error) => return Err(Response::bad_request(&error)),
+        };
+
+        let mut selected: Vec<(&'static str, Vec<String>)> = Vec::new();
+        for part in parts {
+            let (name, sub_fields) = match part.find('(') {
+                Some(i) => (&part[..i], &part[i..]),
+                None => (part, ""),
+            };
+
+            let section = match schema.iter().find(|section| section.name == name) {
+                Some(section) => section,
+                None => return Err(Response::bad_request(
+                    &format!("Invalid field: {}", part)
+                )),
+            };
+
+            if selected.iter().any(|(selected, _)| *selected == section.name) {
+                return Err(Response::bad_request(
+                    &format!("Duplicate field: {}", name)
+                ));
+            }
+
+            let sub_fields = match sub_fields.len() {
+                0 => vec![],
+                _ => match string::parse_comma_separated(sub_fields) {
+                    Ok(sub_fields) => sub_fields,
+                    Err(error) => return Err(Response::bad_request(&error)),
+                },
+            };
+
+            let mut fields = Vec::new();
+            for field in sub_fields {
+                if !section.fields.contains(&field) {
+                    return Err(Response::bad_request(
+                        &format!("Invalid field: {}({})", name, field)
+                    ));
+                }
+
+                fields.push(field.to_string());
+            }
+
+            selected.push((section.name, fields));
+        }
+
+        Ok(Fields { schema, selected })
+    }
+
+    pub fn includes(&self, section: &str) -> bool {
+        self.selected.iter().any(|(name, _)| *name == section)
+    }
+
+    /* Whether `field` of `section` is part of the response */
+    pub fn wants(&self, section: &str, field: &str) -> bool {
+        match self.selected.iter().find(|(name, _)| *name == section) {
+            Some((_, fields)) => fields.len() == 0 || fields.iter().any(|f| f == field),
+            None => false,
+        }
+    }
+
+    /*
+        Mongo projection loading the selected fields of `section`,
+        every field of its schema when it comes whole.
+    */
+    pub fn projection(&self, section: &str) -> Document {
+        let mut projection = doc!{"_id": 0};
+
+        let schema_fields = self.schema.iter()
+        .find(|schema| schema.name == section)
+        .map(|schema| schema.fields)
+        .unwrap_or_default();
+
+        for field in schema_fields {
+            if self.wants(section, field) {
+                projection.insert(*field, 1);
+            }
+        }
+
+        projection
+    }
+
+    /*
+        Trims a response to the selected sections and fields.
+        Keys outside the schema are left as they are.
+    */
+    pub fn apply(&self, mut response: Map<String, Value>) -> Map<String, Value> {
+        for section in self.schema {
+            let fields = match self.selected.iter().find(|(name, _)| *name == section.name) {
+                Some((_, fields)) => fields,
+                None => {
+                    response.remove(section.name);
+                    continue;
+                }
+            };
+
+            if fields.len() == 0 {
+                continue;
+            }
+
+            if let Some(Value::Object(object)) = response.get_mut(section.name) {
+                object.retain(|key, _| fields.contains(key));
+            }
+        }
+
+        response
+    }
+}
@@ -0,0 +1,256 @@
+use std::env;
+use std::collections::HashMap;
+use uuid::Uuid;
+use chrono::Utc;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use mongodb::Database;
+use mongodb::bson::doc;
+use actix_web::HttpResponse;
+use crate::utils::response::Response;
+use crate::Integrations::Unfurl;
+use crate::Handler::Image::Upload::save_image;
+use crate::model::{AssetUsedAt, ImageStruct, LinkPreview};
+
+/* Links unfurled per caption or message, the rest stay bare */
+pub const MAX_PREVIEWS: usize = 3;
+
+/*
+    Largest preview image decoded, in pixels. The byte cap of the fetch
+    doesn't bound it, a small compressed image can decode to gigabytes.
+*/
+const MAX_IMAGE_PIXELS: u64 = 4096 * 4096;
+
+/* Characters a link can't end with, they belong to the sentence around it */
+const URL_TERMINATORS: &[char] = &[
+    '.', ',', '!', '?', ':', ';', ')', ']', '}', '"', '\'', '>'
+];
+
+/* Every http(s) link in the text, in order and without duplicates */
+pub fn extract_urls(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let start = match word.find("https://").or(word.find("http://")) {
+            Some(start) => start,
+            None => continue,
+        };
+
+        let url = word[start..].trim_end_matches(URL_TERMINATORS);
+        if url.len() <= "https://".len() || urls.iter().any(|u| u == url) {
+            continue;
+        }
+
+        urls.push(url.to_string());
+        if urls.len() == MAX_PREVIEWS {
+            break;
+        }
+    }
+
+    urls
+}
+
+/* Previews of `urls` already in the cache, failed ones are left out */
+pub async fn get_cached(
+    db: &Database,
+    urls: &Vec<String>
+) -> Result<HashMap<String, LinkPreview>, HttpResponse> {
+    let mut previews = HashMap::new();
+    if urls.len() == 0 {
+        return Ok(previews);
+    }
+
+    let collection = db.collection::<LinkPreview>("link_preview");
+    let result = collection.find(doc!{
+        "url": {"$in": urls},
+        "failed": false,
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        match result {
+            Ok(preview) => {
+                previews.insert(preview.url.clone(), preview);
+            },
+            Err(error) => {
+                log::error!("{:?}", error);
+                return Err(Response::internal_server_error(&error.to_string()));
+            }
+        }
+    }
+
+    Ok(previews)
+}
+
+/* Previews of the links in `text`, in the order they were written */
+pub fn previews_of(
+    text: &Option<String>,
+    cache: &HashMap<String, LinkPreview>
+) -> Vec<Value> {
+    let text = match text {
+        Some(text) => text,
+        None => return Vec::new(),
+    };
+
+    extract_urls(text)
+    .iter()
+    .filter_map(|url| cache.get(url))
+    .map(to_json)
+    .collect()
+}
+
+pub fn to_json(preview: &LinkPreview) -> Value {
+    json!({
+        "url": &preview.url,
+        "title": &preview.title,
+        "description": &preview.description,
+        "site_name": &preview.site_name,
+        "image": &preview.image,
+    })
+}
+
+/*
+    Unfurls every link in `text` that isn't cached or has gone stale.
+    Meant to run in the background, errors are only logged.
+*/
+pub async fn unfurl_text(db: &Database, text: &str) -> Vec<LinkPreview> {
+    let mut previews = Vec::new();
+    for url in extract_urls(text) {
+        match unfurl(db, &url).await {
+            Ok(Some(preview)) => previews.push(preview),
+            Ok(None) => {},
+            Err(error) => log::error!("{:?}", error),
+        }
+    }
+
+    previews
+}
+
+/* Cached preview of `url`, fetched again once older than the TTL */
+pub async fn unfurl(
+    db: &Database,
+    url: &str
+) -> Result<Option<LinkPreview>, String> {
+    let ttl_hours: i64 = env::var("LINK_PREVIEW_TTL_HOURS")
+    .ok()
+    .and_then(|hours| hours.parse().ok())
+    .unwrap_or(24);
+
+    let collection = db.collection::<LinkPreview>("link_preview");
+    let cached = match collection.find_one(doc!{"url": url}).await {
+        Ok(cached) => cached,
+        Err(error) => return Err(error.to_string()),
+    };
+
+    let now = Utc::now().timestamp_millis();
+    if let Some(cached) = &cached {
+        if cached.fetched_at + ttl_hours * 60 * 60 * 1000 > now {
+            return Ok(Some(cached.clone()).filter(|cached| !cached.failed));
+        }
+    }
+
+    let preview = match Unfurl::fetch_metadata(url).await {
+        Ok(metadata) => {
+            let image = match &metadata.image {
+                Some(image_url) => store_image(db, image_url).await,
+                None => None,
+            };
+
+            LinkPreview {
+                url: url.to_string(),
+                title: metadata.title,
+                description: metadata.description,
+                site_name: metadata.site_name,
+                image,
+                failed: false,
+                fetched_at: now,
+            }
+        },
+        Err(error) => {
+            log::warn!("Unfurling {} failed: {}", url, error);
+            LinkPreview {
+                url: url.to_string(),
+                title: None,
+                description: None,
+                site_name: None,
+                image: None,
+                failed: true,
+                fetched_at: now,
+            }
+        }
+    };
+
+    let result = collection.replace_one(
+        doc!{"url": url},
+        &preview,
+    ).upsert(true).await;
+
+    if let Err(error) = result {
+        return Err(error.to_string());
+    }
+
+    // The refreshed preview replaces the image of the stale one
+    if let Some(image) = cached.and_then(|cached| cached.image) {
+        let collection = db.collection::<ImageStruct>("image");
+        let result = collection.update_one(
+            doc!{"uuid": image},
+            doc!{"$set": {"deleted": true}}
+        ).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+        }
+    }
+
+    Ok(Some(preview).filter(|preview| !preview.failed))
+}
+
+/* A preview without its image is still worth showing */
+async fn store_image(db: &Database, url: &str) -> Option<String> {
+    let bytes = match Unfurl::fetch_image(url).await {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            log::warn!("Preview image {} failed: {}", url, error);
+            return None;
+        }
+    };
+
+    let size = match imagesize::blob_size(&bytes) {
+        Ok(size) => size,
+        Err(error) => {
+            log::warn!("Preview image {} failed: {:?}", url, error);
+            return None;
+        }
+    };
+
+    if size.width as u64 * size.height as u64 > MAX_IMAGE_PIXELS {
+        log::warn!(
+            "Preview image {} is too large: {}x{}", url, size.width, size.height
+        );
+        return None;
+    }
+
+    let result = save_image(
+        db,
+        &Uuid::now_v7().to_string(),
+        &bytes,
+        "",
+        size.width,
+        size.height,
+        AssetUsedAt::LinkPreview,
+        false,
+        None,
+    ).await;
+
+    match result {
+        Ok(image) => Some(image.uuid),
+        Err(error) => {
+            log::warn!("Preview image {} failed: {}", url, error);
+            None
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use futures::StreamExt;
+use serde_json::json;
+use mongodb::Database;
+use mongodb::bson::{doc, Bson, Document};
+use actix_web::HttpResponse;
+use crate::utils::{notification, privacy};
+use crate::utils::response::Response;
+use crate::model::{
+    Mention,
+    Account::AccountCore,
+    Notification::{Notification, NotificationType},
+};
+
+/* Characters ending an `@username` besides whitespace */
+const USERNAME_TERMINATORS: &[char] = &[
+    ',', '!', '?', ':', ';', '(', ')', '[', ']', '{', '}', '"', '\'', '@', '#'
+];
+
+/* Content a mention is written in, decides the notification and its dedup */
+pub enum MentionedIn<'a> {
+    Post { post_id: &'a str },
+    Comment { post_id: &'a str, comment_id: &'a str },
+    Reply { comment_id: &'a str, reply_id: &'a str },
+    Message { conversation_id: &'a str, message_id: &'a str },
+}
+
+impl<'a> MentionedIn<'a> {
+    /* Stored as `metadata.kind`, a comment's metadata also names its post */
+    fn kind(&self) -> &'static str {
+        match self {
+            MentionedIn::Post { .. } => "post",
+            MentionedIn::Comment { .. } => "comment",
+            MentionedIn::Reply { .. } => "reply",
+            MentionedIn::Message { .. } => "message",
+        }
+    }
+
+    /* Metadata key and value identifying the content item */
+    fn key(&self) -> (&'static str, &'a str) {
+        match self {
+            MentionedIn::Post { post_id } => ("post_id", *post_id),
+            MentionedIn::Comment { comment_id, .. } => ("comment_id", *comment_id),
+            MentionedIn::Reply { reply_id, .. } => ("reply_id", *reply_id),
+            MentionedIn::Message { message_id, .. } => ("message_id", *message_id),
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            MentionedIn::Post { .. } => "mentioned you in a post",
+            MentionedIn::Comment { .. } => "mentioned you in a comment",
+            MentionedIn::Reply { .. } => "mentioned you in a reply",
+            MentionedIn::Message { .. } => "mentioned you in a message",
+        }.to_string()
+    }
+
+    fn metadata(&self) -> serde_json::Value {
+        let kind = self.kind();
+        match self {
+            MentionedIn::Post { post_id } => json!({
+                "kind": kind,
+                "post_id": post_id
+            }),
+            MentionedIn::Comment { post_id, comment_id } => json!({
+                "kind": kind,
+                "post_id": post_id,
+                "comment_id": comment_id
+            }),
+            MentionedIn::Reply { comment_id, reply_id } => json!({
+                "kind": kind,
+                "comment_id": comment_id,
+                "reply_id": reply_id
+            }),
+            MentionedIn::Message { conversation_id, message_id } => json!({
+                "kind": kind,
+                "conversation_id": conversation_id,
+                "message_id": message_id
+            }),
+        }
+    }
+}
+
+/*
+    Validates the explicit mentions of `text` and adds the ones written
+    as `@username`. Ranges are character offsets, `end` exclusive.
+
+    Explicit mentions must point to an existing, unsuspended user, fall
+    inside the text, not overlap and be allowed by the mentioned user's
+    privacy settings. Users in `exempt` skip the privacy check (already
+    mentioned before an edit). `@username` that matches nobody, overlaps
+    an explicit mention or isn't allowed stays plain text.
+*/
+pub async fn resolve(
+    db: &Database,
+    author_id: &str,
+    text: &Option<String>,
+    mentions: &Vec<Mention>,
+    exempt: &Vec<String>
+) -> Result<Vec<Mention>, HttpResponse> {
+    let text = match text {
+        Some(text) => text.as_str(),
+        None if mentions.len() == 0 => return Ok(Vec::new()),
+        None => return Err(Response::bad_request("Mentions need a text")),
+    };
+
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut resolved: Vec<Mention> = Vec::new();
+    for mention in mentions {
+        if mention.start >= mention.end || mention.end > chars.len() {
+            return Err(Response::bad_request(&format!(
+                "Mention of user {} is outside the text", mention.user_id
+            )));
+        }
+
+        if resolved.iter().any(|other| overlaps(other, mention.start, mention.end)) {
+            return Err(Response::bad_request("Mentions can't overlap"));
+        }
+
+        resolved.push(mention.clone());
+    }
+
+    let mut user_ids: Vec<String> = Vec::new();
+    for mention in &resolved {
+        if !user_ids.contains(&mention.user_id) {
+            user_ids.push(mention.user_id.clone());
+        }
+    }
+
+    let existing = find_accounts(db, doc!{"uuid": {"$in": &user_ids}}).await?;
+    for user_id in &user_ids {
+        if !existing.iter().any(|(uuid, _)| uuid == user_id) {
+            return Err(Response::not_found(&format!(
+                "Mentioned user {} not found", user_id
+            )));
+        }
+
+        if exempt.contains(user_id) {
+            continue;
+        }
+
+        if !privacy::can_mention(db, user_id, author_id).await? {
+            return Err(Response::forbidden(&format!(
+                "User {} can't be mentioned by you", user_id
+            )));
+        }
+    }
+
+    let written = scan_usernames(&chars);
+    let usernames: Vec<String> = written.iter()
+    .map(|(username, _, _)| username.clone())
+    .collect();
+
+    if usernames.len() > 0 {
+        let accounts = find_accounts(
+            db,
+            doc!{"username": {"$in": &usernames}}
+        ).await?;
+
+        let mut allowed: Vec<(String, bool)> = Vec::new();
+        for (username, start, end) in written {
+            if resolved.iter().any(|other| overlaps(other, start, end)) {
+                continue;
+            }
+
+            let user_id = match accounts.iter().find(|(_, name)| *name == username) {
+                Some((user_id, _)) => user_id.clone(),
+                None => continue,
+            };
+
+            let can_mention = match allowed.iter().find(|(id, _)| *id == user_id) {
+                Some((_, can_mention)) => *can_mention,
+                None => {
+                    let can_mention = exempt.contains(&user_id) ||
+                    user_ids.contains(&user_id) ||
+                    privacy::can_mention(db, &user_id, author_id).await?;
+
+                    allowed.push((user_id.clone(), can_mention));
+                    can_mention
+                }
+            };
+
+            if can_mention {
+                resolved.push(Mention { user_id, start, end });
+            }
+        }
+    }
+
+    resolved.sort_by_key(|mention| mention.start);
+
+    Ok(resolved)
+}
+
+/*
+    Sends a `Mention` notification to every user mentioned in the content.
+    A user is notified once per content item, no matter how often they
+    are mentioned or how many times the item is edited.
+*/
+pub async fn notify(
+    db: &Database,
+    author_id: &str,
+    mentions: &Vec<Mention>,
+    mentioned_in: MentionedIn<'_>
+) -> Result<(), HttpResponse> {
+    let (key, content_id) = mentioned_in.key();
+
+    let mut notified: Vec<&str> = Vec::new();
+    for mention in mentions {
+        let user_id = mention.user_id.as_str();
+        if notified.contains(&user_id) {
+            continue;
+        }
+
+        notified.push(user_id);
+
+        let mut filter = doc!{
+            "recipient_id": user_id,
+            "n_type": NotificationType::Mention.to_string(),
+            "metadata.kind": mentioned_in.kind(),
+        };
+        filter.insert(format!("metadata.{}", key), content_id);
+
+        let collection = db.collection::<Notification>("notification");
+        let result = collection.count_documents(filter).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        if result.unwrap() > 0 {
+            continue;
+        }
+
+        notification::send(
+            db,
+            user_id,
+            author_id,
+            NotificationType::Mention,
+            Some(mentioned_in.message()),
+            Some(mentioned_in.metadata())
+        ).await?;
+    }
+
+    Ok(())
+}
+
+fn overlaps(mention: &Mention, start: usize, end: usize) -> bool {
+    mention.start < end && start < mention.end
+}
+
+/* (uuid, username) of the active accounts matching `filter` */
+async fn find_accounts(
+    db: &Database,
+    mut filter: Document
+) -> Result<Vec<(String, String)>, HttpResponse> {
+    filter.insert("suspended_at", Bson::Null);
+
+    let collection = db.collection::<AccountCore>("account_core");
+    let result = collection.find(filter).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut accounts = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let account = result.unwrap();
+        accounts.push((account.uuid, account.username));
+    }
+
+    Ok(accounts)
+}
+
+/* Every `@username` in the text as (lowercased username, start, end) */
+fn scan_usernames(chars: &Vec<char>) -> Vec<(String, usize, usize)> {
+    let mut usernames = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        // An '@' inside a word (`mail@host`) doesn't start a mention
+        let starts_mention = chars[i] == '@' &&
+        (i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '_'));
+
+        if !starts_mention {
+            i += 1;
+            continue;
+        }
+
+        let start = i + 1;
+        let mut end = start;
+        while end < chars.len() &&
+        !chars[end].is_whitespace() &&
+        !USERNAME_TERMINATORS.contains(&chars[end]) {
+            end += 1;
+        }
+
+        // A sentence ending right after the username
+        while end > start && chars[end - 1] == '.' {
+            end -= 1;
+        }
+
+        if end > start {
+            let username: String = chars[start..end].iter().collect();
+            usernames.push((username.to_lowercase(), i, end));
+        }
+
+        i = end.max(start);
+    }
+
+    usernames
+}
@@ -1,35 +1,40 @@
+use futures::TryStreamExt;
+use serde::de::DeserializeOwned;
 use mongodb::{
-    bson::{ doc, Document },
+    bson::Document,
+    error::{Error, ErrorKind, WriteFailure},
     Collection,
-    Cursor,
 };
 
-pub async fn find_with_pagination<T>(
+/* MongoDB code of a unique index violation */
+const DUPLICATE_KEY: i32 = 11000;
+
+/* Every document matching `filter`, for batched `$in` reads */
+pub async fn find_all<T>(
     collection: &Collection<T>,
     filter: Document,
-    ascending: Option<bool>,
-    limit: Option<u32>,
-    page: Option<u32>,
-) -> mongodb::error::Result<Cursor<T>>
+) -> mongodb::error::Result<Vec<T>>
 where
-    T: Unpin + Send + Sync,
+    T: DeserializeOwned + Unpin + Send + Sync,
 {
-    let limit = limit.unwrap_or(10) as i64;
-
-    let sort_order = match ascending {
-        Some(true) => 1,
-        _ => -1,
-    };
+    collection.find(filter).await?.try_collect().await
+}
 
-    let mut find = collection
-        .find(filter)
-        .sort(doc! { "created_at": sort_order })
-        .limit(limit);
-
-    if let Some(page) = page {
-        let skip = limit * (page.saturating_sub(1) as i64);
-        find = find.skip(skip as u64);
+/*
+    Whether `error` only reports unique index violations, the writes that
+    went through are kept. Lets concurrent requests race on a unique index.
+*/
+pub fn is_duplicate_key(error: &Error) -> bool {
+    match error.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(error)) => {
+            error.code == DUPLICATE_KEY
+        },
+        ErrorKind::InsertMany(error) => {
+            error.write_concern_error.is_none() &&
+            error.write_errors.as_ref().map_or(false, |errors| {
+                errors.iter().all(|error| error.code == DUPLICATE_KEY)
+            })
+        },
+        _ => false,
     }
-
-    find.await
 }
\ No newline at end of file
@@ -0,0 +1,89 @@
+use uuid::Uuid;
+use chrono::Utc;
+use mongodb::Database;
+use mongodb::bson::doc;
+use actix_web::HttpResponse;
+use crate::utils::response::Response;
+use crate::model::{
+    Account::AccountNotificationSettings,
+    Notification::{Notification, NotificationType},
+};
+
+/*
+    Stores a notification for `recipient_id`.
+    Self notifications are skipped and the recipient's notification
+    settings decide whether the notification is stored at all.
+*/
+pub async fn send(
+    db: &Database,
+    recipient_id: &str,
+    sender_id: &str,
+    n_type: NotificationType,
+    message: Option<String>,
+    metadata: Option<serde_json::Value>
+) -> Result<(), HttpResponse> {
+    if recipient_id == sender_id {
+        return Ok(());
+    }
+
+    if !is_enabled(db, recipient_id, &n_type).await? {
+        return Ok(());
+    }
+
+    let notification = Notification {
+        uuid: Uuid::new_v4().to_string(),
+        recipient_id: recipient_id.to_string(),
+        sender_id: Some(sender_id.to_string()),
+        n_type,
+        message,
+        metadata,
+        read: false,
+        seen_at: None,
+        created_at: Utc::now().timestamp_millis(),
+    };
+
+    let collection = db.collection::<Notification>("notification");
+    let result = collection.insert_one(&notification).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(())
+}
+
+async fn is_enabled(
+    db: &Database,
+    recipient_id: &str,
+    n_type: &NotificationType
+) -> Result<bool, HttpResponse> {
+    let collection = db.collection::
+    <AccountNotificationSettings>("account_notification_setting");
+    let result = collection.find_one(doc!{"uuid": recipient_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let settings = match result.unwrap() {
+        Some(settings) => settings,
+        None => return Ok(true),
+    };
+
+    let enabled = match n_type {
+        NotificationType::Like |
+        NotificationType::Shared => settings.appreciation_notification,
+        NotificationType::Comment |
+        NotificationType::Replied => settings.comment_notification,
+        NotificationType::Tag => settings.tag_notification,
+        NotificationType::Follow => settings.following_notification,
+        NotificationType::FriendRequest |
+        NotificationType::FriendAccept => settings.friend_request_notification,
+        NotificationType::Mention |
+        NotificationType::SystemAlert => true,
+    };
+
+    Ok(enabled)
+}
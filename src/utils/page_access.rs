@@ -0,0 +1,63 @@
+use mongodb::Database;
+use mongodb::bson::doc;
+use actix_web::HttpResponse;
+use serde::{Deserialize, Serialize};
+use crate::utils::response::Response;
+use crate::model::Page::PageVisibility;
+
+/*
+    The fields of a `page` document that permission checks need.
+    Every handler deciding what a user may do on a page reads it through
+    here instead of keeping its own copy of the struct.
+*/
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PageStruct {
+    pub uuid: String,
+    pub owner: String,
+    pub admins: Vec<String>,
+
+    // Pages created before visibility and membership are public
+    #[serde(default)]
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub visibility: PageVisibility,
+}
+
+impl PageStruct {
+    /* Owner and admins manage the page and its posts */
+    pub fn is_manager(&self, user_id: &str) -> bool {
+        self.owner == user_id || self.admins.iter().any(|admin| admin == user_id)
+    }
+
+    /* Managers count as members, `Private` pages only show to members */
+    pub fn is_member(&self, user_id: &str) -> bool {
+        self.is_manager(user_id) ||
+        self.members.iter().any(|member| member == user_id)
+    }
+}
+
+pub async fn find(
+    db: &Database,
+    page_id: &str
+) -> Result<Option<PageStruct>, HttpResponse> {
+    let collection = db.collection::<PageStruct>("page");
+    let result = collection.find_one(doc!{"uuid": page_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(result.unwrap())
+}
+
+pub async fn can_manage(
+    db: &Database,
+    page_id: &str,
+    user_id: &str
+) -> Result<bool, HttpResponse> {
+    match find(db, page_id).await? {
+        Some(page) => Ok(page.is_manager(user_id)),
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,294 @@
+use std::env;
+use serde::Serialize;
+use serde_json::json;
+use actix_web::HttpResponse;
+use mongodb::{Collection, action::Find};
+use mongodb::bson::{doc, Document};
+use jsonwebtoken::{crypto, Algorithm, DecodingKey, EncodingKey};
+use crate::utils::response::Response;
+
+/* Upper bound of `limit` on every list endpoint */
+pub const MAX_LIMIT: u32 = 100;
+
+/* Field a list is ordered by, and the unique field breaking its ties */
+pub struct Keyset {
+    field: &'static str,
+    id: &'static str,
+    ascending: bool,
+}
+
+impl Keyset {
+    pub fn desc(field: &'static str, id: &'static str) -> Self {
+        Keyset { field, id, ascending: false }
+    }
+
+    pub fn asc(field: &'static str, id: &'static str) -> Self {
+        Keyset { field, id, ascending: true }
+    }
+
+    fn sort(&self) -> Document {
+        let order = if self.ascending { 1 } else { -1 };
+
+        let mut sort = Document::new();
+        sort.insert(self.field, order);
+        sort.insert(self.id, order);
+        sort
+    }
+
+    /* Items strictly after `cursor` in the list order */
+    fn after(&self, cursor: &Cursor) -> Document {
+        let operator = if self.ascending { "$gt" } else { "$lt" };
+
+        let mut past_at = Document::new();
+        past_at.insert(operator, cursor.at);
+
+        let mut past_id = Document::new();
+        past_id.insert(operator, &cursor.id);
+
+        let mut later = Document::new();
+        later.insert(self.field, past_at);
+
+        let mut tied = Document::new();
+        tied.insert(self.field, cursor.at);
+        tied.insert(self.id, past_id);
+
+        doc!{"$or": [later, tied]}
+    }
+}
+
+/* Sort value and unique id of the last item of a page */
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    pub at: i64,
+    pub id: String,
+}
+
+impl Cursor {
+    /*
+        Opaque to clients: the position is hex encoded and signed
+        together with the sort field, so a cursor can't be forged or
+        replayed against a list ordered by something else.
+    */
+    pub fn encode(&self, keyset: &Keyset, signing_key: &str) -> String {
+        let position: String = format!("{}:{}", self.at, self.id)
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+        let message = format!("{}.{}", keyset.field, position);
+        let key = EncodingKey::from_secret(signing_key.as_ref());
+        let signature = crypto::sign(message.as_bytes(), &key, Algorithm::HS256)
+        .unwrap();
+
+        format!("{}.{}", position, signature)
+    }
+
+    pub fn decode(
+        cursor: &str,
+        keyset: &Keyset,
+        signing_key: &str
+    ) -> Option<Cursor> {
+        let (position, signature) = cursor.split_once('.')?;
+
+        let message = format!("{}.{}", keyset.field, position);
+        let key = DecodingKey::from_secret(signing_key.as_ref());
+        let valid = crypto::verify(
+            signature,
+            message.as_bytes(),
+            &key,
+            Algorithm::HS256
+        ).ok()?;
+
+        if !valid || position.len() % 2 != 0 {
+            return None;
+        }
+
+        let mut bytes = Vec::new();
+        for i in (0..position.len()).step_by(2) {
+            bytes.push(u8::from_str_radix(position.get(i..i + 2)?, 16).ok()?);
+        }
+
+        let decoded = String::from_utf8(bytes).ok()?;
+        let (at, id) = decoded.split_once(':')?;
+
+        Some(Cursor { at: at.parse().ok()?, id: id.to_string() })
+    }
+}
+
+fn signing_key() -> Result<String, HttpResponse> {
+    match env::var("CURSOR_SIGNING_KEY") {
+        Ok(key) if key.len() > 0 => Ok(key),
+        _ => {
+            log::error!("CURSOR_SIGNING_KEY must be set on .env file");
+            Err(Response::internal_server_error("Cursor signing key is not set"))
+        }
+    }
+}
+
+/* Skip based parameters list endpoints accepted before cursors */
+pub enum Legacy {
+    Page(Option<u32>),
+    Offset(Option<u32>),
+}
+
+/* What a list answers with */
+pub enum Shape {
+    /* The items under a key with `next_cursor` and `has_more` */
+    Paged,
+    /*
+        The bare array endpoints older than cursors answered with, until
+        the client sends a `cursor` or asks for pages with `paginated=true`
+    */
+    Legacy(Option<bool>),
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageInfo {
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+/*
+    Keyset pagination shared by the list endpoints.
+    Clients walk a list with the `next_cursor` of the previous page.
+    `page` and `offset` still skip to a page until clients move to cursors.
+    The signing key is only needed by lists answering with cursors.
+*/
+pub struct Pagination {
+    keyset: Keyset,
+    signing_key: Option<String>,
+    limit: i64,
+    cursor: Option<Cursor>,
+    skip: Option<u64>,
+    paginated: bool,
+}
+
+impl Pagination {
+    pub fn new(
+        keyset: Keyset,
+        cursor: &Option<String>,
+        limit: u32,
+        legacy: Legacy,
+        shape: Shape
+    ) -> Result<Self, HttpResponse> {
+        let limit = limit.clamp(1, MAX_LIMIT) as u64;
+
+        let paginated = match shape {
+            Shape::Paged => true,
+            Shape::Legacy(paginated) => cursor.is_some() || paginated.unwrap_or(false),
+        };
+
+        let signing_key = match paginated {
+            true => Some(signing_key()?),
+            false => None,
+        };
+
+        let cursor = match (cursor, &signing_key) {
+            (Some(cursor), Some(signing_key)) => match Cursor::decode(
+                cursor,
+                &keyset,
+                signing_key
+            ) {
+                Some(cursor) => Some(cursor),
+                None => return Err(Response::bad_request("Invalid cursor")),
+            },
+            _ => None,
+        };
+
+        let skip = match (&cursor, legacy) {
+            (Some(_), _) => None,
+            (None, Legacy::Page(page)) => page
+            .map(|page| limit * page.saturating_sub(1) as u64),
+            (None, Legacy::Offset(offset)) => offset.map(|offset| offset as u64),
+        };
+
+        Ok(Pagination {
+            keyset,
+            signing_key,
+            limit: limit as i64,
+            cursor,
+            skip,
+            paginated,
+        })
+    }
+
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.cursor.as_ref()
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        !self.paginated
+    }
+
+    /* Narrows `filter` to the items after the cursor */
+    pub fn filter(&self, filter: Document) -> Document {
+        match &self.cursor {
+            Some(cursor) => doc!{"$and": [filter, self.keyset.after(cursor)]},
+            None => filter,
+        }
+    }
+
+    /*
+        Finds the page in `collection`, ordered by the keyset.
+        One extra item is fetched to tell whether there is a next page,
+        `finish` drops it.
+    */
+    pub fn find<'a, T: Send + Sync>(
+        &self,
+        collection: &'a Collection<T>,
+        filter: Document
+    ) -> Find<'a, T> {
+        collection.find(self.filter(filter))
+        .sort(self.keyset.sort())
+        .limit(self.limit + 1)
+        .skip(self.skip.unwrap_or(0))
+    }
+
+    /*
+        Trims `items` to the page and returns where the next one starts.
+        `position` gives the keyset values (sort value, id) of an item.
+    */
+    pub fn finish<T>(
+        &self,
+        items: &mut Vec<T>,
+        position: impl Fn(&T) -> (i64, String)
+    ) -> PageInfo {
+        let has_more = items.len() as i64 > self.limit;
+        items.truncate(self.limit as usize);
+
+        // Bare arrays don't carry a cursor, nothing to sign
+        let next_cursor = match (has_more, items.last(), &self.signing_key) {
+            (true, Some(last), Some(signing_key)) => {
+                let (at, id) = position(last);
+                Some(Cursor { at, id }.encode(&self.keyset, signing_key))
+            },
+            _ => None,
+        };
+
+        PageInfo { next_cursor, has_more }
+    }
+
+    /* The page under `key` with its `PageInfo`, or the bare legacy array */
+    pub fn response<T: Serialize>(
+        &self,
+        key: &str,
+        items: T,
+        page_info: PageInfo
+    ) -> HttpResponse {
+        if self.is_legacy() {
+            return HttpResponse::Ok()
+            .content_type("application/json")
+            .json(items);
+        }
+
+        let mut response = json!({
+            "next_cursor": page_info.next_cursor,
+            "has_more": page_info.has_more,
+        });
+        response[key] = serde_json::to_value(items).unwrap();
+
+        HttpResponse::Ok()
+        .content_type("application/json")
+        .json(response)
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashSet;
+use futures::StreamExt;
+use mongodb::Database;
+use mongodb::bson::doc;
+use actix_web::HttpResponse;
+use crate::utils::response::Response;
+use crate::model::Account::{
+    AccountBlocked,
+    AccountFollow,
+    AccountPrivacySettings,
+    Friends,
+    PrivacyAudience,
+};
+
+/*
+    Shared relationship and privacy checks.
+    Every handler that exposes a user's content, presence or inbox
+    must go through these instead of querying the collections itself.
+*/
+
+pub async fn get_settings(
+    db: &Database,
+    user_id: &str
+) -> Result<AccountPrivacySettings, HttpResponse> {
+    let collection = db.collection::
+    <AccountPrivacySettings>("account_privacy_setting");
+    let result = collection.find_one(doc!{"uuid": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    match result.unwrap() {
+        Some(settings) => Ok(settings),
+        None => Ok(AccountPrivacySettings::default_for(user_id)),
+    }
+}
+
+pub async fn is_friend(
+    db: &Database,
+    user_a: &str,
+    user_b: &str
+) -> Result<bool, HttpResponse> {
+    let collection = db.collection::<Friends>("friends");
+    let result = collection.count_documents(doc!{
+        "$or": [
+            {"requested_by": user_a, "accepted_by": user_b},
+            {"requested_by": user_b, "accepted_by": user_a},
+        ]
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(result.unwrap() > 0)
+}
+
+pub async fn is_following(
+    db: &Database,
+    follower_id: &str,
+    user_id: &str
+) -> Result<bool, HttpResponse> {
+    let collection = db.collection::<AccountFollow>("account_follow");
+    let result = collection.count_documents(doc!{
+        "followed_by": follower_id,
+        "user_id": user_id
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(result.unwrap() > 0)
+}
+
+/* True when either of the users has blocked the other */
+pub async fn is_blocked_between(
+    db: &Database,
+    user_a: &str,
+    user_b: &str
+) -> Result<bool, HttpResponse> {
+    let collection = db.collection::<AccountBlocked>("account_blocked");
+    let result = collection.count_documents(doc!{
+        "$or": [
+            {"blocked": user_a, "blocked_by": user_b},
+            {"blocked": user_b, "blocked_by": user_a},
+        ]
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    Ok(result.unwrap() > 0)
+}
+
+/* Ids of everyone `user_id` is friends with */
+pub async fn friend_ids(
+    db: &Database,
+    user_id: &str
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<Friends>("friends");
+    let result = collection.find(doc!{
+        "$or": [
+            {"requested_by": user_id},
+            {"accepted_by": user_id},
+        ]
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let friend = result.unwrap();
+        match friend.requested_by == user_id {
+            true => ids.push(friend.accepted_by),
+            false => ids.push(friend.requested_by),
+        }
+    }
+
+    Ok(ids)
+}
+
+/* Ids of the users `user_id` follows */
+pub async fn following_ids(
+    db: &Database,
+    user_id: &str
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<AccountFollow>("account_follow");
+    let result = collection.find(doc!{"followed_by": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        ids.push(result.unwrap().user_id);
+    }
+
+    Ok(ids)
+}
+
+/* Ids of the users following `user_id` */
+pub async fn follower_ids(
+    db: &Database,
+    user_id: &str
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<AccountFollow>("account_follow");
+    let result = collection.find(doc!{"user_id": user_id}).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        ids.push(result.unwrap().followed_by);
+    }
+
+    Ok(ids)
+}
+
+/* Ids of users that blocked `user_id` or were blocked by them */
+pub async fn blocked_ids(
+    db: &Database,
+    user_id: &str
+) -> Result<Vec<String>, HttpResponse> {
+    let collection = db.collection::<AccountBlocked>("account_blocked");
+    let result = collection.find(doc!{
+        "$or": [
+            {"blocked": user_id},
+            {"blocked_by": user_id},
+        ]
+    }).await;
+
+    if let Err(error) = result {
+        log::error!("{:?}", error);
+        return Err(Response::internal_server_error(&error.to_string()));
+    }
+
+    let mut ids = Vec::new();
+    let mut cursor = result.unwrap();
+    while let Some(result) = cursor.next().await {
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        let blocked = result.unwrap();
+        match blocked.blocked == user_id {
+            true => ids.push(blocked.blocked_by),
+            false => ids.push(blocked.blocked),
+        }
+    }
+
+    Ok(ids)
+}
+
+pub async fn audience_allows(
+    db: &Database,
+    owner_id: &str,
+    viewer_id: &str,
+    audience: &PrivacyAudience
+) -> Result<bool, HttpResponse> {
+    if owner_id == viewer_id {
+        return Ok(true);
+    }
+
+    match audience {
+        PrivacyAudience::Everyone => Ok(true),
+        PrivacyAudience::Nobody => Ok(false),
+        PrivacyAudience::Friends => is_friend(db, owner_id, viewer_id).await,
+        PrivacyAudience::FriendsAndFollowers => {
+            if is_friend(db, owner_id, viewer_id).await? {
+                return Ok(true);
+            }
+
+            is_following(db, viewer_id, owner_id).await
+        }
+    }
+}
+
+/*
+    Users `audience` lets see what `owner_id` shares, `None` when it's everyone.
+    Resolved once so live pushes can pick their recipients without a query each.
+*/
+pub async fn audience_members(
+    db: &Database,
+    owner_id: &str,
+    audience: &PrivacyAudience
+) -> Result<Option<HashSet<String>>, HttpResponse> {
+    match audience {
+        PrivacyAudience::Everyone => Ok(None),
+        PrivacyAudience::Nobody => Ok(Some(HashSet::new())),
+        PrivacyAudience::Friends => Ok(Some(
+            friend_ids(db, owner_id).await?.into_iter().collect()
+        )),
+        PrivacyAudience::FriendsAndFollowers => {
+            let mut members: HashSet<String> = friend_ids(db, owner_id).await?
+            .into_iter()
+            .collect();
+
+            members.extend(follower_ids(db, owner_id).await?);
+            Ok(Some(members))
+        }
+    }
+}
+
+/*
+    Whether the viewer may see the owner's posts and activity.
+    Private accounts only expose them to friends and approved followers.
+*/
+pub async fn can_view_content(
+    db: &Database,
+    owner_id: &str,
+    viewer_id: &str
+) -> Result<bool, HttpResponse> {
+    if owner_id == viewer_id {
+        return Ok(true);
+    }
+
+    if is_blocked_between(db, owner_id, viewer_id).await? {
+        return Ok(false);
+    }
+
+    let settings = get_settings(db, owner_id).await?;
+    if !settings.private_account {
+        return Ok(true);
+    }
+
+    audience_allows(
+        db,
+        owner_id,
+        viewer_id,
+        &PrivacyAudience::FriendsAndFollowers
+    ).await
+}
+
+pub async fn can_message(
+    db: &Database,
+    target_id: &str,
+    sender_id: &str
+) -> Result<bool, HttpResponse> {
+    if is_blocked_between(db, target_id, sender_id).await? {
+        return Ok(false);
+    }
+
+    let settings = get_settings(db, target_id).await?;
+    audience_allows(db, target_id, sender_id, &settings.who_can_message).await
+}
+
+pub async fn can_add_to_group(
+    db: &Database,
+    target_id: &str,
+    adder_id: &str
+) -> Result<bool, HttpResponse> {
+    if is_blocked_between(db, target_id, adder_id).await? {
+        return Ok(false);
+    }
+
+    let settings = get_settings(db, target_id).await?;
+    audience_allows(
+        db,
+        target_id,
+        adder_id,
+        &settings.who_can_add_to_group
+    ).await
+}
+
+pub async fn can_mention(
+    db: &Database,
+    target_id: &str,
+    author_id: &str
+) -> Result<bool, HttpResponse> {
+    if is_blocked_between(db, target_id, author_id).await? {
+        return Ok(false);
+    }
+
+    let settings = get_settings(db, target_id).await?;
+    audience_allows(db, target_id, author_id, &settings.who_can_mention).await
+}
+
+/*
+    Online flag and last seen of `target_id` as the viewer is allowed to see them.
+    Hidden values come back as `false` / `None`.
+*/
+pub async fn visible_presence(
+    db: &Database,
+    target_id: &str,
+    viewer_id: &str,
+    online: bool,
+    last_seen: i64
+) -> Result<(bool, Option<i64>), HttpResponse> {
+    let settings = get_settings(db, target_id).await?;
+
+    let show_online = audience_allows(
+        db,
+        target_id,
+        viewer_id,
+        &settings.online_status_visibility
+    ).await?;
+
+    let show_last_seen = audience_allows(
+        db,
+        target_id,
+        viewer_id,
+        &settings.last_seen_visibility
+    ).await?;
+
+    Ok((
+        show_online && online,
+        match show_last_seen {
+            true => Some(last_seen),
+            false => None,
+        }
+    ))
+}
@@ -0,0 +1,23 @@
+use std::cell::Cell;
+use std::future::Future;
+
+tokio::task_local! {
+    static QUERIES: Cell<u64>;
+}
+
+/*
+    Counts the database commands a request sends, for query benchmarks.
+    The MongoDB client reports every started command through `record`,
+    which only counts inside a `count` scope of the same task.
+*/
+pub fn record() {
+    let _ = QUERIES.try_with(|queries| queries.set(queries.get() + 1));
+}
+
+/* Runs `future` and returns its output with the commands it sent */
+pub async fn count<F: Future>(future: F) -> (F::Output, u64) {
+    QUERIES.scope(Cell::new(0), async {
+        let output = future.await;
+        (output, QUERIES.with(|queries| queries.get()))
+    }).await
+}
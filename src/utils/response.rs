@@ -43,7 +43,7 @@ impl Response {
     )
   }
 
-  pub fn _gone(message: &str) -> HttpResponse {
+  pub fn gone(message: &str) -> HttpResponse {
     HttpResponse::Gone().content_type("application/json").json(
       Response { message: message.to_string() }
     )
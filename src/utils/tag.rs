@@ -0,0 +1,114 @@
+/* Longest tag kept, in characters */
+pub const MAX_TAG_LENGTH: usize = 50;
+
+/* Most tags a single post can carry */
+pub const MAX_TAGS_PER_POST: usize = 30;
+
+/*
+    Canonical form of a tag: no leading '#', lowercase, letters, digits
+    and '_' only. Tags made of digits alone (`#1`) aren't tags.
+*/
+pub fn normalize(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+        return None;
+    }
+
+    if !tag.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    if tag.chars().all(|c| c.is_numeric()) {
+        return None;
+    }
+
+    Some(tag)
+}
+
+/* Start of a tag typed so far, used for autocomplete */
+pub fn normalize_prefix(prefix: &str) -> Option<String> {
+    let prefix = prefix.trim().trim_start_matches('#').to_lowercase();
+
+    if prefix.is_empty() || prefix.chars().count() > MAX_TAG_LENGTH {
+        return None;
+    }
+
+    if !prefix.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    Some(prefix)
+}
+
+/* Every `#tag` written in the caption, normalized and in order */
+pub fn extract(caption: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let chars: Vec<char> = caption.chars().collect();
+
+    let mut i = 0;
+    while i < chars.len() {
+        // A '#' glued to a word (`abc#def`) doesn't start a tag
+        let starts_tag = chars[i] == '#' &&
+        (i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '_'));
+
+        if !starts_tag {
+            i += 1;
+            continue;
+        }
+
+        let start = i + 1;
+        let mut end = start;
+        while end < chars.len() &&
+        (chars[end].is_alphanumeric() || chars[end] == '_') {
+            end += 1;
+        }
+
+        let word: String = chars[start..end].iter().collect();
+        if let Some(tag) = normalize(&word) {
+            tags.push(tag);
+        }
+
+        i = end.max(start);
+    }
+
+    tags
+}
+
+/*
+    Tags of a post: the explicit ones followed by the ones in the caption,
+    normalized and deduplicated. Errors name the first invalid tag.
+*/
+pub fn collect(
+    explicit: &Vec<String>,
+    caption: &Option<String>
+) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for tag in explicit {
+        match normalize(tag) {
+            Some(tag) => {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            },
+            None => return Err(format!("Invalid tag '{}'", tag)),
+        }
+    }
+
+    if let Some(caption) = caption {
+        for tag in extract(caption) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+
+    if tags.len() > MAX_TAGS_PER_POST {
+        return Err(format!(
+            "A post can't have more than {} tags", MAX_TAGS_PER_POST
+        ));
+    }
+
+    Ok(tags)
+}
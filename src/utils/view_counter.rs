@@ -0,0 +1,98 @@
+use std::env;
+use std::sync::Mutex;
+use chrono::Utc;
+use std::collections::{HashMap, VecDeque};
+
+/* Upper bound of remembered (viewer, post) pairs */
+const MAX_TRACKED_VIEWS: usize = 200_000;
+
+/*
+    In-memory stand-in for a Redis backed view deduplicator.
+    A viewer counts once per post within the dedup window, the oldest
+    pairs are evicted first once the tracker is full.
+    Counted views are buffered until the cron flushes them to `post_stat`.
+*/
+pub struct ViewCounter {
+    window_ms: i64,
+    state: Mutex<ViewCounterState>,
+}
+
+#[derive(Default)]
+struct ViewCounterState {
+    last_seen: HashMap<(String, String), i64>,
+    order: VecDeque<((String, String), i64)>,
+    pending: HashMap<String, i64>,
+}
+
+impl Default for ViewCounter {
+    fn default() -> Self {
+        let minutes = env::var("VIEW_DEDUP_WINDOW_MINUTES").ok()
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(30);
+
+        ViewCounter {
+            window_ms: minutes * 60_000,
+            state: Mutex::new(ViewCounterState::default()),
+        }
+    }
+}
+
+impl ViewCounter {
+    /* Records the impressions and returns how many of them counted */
+    pub fn record(&self, viewer_id: &str, post_ids: &Vec<String>) -> usize {
+        let now = Utc::now().timestamp_millis();
+        let mut state = self.state.lock().unwrap();
+
+        self.evict(&mut state, now);
+
+        let mut counted = 0;
+        for post_id in post_ids {
+            let key = (viewer_id.to_string(), post_id.clone());
+
+            if let Some(seen_at) = state.last_seen.get(&key) {
+                if now - seen_at < self.window_ms {
+                    continue;
+                }
+            }
+
+            state.last_seen.insert(key.clone(), now);
+            state.order.push_back((key, now));
+            *state.pending.entry(post_id.clone()).or_insert(0) += 1;
+            counted += 1;
+        }
+
+        counted
+    }
+
+    /* Hands over the buffered counts, leaving the buffer empty */
+    pub fn take_pending(&self) -> HashMap<String, i64> {
+        let mut state = self.state.lock().unwrap();
+        std::mem::take(&mut state.pending)
+    }
+
+    /* Puts counts back after a failed flush so they aren't lost */
+    pub fn restore_pending(&self, pending: HashMap<String, i64>) {
+        let mut state = self.state.lock().unwrap();
+        for (post_id, count) in pending {
+            *state.pending.entry(post_id).or_insert(0) += count;
+        }
+    }
+
+    fn evict(&self, state: &mut ViewCounterState, now: i64) {
+        while let Some((key, seen_at)) = state.order.front().cloned() {
+            let expired = now - seen_at >= self.window_ms;
+            let over_capacity = state.last_seen.len() > MAX_TRACKED_VIEWS;
+
+            if !expired && !over_capacity {
+                break;
+            }
+
+            state.order.pop_front();
+
+            // Only drop the pair if it wasn't seen again since
+            if state.last_seen.get(&key) == Some(&seen_at) {
+                state.last_seen.remove(&key);
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use mongodb::Database;
+use mongodb::bson::doc;
+use actix_web::HttpResponse;
+use crate::utils::{page_access, privacy};
+use crate::utils::response::Response;
+use crate::model::{
+    Page::PageVisibility,
+    Post::{PostCore, PostOwnerType, PostPublishState, PostVisibility},
+};
+
+/*
+    Single place deciding whether a viewer may read a post.
+    Every post read path (post, comment and reply reads, feeds) goes
+    through `Viewer::can_view_post` so the rules can't drift apart.
+
+    Drafts and scheduled posts are only visible to the user who wrote
+    them, page drafts only through the page's draft list.
+
+    Rules for user owned posts:
+    - the owner always sees their post
+    - deleted and suspended posts are hidden from everyone else
+    - blocks in either direction hide the post
+    - private accounts only expose posts to friends and followers
+    - `Public` is open, `FriendAndFollowers` needs friendship or a follow
+      and `FieldsOnly` needs friendship
+
+    Page owned posts follow the page visibility, `Private` pages only
+    show posts to their owner, admins and members. `Unlisted` pages
+    aren't listed but their posts stay readable. The page's owner and
+    admins always see them, users blocked with the owner never do.
+*/
+pub struct Viewer {
+    pub user_id: String,
+    friends: Vec<String>,
+    following: Vec<String>,
+    blocked: Vec<String>,
+    private_accounts: HashMap<String, bool>,
+    page_access: HashMap<String, bool>,
+}
+
+/* States of posts that aren't out yet, for `$nin` filters on `post_core` */
+pub fn unpublished_states() -> Vec<String> {
+    vec![
+        PostPublishState::Draft.to_string(),
+        PostPublishState::Scheduled.to_string(),
+    ]
+}
+
+impl Viewer {
+    pub async fn load(
+        db: &Database,
+        user_id: &str
+    ) -> Result<Self, HttpResponse> {
+        Ok(Viewer {
+            user_id: user_id.to_string(),
+            friends: privacy::friend_ids(db, user_id).await?,
+            following: privacy::following_ids(db, user_id).await?,
+            blocked: privacy::blocked_ids(db, user_id).await?,
+            private_accounts: HashMap::new(),
+            page_access: HashMap::new(),
+        })
+    }
+
+    pub fn friends(&self) -> &Vec<String> {
+        &self.friends
+    }
+
+    pub fn following(&self) -> &Vec<String> {
+        &self.following
+    }
+
+    pub fn blocked(&self) -> &Vec<String> {
+        &self.blocked
+    }
+
+    pub fn is_friend(&self, user_id: &str) -> bool {
+        self.friends.iter().any(|id| id == user_id)
+    }
+
+    pub fn is_following(&self, user_id: &str) -> bool {
+        self.following.iter().any(|id| id == user_id)
+    }
+
+    pub fn is_blocked(&self, user_id: &str) -> bool {
+        self.blocked.iter().any(|id| id == user_id)
+    }
+
+    pub async fn can_view_post(
+        &mut self,
+        db: &Database,
+        post_core: &PostCore
+    ) -> Result<bool, HttpResponse> {
+        if post_core.publish_state != PostPublishState::Published {
+            return Ok(
+                matches!(post_core.owner_type, PostOwnerType::User) &&
+                post_core.owner == self.user_id
+            );
+        }
+
+        match post_core.owner_type {
+            PostOwnerType::User => self.can_view_user_post(db, post_core).await,
+            PostOwnerType::Page => self.can_view_page_post(db, post_core).await,
+        }
+    }
+
+    /* Loads the post and checks it, used by comment and reply reads */
+    pub async fn can_view_post_id(
+        &mut self,
+        db: &Database,
+        post_id: &str
+    ) -> Result<bool, HttpResponse> {
+        let collection = db.collection::<PostCore>("post_core");
+        let result = collection.find_one(doc!{"uuid": post_id}).await;
+
+        if let Err(error) = result {
+            log::error!("{:?}", error);
+            return Err(Response::internal_server_error(&error.to_string()));
+        }
+
+        match result.unwrap() {
+            Some(post_core) => self.can_view_post(db, &post_core).await,
+            None => Ok(false),
+        }
+    }
+
+    async fn can_view_user_post(
+        &mut self,
+        db: &Database,
+        post_core: &PostCore
+    ) -> Result<bool, HttpResponse> {
+        let owner = post_core.owner.as_str();
+
+        if owner == self.user_id {
+            return Ok(true);
+        }
+
+        if post_core.deleted_at.is_some() || post_core.suspended_at.is_some() {
+            return Ok(false);
+        }
+
+        if self.is_blocked(owner) {
+            return Ok(false);
+        }
+
+        let is_friend = self.is_friend(owner);
+        let is_following = self.is_following(owner);
+
+        if !is_friend && !is_following && self.is_private(db, owner).await? {
+            return Ok(false);
+        }
+
+        Ok(match post_core.visibility {
+            PostVisibility::Public => true,
+            PostVisibility::FriendAndFollowers => is_friend || is_following,
+            PostVisibility::FieldsOnly => is_friend,
+        })
+    }
+
+    async fn can_view_page_post(
+        &mut self,
+        db: &Database,
+        post_core: &PostCore
+    ) -> Result<bool, HttpResponse> {
+        let page_id = post_core.owner.as_str();
+
+        let allowed = match self.page_access.get(page_id) {
+            Some(allowed) => *allowed,
+            None => {
+                let allowed = self.page_allows(db, page_id).await?;
+                self.page_access.insert(page_id.to_string(), allowed);
+                allowed
+            }
+        };
+
+        if !allowed {
+            return Ok(false);
+        }
+
+        Ok(post_core.deleted_at.is_none() && post_core.suspended_at.is_none())
+    }
+
+    async fn is_private(
+        &mut self,
+        db: &Database,
+        user_id: &str
+    ) -> Result<bool, HttpResponse> {
+        if let Some(private) = self.private_accounts.get(user_id) {
+            return Ok(*private);
+        }
+
+        let settings = privacy::get_settings(db, user_id).await?;
+        self.private_accounts.insert(
+            user_id.to_string(),
+            settings.private_account
+        );
+
+        Ok(settings.private_account)
+    }
+
+    async fn page_allows(
+        &self,
+        db: &Database,
+        page_id: &str
+    ) -> Result<bool, HttpResponse> {
+        let page = match page_access::find(db, page_id).await? {
+            Some(page) => page,
+            None => return Ok(false),
+        };
+
+        if page.is_manager(&self.user_id) {
+            return Ok(true);
+        }
+
+        if self.is_blocked(&page.owner) {
+            return Ok(false);
+        }
+
+        match page.visibility {
+            PageVisibility::Public | PageVisibility::Unlisted => Ok(true),
+            PageVisibility::Private => Ok(page.is_member(&self.user_id)),
+        }
+    }
+}
\ No newline at end of file